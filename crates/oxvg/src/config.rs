@@ -1,8 +1,122 @@
-use oxvg_ast::implementations::markup5ever::Element5Ever;
+use oxvg_ast::{
+    implementations::arena::ElementArena,
+    parse,
+    serialize::{LineEnding, Options, Quote},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
-    pub optimise: Option<oxvg_optimiser::Jobs<Element5Ever>>,
+    pub optimise: Option<oxvg_optimiser::Jobs<ElementArena>>,
+    /// The limits documents are parsed within
+    pub parse: Option<ParseConfig>,
+    /// How optimised documents are written
+    pub serialize: Option<SerializeConfig>,
+}
+
+/// The limits documents are parsed within
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ParseConfig {
+    /// Whether documents are from an untrusted source, such as user uploads, and should be parsed
+    /// within the limits of [`parse::Options::untrusted`]
+    pub untrusted: bool,
+}
+
+/// How documents are written, similar to SVGO's `js2svg` options
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct SerializeConfig {
+    /// Whether to place each node on it's own line
+    pub pretty: bool,
+    /// The number of spaces to indent by when pretty printing
+    pub indent: usize,
+    /// The quote used for attribute values
+    pub quotes: QuoteConfig,
+    /// Whether to write elements without children as `<x/>` instead of `<x></x>`
+    pub use_short_tags: bool,
+    /// Whether to end the document with a line ending
+    pub final_newline: bool,
+    /// The line ending to use
+    pub eol: EolConfig,
+    /// When pretty printing, the width at which a start tag's attributes are placed on their own
+    /// lines
+    pub line_width: Option<usize>,
+    /// Whether to add an xml declaration when the document doesn't have one
+    pub xml_declaration: bool,
+}
+
+/// The quote used for attribute values
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum QuoteConfig {
+    #[default]
+    /// `"`
+    Double,
+    /// `'`
+    Single,
+}
+
+/// The line ending to use
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum EolConfig {
+    #[default]
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    Crlf,
+}
+
+impl ParseConfig {
+    /// Creates the parser options for the config
+    pub fn options(&self) -> parse::Options {
+        if self.untrusted {
+            parse::Options::untrusted()
+        } else {
+            parse::Options::default()
+        }
+    }
+}
+
+impl Default for SerializeConfig {
+    fn default() -> Self {
+        Self {
+            pretty: false,
+            indent: 4,
+            quotes: QuoteConfig::default(),
+            use_short_tags: true,
+            final_newline: false,
+            eol: EolConfig::default(),
+            line_width: None,
+            xml_declaration: false,
+        }
+    }
+}
+
+impl SerializeConfig {
+    /// Creates the serializer options for the config
+    pub fn options(&self) -> Options {
+        let options = Options::new()
+            .indent(self.indent)
+            .quote(match self.quotes {
+                QuoteConfig::Double => Quote::Double,
+                QuoteConfig::Single => Quote::Single,
+            })
+            .self_closing(self.use_short_tags)
+            .final_newline(self.final_newline)
+            .eol(match self.eol {
+                EolConfig::Lf => LineEnding::Lf,
+                EolConfig::Crlf => LineEnding::Crlf,
+            })
+            .line_width(self.line_width)
+            .xml_declaration(self.xml_declaration);
+        if self.pretty {
+            options.pretty()
+        } else {
+            options
+        }
+    }
 }
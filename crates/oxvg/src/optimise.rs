@@ -1,5 +1,4 @@
 use std::{
-    cell::RefCell,
     ffi::OsStr,
     io::{IsTerminal, Read, Write},
     path::PathBuf,
@@ -9,7 +8,8 @@ use std::{
 use anyhow::anyhow;
 use ignore::{WalkBuilder, WalkState};
 use oxvg_ast::{
-    implementations::markup5ever::{Element5Ever, Node5Ever},
+    implementations::arena::{ElementArena, NodeArena},
+    parse, serialize,
     visitor::Info,
 };
 use oxvg_optimiser::Jobs;
@@ -39,6 +39,10 @@ pub struct Optimise {
     /// Sets the approximate number of threads to use. A value of 0 (default) will automatically determine the appropriate number
     #[clap(long, short, default_value = "0")]
     pub threads: usize,
+    /// Parse the inputs within limits suitable for untrusted sources, such as user uploads,
+    /// rather than the config's `parse` options
+    #[clap(long, default_value = "false")]
+    pub untrusted: bool,
 }
 
 impl RunCommand for Optimise {
@@ -47,27 +51,43 @@ impl RunCommand for Optimise {
         let Some(config) = config else {
             return Ok(());
         };
-        if let Some(jobs) = config.optimise {
-            LOADED_JOBS.set(jobs);
-        }
+        let jobs = config.optimise.unwrap_or_default();
+        let parse = if self.untrusted {
+            parse::Options::untrusted()
+        } else {
+            config.parse.unwrap_or_default().options()
+        };
+        let serialize = jobs.serialize_options(config.serialize.unwrap_or_default().options());
 
-        self.handle_paths()
+        self.handle_paths(&jobs, &parse, &serialize)
     }
 }
 
 impl Optimise {
-    fn handle_out<W: Write>(dom: &Node5Ever, wr: W) -> anyhow::Result<()> {
+    fn handle_out<W: Write>(
+        dom: &NodeArena,
+        wr: W,
+        serialize: &serialize::Options,
+    ) -> anyhow::Result<()> {
         use oxvg_ast::serialize::Node;
 
-        dom.serialize_into(wr)
+        let mut wr = std::io::BufWriter::new(wr);
+        dom.serialize_into_with_options(&mut wr, serialize.clone())?;
+        wr.flush()?;
+        Ok(())
     }
 
-    fn handle_stdin(&self, jobs: Jobs<Element5Ever>) -> anyhow::Result<()> {
+    fn handle_stdin(
+        &self,
+        jobs: &Jobs<ElementArena>,
+        parse: &parse::Options,
+        serialize: &serialize::Options,
+    ) -> anyhow::Result<()> {
         use oxvg_ast::parse::Node;
 
         let mut string = String::new();
         std::io::stdin().read_to_string(&mut string)?;
-        let dom = Node5Ever::parse(&string)?;
+        let dom = NodeArena::parse_with_options(&string, parse)?;
 
         let info = Info {
             path: None,
@@ -84,41 +104,43 @@ impl Optimise {
                 eprintln!(
                     "Cannot use dir as output for stdin input. Printing result to stdout instead"
                 );
-                Self::handle_out(&dom, std::io::stdout())?;
+                Self::handle_out(&dom, std::io::stdout(), serialize)?;
             } else {
-                Self::handle_out(&dom, file)?;
+                Self::handle_out(&dom, file, serialize)?;
             }
         } else {
-            Self::handle_out(&dom, std::io::stdout())?;
+            Self::handle_out(&dom, std::io::stdout(), serialize)?;
         }
 
         Ok(())
     }
 
     fn handle_file(
-        jobs: &Jobs<Element5Ever>,
+        jobs: &Jobs<ElementArena>,
         path: &PathBuf,
         output: Option<&PathBuf>,
+        parse: &parse::Options,
+        serialize: &serialize::Options,
     ) -> anyhow::Result<()> {
         use oxvg_ast::parse::Node;
 
         let file = std::fs::File::open(path)?;
         let input_size = file.metadata()?.len() as f64 / 1000.0;
-        let dom = Node5Ever::parse_file(&file)?;
+        let dom = NodeArena::parse_file_with_options(&file, parse)?;
         drop(file);
 
         let info = Info {
             path: Some(path.clone()),
             multipass_count: 0,
         };
-        jobs.clone().run(&dom, &info)?;
+        jobs.run(&dom, &info)?;
 
         if let Some(output_path) = output {
             if let Some(parent) = output_path.parent() {
                 std::fs::create_dir_all(parent)?;
             }
             let file = std::fs::File::create(output_path)?;
-            Self::handle_out(&dom, file)?;
+            Self::handle_out(&dom, file, serialize)?;
 
             let output_size = output_path.metadata()?.len() as f64 / 1000.0;
             let change = 100.0 * (input_size - output_size) / input_size;
@@ -130,11 +152,17 @@ impl Optimise {
         } else {
             // Print to stderr, so that stdout is clean for writing
             eprintln!("\n\n\x1b[32m{}\x1b[0m", path.to_string_lossy());
-            Self::handle_out(&dom, std::io::stdout())
+            Self::handle_out(&dom, std::io::stdout(), serialize)
         }
     }
 
-    fn handle_path(&self, path: &PathBuf) {
+    fn handle_path(
+        &self,
+        jobs: &Jobs<ElementArena>,
+        path: &PathBuf,
+        parse: &parse::Options,
+        serialize: &serialize::Options,
+    ) {
         let output_path = |input: &PathBuf| {
             let Some(output) = self.output.as_ref() else {
                 return Ok(None);
@@ -151,7 +179,6 @@ impl Optimise {
             .build_parallel()
             .run(|| {
                 Box::new(move |path| {
-                    let jobs = LOADED_JOBS.with_borrow(Clone::clone);
                     let Ok(path) = path else {
                         return WalkState::Continue;
                     };
@@ -165,7 +192,9 @@ impl Optimise {
                     let Ok(output_path) = output_path(&path) else {
                         return WalkState::Continue;
                     };
-                    if let Err(err) = Self::handle_file(&jobs, &path, output_path.as_ref()) {
+                    if let Err(err) =
+                        Self::handle_file(jobs, &path, output_path.as_ref(), parse, serialize)
+                    {
                         eprintln!("{err}");
                     };
                     WalkState::Continue
@@ -173,7 +202,12 @@ impl Optimise {
             });
     }
 
-    fn handle_paths(&self) -> anyhow::Result<()> {
+    fn handle_paths(
+        &self,
+        jobs: &Jobs<ElementArena>,
+        parse: &parse::Options,
+        serialize: &serialize::Options,
+    ) -> anyhow::Result<()> {
         if !std::io::stdin().is_terminal()
             && self.paths.len() <= 1
             && self
@@ -181,7 +215,7 @@ impl Optimise {
                 .first()
                 .is_none_or(|path| path == &PathBuf::from_str(".").unwrap())
         {
-            return LOADED_JOBS.with(|jobs| self.handle_stdin(jobs.take()));
+            return self.handle_stdin(jobs, parse, serialize);
         }
         if self.paths.is_empty() {
             return Err(anyhow!(
@@ -190,7 +224,7 @@ impl Optimise {
         }
 
         for path in &self.paths {
-            self.handle_path(path);
+            self.handle_path(jobs, path, parse, serialize);
         }
         Ok(())
     }
@@ -209,6 +243,8 @@ impl Optimise {
                     std::io::stdout(),
                     &Config {
                         optimise: Some(config.optimise.unwrap_or_default()),
+                        parse: Some(config.parse.unwrap_or_default()),
+                        serialize: Some(config.serialize.unwrap_or_default()),
                     },
                 )?;
                 Ok(None)
@@ -219,7 +255,3 @@ impl Optimise {
         }
     }
 }
-
-thread_local! {
-    static LOADED_JOBS: RefCell<Jobs<Element5Ever>> = RefCell::new(Jobs::default());
-}
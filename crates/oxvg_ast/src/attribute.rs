@@ -152,6 +152,11 @@ pub trait Attributes<'a>: Debug + Clone {
     /// Returns an new iterator that mutably goes over each attribute in the collection.
     fn into_iter_mut(self) -> AttributesIterMut<'a, Self>;
 
+    /// Sorts the attributes in place, matching the order of SVGO's `sortAttrs`.
+    ///
+    /// When `xmlns_front` is set, `xmlns` is placed first followed by other namespace
+    /// declarations. Prefixed attributes come next, then attributes whose name (up to the first
+    /// `-`) is found in `order`, and the remaining attributes are sorted alphabetically.
     fn sort(&self, order: &[String], xmlns_front: bool);
 
     fn retain<F>(&self, f: F)
@@ -159,6 +164,61 @@ pub trait Attributes<'a>: Debug + Clone {
         F: FnMut(&Self::Attribute) -> bool;
 }
 
+/// Sorts a list of attributes in place, as described by [`Attributes::sort`]
+pub(crate) fn sort<A: Attr>(attrs: &mut Vec<A>, order: &[String], xmlns_front: bool) {
+    fn get_ns_priority<N: Name>(name: &N, xmlns_front: bool) -> usize {
+        if xmlns_front {
+            if name.prefix().is_none() && name.local_name().as_ref() == "xmlns" {
+                return 3;
+            }
+            if name
+                .prefix()
+                .as_ref()
+                .is_some_and(|p| p.as_ref() == "xmlns")
+            {
+                return 2;
+            }
+        }
+        if name.prefix().is_some() {
+            return 1;
+        }
+        0
+    }
+
+    // NOTE: Names are compared as written, e.g. `xlink:href`, for parity with SVGO
+    let mut named_attrs: Vec<_> = attrs
+        .drain(..)
+        .map(|attr| (attr.name().formatter().to_string(), attr))
+        .collect();
+    named_attrs.sort_by(|(a_name, a), (b_name, b)| {
+        let a_priority = get_ns_priority(a.name(), xmlns_front);
+        let b_priority = get_ns_priority(b.name(), xmlns_front);
+        let priority_ord = b_priority.cmp(&a_priority);
+        if priority_ord != std::cmp::Ordering::Equal {
+            return priority_ord;
+        }
+
+        let a_part = a_name.split_once('-').map_or(a_name.as_str(), |p| p.0);
+        let b_part = b_name.split_once('-').map_or(b_name.as_str(), |p| p.0);
+        if a_part != b_part {
+            let a_in_order = order.iter().position(|x| x == a_part);
+            let b_in_order = order.iter().position(|x| x == b_part);
+            if a_in_order.is_some() && b_in_order.is_some() {
+                return a_in_order.cmp(&b_in_order);
+            }
+            if a_in_order.is_some() {
+                return std::cmp::Ordering::Less;
+            }
+            if b_in_order.is_some() {
+                return std::cmp::Ordering::Greater;
+            }
+        }
+
+        a_name.cmp(b_name)
+    });
+    attrs.extend(named_attrs.into_iter().map(|(_, attr)| attr));
+}
+
 macro_rules! define_attrs_iter {
     ($name:ident$((ref $deref:ident))?$((mut $derefmut:ident))?) => {
         pub struct $name<'a, A: Attributes<'a>> {
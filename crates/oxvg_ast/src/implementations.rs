@@ -1,2 +1,7 @@
 #[cfg(feature = "markup5ever")]
+/// A DOM of reference counted nodes, using the names and atoms of markup5ever
 pub mod markup5ever;
+
+#[cfg(feature = "arena")]
+/// A DOM of nodes allocated within an arena, which can be shared across threads
+pub mod arena;
@@ -0,0 +1,2204 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::{ControlFlow, Deref},
+    sync::Arc,
+};
+
+use markup5ever::{local_name, LocalName, Namespace, QualName};
+use parking_lot::{MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock, RwLockReadGuard};
+use string_cache::DefaultAtom;
+
+use crate::{
+    attribute::{Attr, Attributes, AttributesIter, AttributesIterMut},
+    class_list::ClassList,
+    document::Document,
+    element::{self, Element},
+    name::Name,
+    node::{self, Node},
+};
+
+#[cfg(feature = "parse")]
+use super::markup5ever::ReaderContext;
+#[cfg(feature = "parse")]
+use crate::parse;
+
+#[cfg(feature = "serialize")]
+use crate::serialize;
+
+/// The atom used for the text and attribute values of the arena, which are interned so that
+/// repeated values share the same allocation.
+pub type Atom = DefaultAtom;
+
+/// An attribute of an [`ElementArena`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeArena {
+    /// The qualified name of the attribute
+    pub name: QualName,
+    /// The value of the attribute
+    pub value: Atom,
+}
+
+/// The attributes of an [`ElementArena`], borrowed from the element's node
+#[derive(Clone)]
+pub struct AttributesArena<'a>(&'a RwLock<Vec<AttributeArena>>);
+
+/// The tokens of an [`ElementArena`]'s `class` attribute
+pub struct ClassListArena<'a> {
+    attrs: AttributesArena<'a>,
+    tokens: Vec<Atom>,
+}
+
+/// The storage of a document's nodes.
+///
+/// Parsed nodes are allocated together and each node is linked to it's family by index, so
+/// building and dropping a tree doesn't require an allocation per node. Nodes that are removed
+/// from the tree stay in the arena until the arena itself is dropped.
+pub struct Arena {
+    entries: RwLock<Vec<Entry>>,
+}
+
+/// A reference to a node within an [Arena].
+///
+/// Unlike `Node5Ever`, the tree is behind a lock rather than a `RefCell`, so nodes are `Send` and
+/// `Sync`.
+#[derive(Clone)]
+pub struct NodeArena {
+    arena: Arc<Arena>,
+    id: usize,
+    data: Slot,
+}
+
+/// A type-erased reference to a [`NodeArena`]
+#[derive(Debug)]
+pub struct NodeArenaRef(NodeArena);
+
+/// A [`NodeArena`] that's known to be an element
+#[derive(Clone)]
+pub struct ElementArena {
+    node: NodeArena,
+}
+
+/// The root element of a document within an [Arena], used for creating new nodes in the arena
+pub struct DocumentArena(ElementArena);
+
+enum NodeData {
+    Document,
+    Doctype {
+        name: Atom,
+        public_id: Atom,
+        system_id: Atom,
+    },
+    Element {
+        name: QualName,
+        attrs: RwLock<Vec<AttributeArena>>,
+    },
+    Text {
+        contents: RwLock<Atom>,
+    },
+    CDataSection {
+        contents: RwLock<Atom>,
+    },
+    Comment {
+        contents: Atom,
+    },
+    ProcessingInstruction {
+        target: Atom,
+        contents: Atom,
+    },
+}
+
+/// The data of a node, within the chunk it was allocated with
+#[derive(Clone)]
+struct Slot {
+    chunk: Arc<[NodeData]>,
+    index: usize,
+}
+
+/// The index of each node related to a node
+#[derive(Clone, Copy, Default)]
+struct Links {
+    parent: Option<usize>,
+    first_child: Option<usize>,
+    last_child: Option<usize>,
+    previous_sibling: Option<usize>,
+    next_sibling: Option<usize>,
+}
+
+struct Entry {
+    links: Links,
+    data: Slot,
+    #[cfg(feature = "selectors")]
+    selector_flags: selectors::matching::ElementSelectorFlags,
+}
+
+impl Attr for AttributeArena {
+    type Atom = Atom;
+    type Name = QualName;
+
+    fn new(name: Self::Name, value: Self::Atom) -> Self {
+        Self { name, value }
+    }
+
+    fn name(&self) -> &Self::Name {
+        &self.name
+    }
+
+    fn name_mut(&mut self) -> &mut Self::Name {
+        &mut self.name
+    }
+
+    fn value(&self) -> &Self::Atom {
+        &self.value
+    }
+
+    fn value_mut(&mut self) -> &mut Self::Atom {
+        &mut self.value
+    }
+
+    fn set_value(&mut self, value: Self::Atom) -> Self::Atom {
+        std::mem::replace(&mut self.value, value)
+    }
+
+    fn presentation(&self) -> Option<crate::style::PresentationAttr<'_>> {
+        if self.name.prefix.is_some() {
+            return None;
+        }
+        let id = crate::style::PresentationAttrId::from(self.name.local.as_ref());
+        crate::style::PresentationAttr::parse_string(
+            id,
+            self.value.as_ref(),
+            lightningcss::stylesheet::ParserOptions::default(),
+        )
+        .ok()
+    }
+}
+
+impl<'a> Attributes<'a> for AttributesArena<'a> {
+    type Attribute = AttributeArena;
+    type Deref = MappedRwLockReadGuard<'a, AttributeArena>;
+    type DerefMut = MappedRwLockWriteGuard<'a, AttributeArena>;
+
+    fn len(&self) -> usize {
+        self.0.read().len()
+    }
+
+    fn item(&self, index: usize) -> Option<Self::Deref> {
+        self.find(|attrs| attrs.get(index).map(|_| index))
+    }
+
+    fn item_mut(&self, index: usize) -> Option<Self::DerefMut> {
+        self.find_mut(|attrs| attrs.get(index).map(|_| index))
+    }
+
+    fn get_named_item(&self, name: &QualName) -> Option<Self::Deref> {
+        self.find(|attrs| {
+            attrs
+                .iter()
+                .position(|a| a.name.prefix == name.prefix && a.name.local == name.local)
+        })
+    }
+
+    fn get_named_item_mut(&self, name: &QualName) -> Option<Self::DerefMut> {
+        self.find_mut(|attrs| {
+            attrs
+                .iter()
+                .position(|a| a.name.prefix == name.prefix && a.name.local == name.local)
+        })
+    }
+
+    fn get_named_item_local(&self, name: &LocalName) -> Option<Self::Deref> {
+        self.find(|attrs| {
+            attrs
+                .iter()
+                .position(|a| a.name.prefix.is_none() && &a.name.local == name)
+        })
+    }
+
+    fn get_named_item_local_mut(&self, name: &LocalName) -> Option<Self::DerefMut> {
+        self.find_mut(|attrs| {
+            attrs
+                .iter()
+                .position(|a| a.name.prefix.is_none() && &a.name.local == name)
+        })
+    }
+
+    fn get_named_item_ns(&self, namespace: &Namespace, name: &LocalName) -> Option<Self::Deref> {
+        self.find(|attrs| {
+            attrs
+                .iter()
+                .position(|a| &a.name.local == name && &a.name.ns == namespace)
+        })
+    }
+
+    fn remove_named_item(&self, name: &QualName) -> Option<Self::Attribute> {
+        let mut attrs = self.0.write();
+        let index = attrs
+            .iter()
+            .position(|a| a.name.prefix == name.prefix && a.name.local == name.local)?;
+        Some(attrs.remove(index))
+    }
+
+    fn remove_named_item_local(&self, name: &LocalName) -> Option<Self::Attribute> {
+        let mut attrs = self.0.write();
+        let index = attrs
+            .iter()
+            .position(|a| a.name.prefix.is_none() && &a.name.local == name)?;
+        Some(attrs.remove(index))
+    }
+
+    fn set_named_item(&self, attr: Self::Attribute) -> Option<Self::Attribute> {
+        let mut attrs = self.0.write();
+        if let Some(index) = attrs
+            .iter()
+            .position(|a| a.name.prefix == attr.name.prefix && a.name.local == attr.name.local)
+        {
+            Some(std::mem::replace(&mut attrs[index], attr))
+        } else {
+            attrs.push(attr);
+            None
+        }
+    }
+
+    fn set_named_item_qual(&self, name: QualName, value: Atom) -> Option<Self::Attribute> {
+        self.set_named_item(AttributeArena { name, value })
+    }
+
+    fn into_iter(self) -> AttributesIter<'a, Self> {
+        AttributesIter::new(self)
+    }
+
+    fn into_iter_mut(self) -> AttributesIterMut<'a, Self> {
+        AttributesIterMut::new(self)
+    }
+
+    fn sort(&self, order: &[String], xmlns_front: bool) {
+        crate::attribute::sort(&mut self.0.write(), order, xmlns_front);
+    }
+
+    fn retain<F>(&self, mut f: F)
+    where
+        F: FnMut(&Self::Attribute) -> bool,
+    {
+        self.0.write().retain(|attr| f(attr));
+    }
+}
+
+impl<'a> AttributesArena<'a> {
+    fn find<F>(&self, f: F) -> Option<MappedRwLockReadGuard<'a, AttributeArena>>
+    where
+        F: FnOnce(&[AttributeArena]) -> Option<usize>,
+    {
+        let attrs = self.0.read();
+        let index = f(&attrs)?;
+        Some(RwLockReadGuard::map(attrs, |attrs| &attrs[index]))
+    }
+
+    fn find_mut<F>(&self, f: F) -> Option<MappedRwLockWriteGuard<'a, AttributeArena>>
+    where
+        F: FnOnce(&[AttributeArena]) -> Option<usize>,
+    {
+        let attrs = self.0.write();
+        let index = f(&attrs)?;
+        Some(parking_lot::RwLockWriteGuard::map(attrs, |attrs| {
+            &mut attrs[index]
+        }))
+    }
+}
+
+impl Debug for AttributesArena<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AttributesArena { ")?;
+        self.0.read().iter().try_for_each(|a| {
+            f.write_fmt(format_args!(r#"{}="{}" "#, a.name.formatter(), a.value))
+        })?;
+        f.write_str("} ")
+    }
+}
+
+impl ClassList for ClassListArena<'_> {
+    type Attribute = AttributeArena;
+
+    fn length(&self) -> usize {
+        self.tokens.len()
+    }
+
+    fn value(&self) -> Atom {
+        self.attrs
+            .get_named_item_local(&local_name!("class"))
+            .map(|a| a.value.clone())
+            .unwrap_or_default()
+    }
+
+    fn add(&mut self, token: Atom) {
+        if self.contains(&token) {
+            return;
+        }
+        self.tokens.push(token);
+        self.update();
+    }
+
+    fn contains(&self, token: &Atom) -> bool {
+        self.tokens.contains(token)
+    }
+
+    fn item(&self, index: usize) -> Option<&Atom> {
+        self.tokens.get(index)
+    }
+
+    fn remove(&mut self, token: &Atom) {
+        let Some(index) = self.tokens.iter().position(|t| t == token) else {
+            log::debug!("class not removed, not present in token memo");
+            return;
+        };
+        self.tokens.remove(index);
+        self.update();
+    }
+
+    fn replace(&mut self, old_token: Atom, new_token: Atom) -> bool {
+        let Some(index) = self.tokens.iter().position(|t| t == &old_token) else {
+            return false;
+        };
+        self.tokens[index] = new_token;
+        self.update();
+        true
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = &Atom> {
+        self.tokens.iter()
+    }
+}
+
+impl ClassListArena<'_> {
+    /// Writes the tokens to the class attribute, removing the attribute when there are none
+    fn update(&self) {
+        if self.tokens.is_empty() {
+            self.attrs.remove_named_item_local(&local_name!("class"));
+            return;
+        }
+        let value = self
+            .tokens
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<&str>>()
+            .join(" ");
+        self.attrs
+            .set_named_item_qual(Name::new(None, local_name!("class")), value.into());
+    }
+}
+
+impl Deref for Slot {
+    type Target = NodeData;
+
+    fn deref(&self) -> &Self::Target {
+        &self.chunk[self.index]
+    }
+}
+
+impl Arena {
+    /// Creates an arena from the data of each node, sharing a single allocation, and the index
+    /// of their relatives. The first node is expected to be the document.
+    fn from_nodes(data: Vec<NodeData>, links: Vec<Links>) -> NodeArena {
+        let chunk: Arc<[NodeData]> = data.into();
+        let entries = links
+            .into_iter()
+            .enumerate()
+            .map(|(index, links)| Entry {
+                links,
+                data: Slot {
+                    chunk: chunk.clone(),
+                    index,
+                },
+                #[cfg(feature = "selectors")]
+                selector_flags: selectors::matching::ElementSelectorFlags::empty(),
+            })
+            .collect();
+        let arena = Arc::new(Self {
+            entries: RwLock::new(entries),
+        });
+        NodeArena {
+            arena,
+            id: 0,
+            data: Slot { chunk, index: 0 },
+        }
+    }
+
+    /// Creates a new detached node in the arena
+    fn push(self: &Arc<Self>, data: NodeData) -> NodeArena {
+        let data = Slot {
+            chunk: Arc::new([data]),
+            index: 0,
+        };
+        let mut entries = self.entries.write();
+        let id = entries.len();
+        entries.push(Entry {
+            links: Links::default(),
+            data: data.clone(),
+            #[cfg(feature = "selectors")]
+            selector_flags: selectors::matching::ElementSelectorFlags::empty(),
+        });
+        NodeArena {
+            arena: self.clone(),
+            id,
+            data,
+        }
+    }
+
+    fn node(self: &Arc<Self>, entries: &[Entry], id: usize) -> NodeArena {
+        NodeArena {
+            arena: self.clone(),
+            id,
+            data: entries[id].data.clone(),
+        }
+    }
+
+    /// Removes the node from it's parent and siblings
+    fn detach(entries: &mut [Entry], id: usize) {
+        let links = &mut entries[id].links;
+        let Some(parent) = links.parent.take() else {
+            return;
+        };
+        let previous_sibling = links.previous_sibling.take();
+        let next_sibling = links.next_sibling.take();
+        // NOTE: The node may only reference it's parent, after `Node::set_parent_node`
+        let parent = &mut entries[parent].links;
+        if parent.first_child == Some(id) {
+            parent.first_child = next_sibling;
+        }
+        if parent.last_child == Some(id) {
+            parent.last_child = previous_sibling;
+        }
+        if let Some(previous) = previous_sibling {
+            entries[previous].links.next_sibling = next_sibling;
+        }
+        if let Some(next) = next_sibling {
+            entries[next].links.previous_sibling = previous_sibling;
+        }
+    }
+
+    /// Adds the node to the parent's children, before the given sibling or otherwise at the end.
+    /// The node is detached from it's current parent first.
+    fn attach(entries: &mut [Entry], parent: usize, id: usize, before: Option<usize>) {
+        Self::detach(entries, id);
+        let previous = match before {
+            Some(before) => entries[before].links.previous_sibling,
+            None => entries[parent].links.last_child,
+        };
+        let links = &mut entries[id].links;
+        links.parent = Some(parent);
+        links.previous_sibling = previous;
+        links.next_sibling = before;
+        match previous {
+            Some(previous) => entries[previous].links.next_sibling = Some(id),
+            None => entries[parent].links.first_child = Some(id),
+        }
+        match before {
+            Some(before) => entries[before].links.previous_sibling = Some(id),
+            None => entries[parent].links.last_child = Some(id),
+        }
+    }
+
+    /// Returns the index of the nth child of the parent
+    fn nth_child(entries: &[Entry], parent: usize, index: usize) -> Option<usize> {
+        let mut child = entries[parent].links.first_child;
+        for _ in 0..index {
+            child = entries[child?].links.next_sibling;
+        }
+        child
+    }
+}
+
+impl NodeArena {
+    fn links(&self) -> Links {
+        self.arena.entries.read()[self.id].links
+    }
+
+    /// Asserts that the node belongs to the same arena, as links can't be made between arenas
+    fn assert_same_arena(&self, other: &Self) {
+        assert!(
+            Arc::ptr_eq(&self.arena, &other.arena),
+            "Node from another arena passed to arena node"
+        );
+    }
+
+    /// Calls back with each child of the node until a break is returned.
+    ///
+    /// The lock on the tree isn't held between calls, so the child may be removed or moved by the
+    /// callback.
+    fn try_fold_children<B, F>(&self, mut f: F) -> ControlFlow<B>
+    where
+        F: FnMut(Self) -> ControlFlow<B>,
+    {
+        let mut next = self.links().first_child;
+        while let Some(id) = next {
+            let child = {
+                let entries = self.arena.entries.read();
+                next = entries[id].links.next_sibling;
+                self.arena.node(&entries, id)
+            };
+            f(child)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Creates a deep clone of the node's data
+    fn clone_data(&self) -> NodeData {
+        match &*self.data {
+            NodeData::Document => NodeData::Document,
+            NodeData::Doctype {
+                name,
+                public_id,
+                system_id,
+            } => NodeData::Doctype {
+                name: name.clone(),
+                public_id: public_id.clone(),
+                system_id: system_id.clone(),
+            },
+            NodeData::Element { name, attrs } => NodeData::Element {
+                name: name.clone(),
+                attrs: RwLock::new(attrs.read().clone()),
+            },
+            NodeData::Text { contents } => NodeData::Text {
+                contents: RwLock::new(contents.read().clone()),
+            },
+            NodeData::CDataSection { contents } => NodeData::CDataSection {
+                contents: RwLock::new(contents.read().clone()),
+            },
+            NodeData::Comment { contents } => NodeData::Comment {
+                contents: contents.clone(),
+            },
+            NodeData::ProcessingInstruction { target, contents } => {
+                NodeData::ProcessingInstruction {
+                    target: target.clone(),
+                    contents: contents.clone(),
+                }
+            }
+        }
+    }
+
+    /// Returns the node as an arena node, if it's implemented by this module
+    fn downcast(node: &impl Node) -> Option<&Self> {
+        let node = node as &dyn std::any::Any;
+        node.downcast_ref::<Self>()
+            .or_else(|| node.downcast_ref::<ElementArena>().map(|e| &e.node))
+    }
+}
+
+impl Node for NodeArena {
+    type Atom = Atom;
+    type Child = NodeArena;
+    type ParentChild = NodeArena;
+
+    fn ptr_eq(&self, other: &impl Node) -> bool {
+        self.as_ptr_byte() == other.as_ptr_byte()
+    }
+
+    fn as_ptr_byte(&self) -> usize {
+        std::ptr::from_ref::<NodeData>(&self.data).addr()
+    }
+
+    fn as_ref(&self) -> Box<dyn node::Ref> {
+        Box::new(NodeArenaRef(self.clone()))
+    }
+
+    fn child_nodes_iter(&self) -> impl DoubleEndedIterator<Item = Self> {
+        self.child_nodes().into_iter()
+    }
+
+    fn child_nodes(&self) -> Vec<Self::Child> {
+        let entries = self.arena.entries.read();
+        let mut children = vec![];
+        let mut child = entries[self.id].links.first_child;
+        while let Some(id) = child {
+            children.push(self.arena.node(&entries, id));
+            child = entries[id].links.next_sibling;
+        }
+        children
+    }
+
+    fn child_node_count(&self) -> usize {
+        let entries = self.arena.entries.read();
+        let mut count = 0;
+        let mut child = entries[self.id].links.first_child;
+        while let Some(id) = child {
+            count += 1;
+            child = entries[id].links.next_sibling;
+        }
+        count
+    }
+
+    fn has_child_nodes(&self) -> bool {
+        self.links().first_child.is_some()
+    }
+
+    #[allow(refining_impl_trait)]
+    fn element(&self) -> Option<ElementArena> {
+        match self.node_type() {
+            node::Type::Element => ElementArena::new(self.clone()),
+            _ => None,
+        }
+    }
+
+    fn empty(&self) {
+        let mut entries = self.arena.entries.write();
+        while let Some(child) = entries[self.id].links.first_child {
+            Arena::detach(&mut entries, child);
+        }
+    }
+
+    #[allow(refining_impl_trait)]
+    fn find_element(&self) -> Option<ElementArena> {
+        <ElementArena as Element>::find_element(self.clone())
+    }
+
+    fn for_each_child<F>(&self, mut f: F)
+    where
+        F: FnMut(Self),
+    {
+        let _: ControlFlow<()> = self.try_fold_children(|child| {
+            f(child);
+            ControlFlow::Continue(())
+        });
+    }
+
+    fn try_for_each_child<F, E>(&self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(Self) -> Result<(), E>,
+    {
+        match self.try_fold_children(|child| match f(child) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(error) => ControlFlow::Break(error),
+        }) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(error) => Err(error),
+        }
+    }
+
+    fn any_child<F>(&self, mut f: F) -> bool
+    where
+        F: FnMut(Self) -> bool,
+    {
+        self.try_fold_children(|child| {
+            if f(child) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .is_break()
+    }
+
+    fn all_children<F>(&self, mut f: F) -> bool
+    where
+        F: FnMut(Self) -> bool,
+    {
+        self.try_fold_children(|child| {
+            if f(child) {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        })
+        .is_continue()
+    }
+
+    fn retain_children<F>(&self, mut f: F)
+    where
+        F: FnMut(Self::Child) -> bool,
+    {
+        self.for_each_child(|child| {
+            if !f(child.clone()) {
+                child.remove();
+            }
+        });
+    }
+
+    fn node_type(&self) -> node::Type {
+        match &*self.data {
+            NodeData::Document => node::Type::Document,
+            NodeData::Doctype { .. } => node::Type::DocumentType,
+            NodeData::Element { .. } => node::Type::Element,
+            NodeData::Text { .. } => node::Type::Text,
+            NodeData::CDataSection { .. } => node::Type::CDataSection,
+            NodeData::Comment { .. } => node::Type::Comment,
+            NodeData::ProcessingInstruction { .. } => node::Type::ProcessingInstruction,
+        }
+    }
+
+    #[allow(refining_impl_trait)]
+    fn parent_node(&self) -> Option<NodeArena> {
+        let entries = self.arena.entries.read();
+        let parent = entries[self.id].links.parent?;
+        Some(self.arena.node(&entries, parent))
+    }
+
+    #[allow(refining_impl_trait)]
+    fn set_parent_node(&self, new_parent: &impl Node<Atom = Self::Atom>) -> Option<NodeArena> {
+        let new_parent =
+            Self::downcast(new_parent).expect("Incorrect implementation passed as new parent");
+        self.assert_same_arena(new_parent);
+        let mut entries = self.arena.entries.write();
+        let old_parent = entries[self.id].links.parent.replace(new_parent.id)?;
+        Some(self.arena.node(&entries, old_parent))
+    }
+
+    fn append_child(&mut self, a_child: Self::Child) {
+        self.assert_same_arena(&a_child);
+        Arena::attach(&mut self.arena.entries.write(), self.id, a_child.id, None);
+    }
+
+    fn insert(&mut self, index: usize, new_node: Self::Child) {
+        self.assert_same_arena(&new_node);
+        let entries = &mut *self.arena.entries.write();
+        Arena::detach(entries, new_node.id);
+        let before = Arena::nth_child(entries, self.id, index);
+        Arena::attach(entries, self.id, new_node.id, before);
+    }
+
+    fn node_name(&self) -> Self::Atom {
+        match &*self.data {
+            NodeData::Document => "#document".into(),
+            NodeData::Doctype { name, .. } => name.clone(),
+            NodeData::Element { name, .. } => name.local.to_uppercase().into(),
+            NodeData::Text { .. } => "#text".into(),
+            NodeData::CDataSection { .. } => "#cdata-section".into(),
+            NodeData::Comment { .. } => "#comment".into(),
+            NodeData::ProcessingInstruction { target, .. } => target.clone(),
+        }
+    }
+
+    fn node_value(&self) -> Option<Self::Atom> {
+        Some(match &*self.data {
+            NodeData::Comment { contents } | NodeData::ProcessingInstruction { contents, .. } => {
+                contents.clone()
+            }
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
+                contents.read().clone()
+            }
+            _ => return None,
+        })
+    }
+
+    fn processing_instruction(&self) -> Option<(&Self::Atom, &Self::Atom)> {
+        match &*self.data {
+            NodeData::ProcessingInstruction { target, contents } => Some((target, contents)),
+            _ => None,
+        }
+    }
+
+    fn try_set_node_value(&self, value: Self::Atom) -> Option<()> {
+        match &*self.data {
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
+                *contents.write() = value;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn text_content(&self) -> Option<String> {
+        match &*self.data {
+            NodeData::Doctype { .. } | NodeData::Document => None,
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
+                Some(contents.read().to_string())
+            }
+            // FIXME: Empty string should only be returned on recursive calls
+            NodeData::Comment { contents } | NodeData::ProcessingInstruction { contents, .. } => {
+                Some(contents.to_string())
+            }
+            NodeData::Element { .. } => {
+                let mut text = String::new();
+                self.for_each_child(|child| {
+                    if matches!(
+                        child.node_type(),
+                        node::Type::Comment | node::Type::ProcessingInstruction
+                    ) {
+                        return;
+                    }
+                    if let Some(child_text) = child.text_content() {
+                        text.push_str(&child_text);
+                    }
+                });
+                Some(text)
+            }
+        }
+    }
+
+    fn set_text_content(&mut self, content: Self::Atom) {
+        match &*self.data {
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
+                *contents.write() = content;
+            }
+            NodeData::Element { .. } => {
+                let text = self.text(content);
+                self.empty();
+                self.append_child(text);
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&self, content: Self::Atom) -> Self {
+        self.arena.push(NodeData::Text {
+            contents: RwLock::new(content),
+        })
+    }
+
+    fn remove(&self) {
+        Arena::detach(&mut self.arena.entries.write(), self.id);
+    }
+
+    fn remove_child_at(&mut self, index: usize) -> Option<Self::Child> {
+        let mut entries = self.arena.entries.write();
+        let child = Arena::nth_child(&entries, self.id, index)?;
+        Arena::detach(&mut entries, child);
+        Some(self.arena.node(&entries, child))
+    }
+
+    fn clone_node(&self) -> Self {
+        let mut clone = self.arena.push(self.clone_data());
+        self.for_each_child(|child| clone.append_child(child.clone_node()));
+        clone
+    }
+
+    fn replace_child(
+        &mut self,
+        new_child: Self::Child,
+        old_child: &Self::Child,
+    ) -> Option<Self::Child> {
+        self.assert_same_arena(&new_child);
+        let mut entries = self.arena.entries.write();
+        if entries[old_child.id].links.parent != Some(self.id) {
+            return None;
+        }
+        Arena::attach(&mut entries, self.id, new_child.id, Some(old_child.id));
+        Arena::detach(&mut entries, old_child.id);
+        Some(old_child.clone())
+    }
+
+    fn to_owned(&self) -> Self {
+        self.clone()
+    }
+
+    fn as_child(&self) -> Self::Child {
+        self.clone()
+    }
+
+    fn as_impl(&self) -> impl Node {
+        self.clone()
+    }
+
+    fn as_parent_child(&self) -> Self::ParentChild {
+        self.clone()
+    }
+}
+
+impl Debug for NodeArena {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let data = match &*self.data {
+            NodeData::Element { name, .. } => format!("Element {{ {} }}", name.formatter()),
+            _ => format!("{:?} {:?}", self.node_type(), self.node_value()),
+        };
+        let child_len = self.child_node_count();
+        f.write_fmt(format_args!(
+            "NodeArena {{
+    data: {data}
+    children: {child_len}
+}}"
+        ))
+    }
+}
+
+impl node::Features for NodeArena {}
+
+impl node::Ref for NodeArenaRef {
+    fn inner_as_any(&self) -> &dyn std::any::Any {
+        &self.0
+    }
+
+    fn clone(&self) -> Box<dyn node::Ref> {
+        Box::new(Self(self.0.clone()))
+    }
+}
+
+#[cfg(feature = "parse")]
+impl parse::Node for NodeArena {
+    fn parse_with_options(source: &str, options: &parse::Options) -> anyhow::Result<Self> {
+        TreeBuilder::new(options).build(source)
+    }
+}
+
+#[cfg(feature = "parse")]
+#[derive(Default)]
+/// Builds the nodes of an arena from the events of an xml reader, with the same handling of
+/// names and references as the markup5ever implementation.
+struct TreeBuilder {
+    data: Vec<NodeData>,
+    links: Vec<Links>,
+    /// The element's the reader is currently within, starting with the document
+    open_elements: Vec<usize>,
+    /// The namespaces and entities in scope
+    context: ReaderContext,
+}
+
+#[cfg(feature = "parse")]
+impl TreeBuilder {
+    fn new(options: &parse::Options) -> Self {
+        Self {
+            context: ReaderContext::new(options.clone()),
+            ..Self::default()
+        }
+    }
+
+    fn build(mut self, source: &str) -> anyhow::Result<NodeArena> {
+        use quick_xml::events::Event;
+
+        self.context.check_size(source)?;
+        self.data.push(NodeData::Document);
+        self.links.push(Links::default());
+        self.open_elements.push(0);
+
+        let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+        let mut reader = quick_xml::Reader::from_str(source);
+        reader.trim_text(false).check_end_names(false);
+        loop {
+            match ReaderContext::read_event(&mut reader, source)? {
+                Event::Start(start) => {
+                    let element = self.create_element(&start)?;
+                    let id = self.append(element);
+                    self.open_elements.push(id);
+                }
+                Event::Empty(start) => {
+                    let element = self.create_element(&start)?;
+                    self.append(element);
+                    self.context.close(self.open_elements.len());
+                }
+                Event::End(end) => {
+                    let Some(index) = self.open_elements.iter().rposition(|id| {
+                        matches!(&self.data[*id], NodeData::Element { name, .. } if ReaderContext::is_end_of(&end, name))
+                    }) else {
+                        continue;
+                    };
+                    self.open_elements.truncate(index);
+                    self.context.close(self.open_elements.len());
+                }
+                Event::Text(text) => {
+                    let text = std::str::from_utf8(&text)?;
+                    let text = self.context.unescape(text)?.into_owned();
+                    self.append_text(text);
+                }
+                Event::CData(c_data) => {
+                    let contents = std::str::from_utf8(&c_data)?;
+                    self.append(NodeData::CDataSection {
+                        contents: RwLock::new(contents.into()),
+                    });
+                }
+                Event::Comment(comment) => {
+                    let contents = std::str::from_utf8(&comment)?;
+                    self.append(NodeData::Comment {
+                        contents: contents.into(),
+                    });
+                }
+                Event::Decl(declaration) => {
+                    let contents = std::str::from_utf8(&declaration)?;
+                    self.append_processing_instruction(contents);
+                }
+                Event::PI(instruction) => {
+                    let contents = std::str::from_utf8(&instruction)?;
+                    self.append_processing_instruction(contents);
+                }
+                Event::DocType(doctype) => {
+                    let doctype = std::str::from_utf8(&doctype)?;
+                    self.context.declare_entities(doctype)?;
+                    let (name, public_id, system_id) = ReaderContext::doctype(doctype);
+                    self.append(NodeData::Doctype {
+                        name: name.into(),
+                        public_id: public_id.into(),
+                        system_id: system_id.into(),
+                    });
+                }
+                Event::Eof => break,
+            }
+        }
+        Ok(Arena::from_nodes(self.data, self.links))
+    }
+
+    fn create_element(
+        &mut self,
+        start: &quick_xml::events::BytesStart,
+    ) -> anyhow::Result<NodeData> {
+        let (name, attrs) = self.context.start(start, self.open_elements.len())?;
+        Ok(NodeData::Element {
+            name,
+            attrs: RwLock::new(
+                attrs
+                    .into_iter()
+                    .map(|(name, value)| AttributeArena { name, value })
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Appends the node to the current element, returning it's index
+    fn append(&mut self, data: NodeData) -> usize {
+        let parent = *self
+            .open_elements
+            .last()
+            .expect("document should always be open");
+        let id = self.data.len();
+        let previous_sibling = self.links[parent].last_child;
+        self.data.push(data);
+        self.links.push(Links {
+            parent: Some(parent),
+            previous_sibling,
+            ..Links::default()
+        });
+        match previous_sibling {
+            Some(previous) => self.links[previous].next_sibling = Some(id),
+            None => self.links[parent].first_child = Some(id),
+        }
+        self.links[parent].last_child = Some(id);
+        id
+    }
+
+    fn append_text(&mut self, text: String) {
+        let parent = *self
+            .open_elements
+            .last()
+            .expect("document should always be open");
+        if parent == 0 && text.trim().is_empty() {
+            return;
+        }
+        if let Some(last_child) = self.links[parent].last_child {
+            if let NodeData::Text { contents } = &mut self.data[last_child] {
+                let contents = contents.get_mut();
+                *contents = format!("{contents}{text}").into();
+                return;
+            }
+        }
+        self.append(NodeData::Text {
+            contents: RwLock::new(text.into()),
+        });
+    }
+
+    fn append_processing_instruction(&mut self, contents: &str) {
+        let (target, contents) = contents
+            .split_once(char::is_whitespace)
+            .unwrap_or((contents, ""));
+        self.append(NodeData::ProcessingInstruction {
+            target: target.into(),
+            contents: contents.trim_start().into(),
+        });
+    }
+}
+
+#[cfg(feature = "serialize")]
+enum SerializeOp {
+    Open(NodeArena),
+    Close(QualName),
+}
+
+#[cfg(feature = "serialize")]
+impl serialize::Serialize for NodeArena {
+    fn serialize<Wr: std::io::Write>(
+        &self,
+        serializer: &mut serialize::Serializer<Wr>,
+        traversal_scope: xml5ever::serialize::TraversalScope,
+    ) -> std::io::Result<()> {
+        use xml5ever::serialize::TraversalScope;
+
+        let mut ops = VecDeque::new();
+        match traversal_scope {
+            TraversalScope::IncludeNode => ops.push_back(SerializeOp::Open(self.clone())),
+            TraversalScope::ChildrenOnly(_) => {
+                ops.extend(self.child_nodes().into_iter().map(SerializeOp::Open));
+            }
+        }
+
+        while let Some(op) = ops.pop_front() {
+            let handle = match op {
+                SerializeOp::Open(handle) => handle,
+                SerializeOp::Close(name) => {
+                    serializer.end_elem(&name)?;
+                    continue;
+                }
+            };
+            match &*handle.data {
+                NodeData::Element { name, attrs } => {
+                    let children = handle.child_nodes();
+                    let is_empty = if serializer.is_lossless() {
+                        children.is_empty()
+                    } else {
+                        children.iter().all(|child| match &*child.data {
+                            NodeData::Text { contents } => contents.read().trim().is_empty(),
+                            _ => false,
+                        })
+                    };
+                    serializer.start_elem(
+                        name,
+                        attrs.read().iter().map(|a| (&a.name, a.value.as_ref())),
+                        is_empty,
+                    )?;
+                    if is_empty {
+                        continue;
+                    }
+
+                    ops.reserve(1 + children.len());
+                    ops.push_front(SerializeOp::Close(name.clone()));
+                    for child in children.into_iter().rev() {
+                        ops.push_front(SerializeOp::Open(child));
+                    }
+                }
+                NodeData::Doctype {
+                    name,
+                    public_id,
+                    system_id,
+                } => serializer.write_doctype_with_ids(name, public_id, system_id)?,
+                NodeData::Text { contents } => serializer.write_text(&contents.read())?,
+                NodeData::CDataSection { contents } => {
+                    serializer.write_c_data_section(&contents.read())?;
+                }
+                NodeData::Comment { contents } => serializer.write_comment(contents)?,
+                NodeData::ProcessingInstruction { target, contents } => {
+                    serializer.write_processing_instruction(target, contents)?;
+                }
+                NodeData::Document => panic!("Can't serialize Document node itself"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl serialize::Node for NodeArena {
+    fn serialize(&self) -> anyhow::Result<String> {
+        self.serialize_with_options(serialize::Options::new())
+    }
+
+    fn serialize_with_options(&self, options: serialize::Options) -> anyhow::Result<String> {
+        let mut sink = Vec::new();
+        self.serialize_into_with_options(&mut sink, options)?;
+        Ok(String::from_utf8_lossy(&sink).to_string())
+    }
+
+    fn serialize_into<Wr: std::io::Write>(&self, sink: Wr) -> anyhow::Result<()> {
+        Ok(serialize::serialize(
+            sink,
+            self,
+            xml5ever::serialize::SerializeOpts::default(),
+        )?)
+    }
+
+    fn serialize_into_with_options<Wr: std::io::Write>(
+        &self,
+        sink: Wr,
+        options: serialize::Options,
+    ) -> anyhow::Result<()> {
+        Ok(serialize::with_options(
+            sink,
+            self,
+            xml5ever::serialize::SerializeOpts::default(),
+            options,
+        )?)
+    }
+}
+
+impl Element for ElementArena {
+    type Name = QualName;
+    type Attributes<'a> = AttributesArena<'a>;
+    type Attr = AttributeArena;
+
+    fn new(node: NodeArena) -> Option<Self> {
+        if !matches!(node.node_type(), node::Type::Element | node::Type::Document) {
+            return None;
+        }
+        Some(Self { node })
+    }
+
+    fn as_document(&self) -> impl Document<Root = Self> {
+        DocumentArena(self.clone())
+    }
+
+    fn from_parent(node: NodeArena) -> Option<Self> {
+        Self::new(node)
+    }
+
+    fn tag_name(&self) -> Self::Atom {
+        self.node.node_name()
+    }
+
+    fn qual_name(&self) -> &Self::Name {
+        self.data().0
+    }
+
+    fn replace_children(&self, children: Vec<Self::Child>) {
+        let entries = &mut *self.node.arena.entries.write();
+        while let Some(child) = entries[self.node.id].links.first_child {
+            Arena::detach(entries, child);
+        }
+        for child in children {
+            self.node.assert_same_arena(&child);
+            Arena::attach(entries, self.node.id, child.id, None);
+        }
+    }
+
+    fn set_local_name(&mut self, new_name: <Self::Name as Name>::LocalName) {
+        let mut name = self.qual_name().clone();
+        name.local = new_name;
+        self.replace_name(name);
+    }
+
+    fn append(&self, node: Self::Child) {
+        self.node.clone().append_child(node);
+    }
+
+    fn attributes(&self) -> Self::Attributes<'_> {
+        AttributesArena(self.data().1)
+    }
+
+    fn set_attributes(&self, new_attrs: Self::Attributes<'_>) {
+        let new_attrs = std::mem::take(&mut *new_attrs.0.write());
+        *self.data().1.write() = new_attrs;
+    }
+
+    fn parent_element(&self) -> Option<Self> {
+        Self::new(self.node.parent_node()?)
+    }
+
+    #[allow(refining_impl_trait)]
+    fn class_list(&self) -> ClassListArena<'_> {
+        let attrs = self.attributes();
+        let tokens = attrs
+            .get_named_item_local(&local_name!("class"))
+            .map(|a| a.value.split_whitespace().map(Into::into).collect())
+            .unwrap_or_default();
+        ClassListArena { attrs, tokens }
+    }
+
+    fn has_class(&self, token: &Self::Atom) -> bool {
+        let token = token.trim_start_matches('.');
+        self.get_attribute_local(&local_name!("class"))
+            .is_some_and(|class| class.split_whitespace().any(|t| t == token))
+    }
+
+    fn document(&self) -> Option<Self> {
+        let parent = self.node.parent_node()?;
+        match &*parent.data {
+            NodeData::Element { .. } => parent.element()?.document(),
+            NodeData::Document => Some(Self { node: parent }),
+            _ => None,
+        }
+    }
+
+    fn flatten(&self) {
+        let entries = &mut *self.node.arena.entries.write();
+        if entries[self.node.id].links.parent.is_none() {
+            return;
+        }
+        let parent = entries[self.node.id].links.parent;
+        while let (Some(parent), Some(child)) = (parent, entries[self.node.id].links.first_child) {
+            Arena::attach(entries, parent, child, Some(self.node.id));
+        }
+        Arena::detach(entries, self.node.id);
+    }
+
+    /// Runs a breadth-first search to get the first element of a node.
+    fn find_element(node: <Self as Node>::ParentChild) -> Option<Self> {
+        let mut queue = VecDeque::new();
+        queue.push_back(node);
+
+        while let Some(current) = queue.pop_front() {
+            let maybe_element = current.element();
+            if maybe_element.is_some() {
+                return maybe_element;
+            }
+
+            current.for_each_child(|child| queue.push_back(child));
+        }
+        None
+    }
+
+    #[allow(refining_impl_trait)]
+    fn get_attribute_node<'a>(
+        &'a self,
+        attr_name: &QualName,
+    ) -> Option<MappedRwLockReadGuard<'a, AttributeArena>> {
+        self.attributes().get_named_item(attr_name)
+    }
+
+    #[allow(refining_impl_trait)]
+    fn get_attribute_node_mut<'a>(
+        &'a self,
+        attr_name: &QualName,
+    ) -> Option<MappedRwLockWriteGuard<'a, AttributeArena>> {
+        self.attributes().get_named_item_mut(attr_name)
+    }
+
+    #[allow(refining_impl_trait)]
+    fn get_attribute_node_local<'a>(
+        &'a self,
+        attr_name: &LocalName,
+    ) -> Option<MappedRwLockReadGuard<'a, AttributeArena>> {
+        self.attributes().get_named_item_local(attr_name)
+    }
+
+    #[allow(refining_impl_trait)]
+    fn get_attribute_node_ns<'a>(
+        &'a self,
+        namespace: &Namespace,
+        name: &LocalName,
+    ) -> Option<MappedRwLockReadGuard<'a, AttributeArena>> {
+        self.attributes().get_named_item_ns(namespace, name)
+    }
+
+    #[allow(refining_impl_trait)]
+    fn get_attribute<'a>(&'a self, name: &QualName) -> Option<MappedRwLockReadGuard<'a, Atom>> {
+        self.get_attribute_node(name)
+            .map(|a| MappedRwLockReadGuard::map(a, |a| &a.value))
+    }
+
+    #[allow(refining_impl_trait)]
+    fn get_attribute_local<'a>(
+        &'a self,
+        name: &LocalName,
+    ) -> Option<MappedRwLockReadGuard<'a, Atom>> {
+        self.get_attribute_node_local(name)
+            .map(|a| MappedRwLockReadGuard::map(a, |a| &a.value))
+    }
+
+    #[allow(refining_impl_trait)]
+    fn get_attribute_ns<'a>(
+        &'a self,
+        namespace: &Namespace,
+        name: &LocalName,
+    ) -> Option<MappedRwLockReadGuard<'a, Atom>> {
+        self.get_attribute_node_ns(namespace, name)
+            .map(|a| MappedRwLockReadGuard::map(a, |a| &a.value))
+    }
+
+    #[allow(refining_impl_trait)]
+    fn get_attribute_names(&self) -> Vec<MappedRwLockReadGuard<'_, QualName>> {
+        let attrs = self.data().1;
+        (0..attrs.read().len())
+            .filter_map(|i| {
+                RwLockReadGuard::try_map(attrs.read_recursive(), |a| a.get(i).map(|a| &a.name)).ok()
+            })
+            .collect()
+    }
+
+    fn for_each_element_child<F>(&self, mut f: F)
+    where
+        F: FnMut(Self),
+    {
+        self.node.for_each_child(|child| {
+            if let NodeData::Element { .. } = &*child.data {
+                f(Self { node: child });
+            }
+        });
+    }
+
+    fn sort_child_elements<F>(&self, mut f: F)
+    where
+        F: FnMut(Self, Self) -> std::cmp::Ordering,
+    {
+        let mut children = self.node.child_nodes();
+        children.sort_by(|a, b| {
+            let Some(a) = Element::new(a.clone()) else {
+                return std::cmp::Ordering::Less;
+            };
+            let Some(b) = Element::new(b.clone()) else {
+                return std::cmp::Ordering::Greater;
+            };
+            f(a, b)
+        });
+        let entries = &mut *self.node.arena.entries.write();
+        for child in children {
+            Arena::attach(entries, self.node.id, child.id, None);
+        }
+    }
+}
+
+impl ElementArena {
+    /// Gets the associated element data.
+    fn data(&self) -> (&QualName, &RwLock<Vec<AttributeArena>>) {
+        if let NodeData::Element { name, attrs } = &*self.node.data {
+            (name, attrs)
+        } else {
+            log::debug!(
+                "You probably tried getting something element related from a document element. Check the stack trace."
+            );
+            log::debug!("{:?}", self.node);
+            unreachable!("Element contains non-element data. This is a bug!")
+        }
+    }
+
+    /// Replaces the element in it's parent with a clone using the given name, moving the
+    /// children over to the clone.
+    fn replace_name(&mut self, name: QualName) {
+        let clone = self.node.arena.push(NodeData::Element {
+            name,
+            attrs: RwLock::new(self.data().1.read().clone()),
+        });
+        {
+            let entries = &mut *self.node.arena.entries.write();
+            while let Some(child) = entries[self.node.id].links.first_child {
+                Arena::attach(entries, clone.id, child, None);
+            }
+            if let Some(parent) = entries[self.node.id].links.parent {
+                Arena::attach(entries, parent, clone.id, Some(self.node.id));
+                Arena::detach(entries, self.node.id);
+            }
+        }
+        self.node = clone;
+    }
+
+    #[cfg(feature = "selectors")]
+    fn set_selector_flags(&self, selector_flags: selectors::matching::ElementSelectorFlags) {
+        if selector_flags.is_empty() {
+            return;
+        }
+        self.node.arena.entries.write()[self.node.id].selector_flags |= selector_flags;
+    }
+}
+
+impl Hash for ElementArena {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node.as_ptr_byte().hash(state);
+    }
+}
+
+impl Eq for ElementArena {}
+
+impl PartialEq for ElementArena {
+    fn eq(&self, other: &Self) -> bool {
+        self.node.ptr_eq(&other.node)
+    }
+}
+
+impl Node for ElementArena {
+    type Atom = Atom;
+    type Child = NodeArena;
+    type ParentChild = NodeArena;
+
+    fn ptr_eq(&self, other: &impl Node) -> bool {
+        self.node.ptr_eq(other)
+    }
+
+    fn as_ptr_byte(&self) -> usize {
+        self.node.as_ptr_byte()
+    }
+
+    fn as_ref(&self) -> Box<dyn node::Ref> {
+        self.node.as_ref()
+    }
+
+    fn child_nodes_iter(&self) -> impl DoubleEndedIterator<Item = Self::Child> {
+        self.node.child_nodes().into_iter()
+    }
+
+    fn child_node_count(&self) -> usize {
+        self.node.child_node_count()
+    }
+
+    fn child_nodes(&self) -> Vec<Self::Child> {
+        self.node.child_nodes()
+    }
+
+    fn has_child_nodes(&self) -> bool {
+        self.node.has_child_nodes()
+    }
+
+    fn for_each_child<F>(&self, f: F)
+    where
+        F: FnMut(Self::Child),
+    {
+        self.node.for_each_child(f);
+    }
+
+    fn try_for_each_child<F, E>(&self, f: F) -> Result<(), E>
+    where
+        F: FnMut(Self::Child) -> Result<(), E>,
+    {
+        self.node.try_for_each_child(f)
+    }
+
+    fn any_child<F>(&self, f: F) -> bool
+    where
+        F: FnMut(Self::Child) -> bool,
+    {
+        self.node.any_child(f)
+    }
+
+    fn all_children<F>(&self, f: F) -> bool
+    where
+        F: FnMut(Self::Child) -> bool,
+    {
+        self.node.all_children(f)
+    }
+
+    fn retain_children<F>(&self, f: F)
+    where
+        F: FnMut(Self::Child) -> bool,
+    {
+        self.node.retain_children(f);
+    }
+
+    fn element(&self) -> Option<impl Element> {
+        Some(self.clone())
+    }
+
+    fn empty(&self) {
+        self.node.empty();
+    }
+
+    fn find_element(&self) -> Option<impl Element> {
+        Some(self.clone())
+    }
+
+    fn node_type(&self) -> node::Type {
+        self.node.node_type()
+    }
+
+    fn processing_instruction(&self) -> Option<(&Self::Atom, &Self::Atom)> {
+        None
+    }
+
+    #[allow(refining_impl_trait)]
+    fn parent_node(&self) -> Option<NodeArena> {
+        self.node.parent_node()
+    }
+
+    #[allow(refining_impl_trait)]
+    fn set_parent_node(&self, new_parent: &impl Node<Atom = Self::Atom>) -> Option<NodeArena> {
+        self.node.set_parent_node(new_parent)
+    }
+
+    fn append_child(&mut self, a_child: Self::Child) {
+        self.node.append_child(a_child);
+    }
+
+    fn insert(&mut self, index: usize, new_node: Self::Child) {
+        self.node.insert(index, new_node);
+    }
+
+    fn node_name(&self) -> Self::Atom {
+        self.node.node_name()
+    }
+
+    fn node_value(&self) -> Option<Self::Atom> {
+        self.node.node_value()
+    }
+
+    fn try_set_node_value(&self, _value: Self::Atom) -> Option<()> {
+        None
+    }
+
+    fn text_content(&self) -> Option<String> {
+        self.node.text_content()
+    }
+
+    fn set_text_content(&mut self, content: Self::Atom) {
+        self.node.set_text_content(content);
+    }
+
+    fn text(&self, content: Self::Atom) -> Self::Child {
+        self.node.text(content)
+    }
+
+    fn remove(&self) {
+        self.node.remove();
+    }
+
+    fn remove_child_at(&mut self, index: usize) -> Option<Self::Child> {
+        self.node.remove_child_at(index)
+    }
+
+    fn clone_node(&self) -> Self {
+        Self {
+            node: self.node.clone_node(),
+        }
+    }
+
+    fn replace_child(
+        &mut self,
+        new_child: Self::Child,
+        old_child: &Self::Child,
+    ) -> Option<Self::Child> {
+        self.node.replace_child(new_child, old_child)
+    }
+
+    fn to_owned(&self) -> Self {
+        self.clone()
+    }
+
+    fn as_impl(&self) -> impl Node {
+        self.clone()
+    }
+
+    fn as_child(&self) -> Self::Child {
+        self.node.clone()
+    }
+
+    fn as_parent_child(&self) -> Self::ParentChild {
+        self.node.clone()
+    }
+}
+
+impl Debug for ElementArena {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.node_type() != node::Type::Element {
+            return self.node.fmt(f);
+        }
+        let name = self.qual_name().formatter();
+        let attributes = self.attributes();
+        let text = self.text_content().map(|s| s.trim().to_string());
+        let child_count = match self.child_node_count() {
+            0 => String::from("/>"),
+            len => format!(">{len} child nodes</{name}>"),
+        };
+        f.write_fmt(format_args!(
+            r"ElementArena {{ <{name} {attributes:?}{child_count} {text:?} }}"
+        ))
+    }
+}
+
+impl node::Features for ElementArena {}
+
+#[cfg(feature = "parse")]
+impl parse::Node for ElementArena {
+    fn parse_with_options(source: &str, options: &parse::Options) -> anyhow::Result<Self> {
+        let root = NodeArena::parse_with_options(source, options)?;
+        match NodeArena::find_element(&root) {
+            Some(element) => Ok(element),
+            None => Err(anyhow::Error::new(parse::Error::NoElementInDocument)),
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl serialize::Node for ElementArena {
+    fn serialize_with_options(&self, options: serialize::Options) -> anyhow::Result<String> {
+        self.node.serialize_with_options(options)
+    }
+
+    fn serialize(&self) -> anyhow::Result<String> {
+        self.node.serialize()
+    }
+
+    fn serialize_into<Wr: std::io::Write>(&self, sink: Wr) -> anyhow::Result<()> {
+        self.node.serialize_into(sink)
+    }
+
+    fn serialize_into_with_options<Wr: std::io::Write>(
+        &self,
+        sink: Wr,
+        options: serialize::Options,
+    ) -> anyhow::Result<()> {
+        self.node.serialize_into_with_options(sink, options)
+    }
+}
+
+impl element::Features for ElementArena {}
+
+#[cfg(feature = "selectors")]
+impl selectors::Element for ElementArena {
+    type Impl = crate::selectors::SelectorImpl<
+        <Self as Node>::Atom,
+        <<Self as Element>::Name as Name>::LocalName,
+        <<Self as Element>::Name as Name>::Namespace,
+    >;
+
+    fn opaque(&self) -> selectors::OpaqueElement {
+        selectors::OpaqueElement::new::<NodeData>(&self.node.data)
+    }
+
+    fn parent_element(&self) -> Option<Self> {
+        Element::parent_element(self)
+    }
+
+    fn parent_node_is_shadow_root(&self) -> bool {
+        false
+    }
+
+    fn containing_shadow_host(&self) -> Option<Self> {
+        None
+    }
+
+    fn is_pseudo_element(&self) -> bool {
+        false
+    }
+
+    fn prev_sibling_element(&self) -> Option<Self> {
+        let entries = self.node.arena.entries.read();
+        let mut sibling = entries[self.node.id].links.previous_sibling;
+        while let Some(id) = sibling {
+            if let NodeData::Element { .. } = &*entries[id].data {
+                return Self::new(self.node.arena.node(&entries, id));
+            }
+            sibling = entries[id].links.previous_sibling;
+        }
+        None
+    }
+
+    fn next_sibling_element(&self) -> Option<Self> {
+        let entries = self.node.arena.entries.read();
+        let mut sibling = entries[self.node.id].links.next_sibling;
+        while let Some(id) = sibling {
+            if let NodeData::Element { .. } = &*entries[id].data {
+                return Self::new(self.node.arena.node(&entries, id));
+            }
+            sibling = entries[id].links.next_sibling;
+        }
+        None
+    }
+
+    fn first_element_child(&self) -> Option<Self> {
+        let entries = self.node.arena.entries.read();
+        let mut child = entries[self.node.id].links.first_child;
+        while let Some(id) = child {
+            if let NodeData::Element { .. } = &*entries[id].data {
+                return Self::new(self.node.arena.node(&entries, id));
+            }
+            child = entries[id].links.next_sibling;
+        }
+        None
+    }
+
+    fn is_html_element_in_html_document(&self) -> bool {
+        true
+    }
+
+    fn has_local_name(
+        &self,
+        local_name: &<Self::Impl as selectors::SelectorImpl>::BorrowedLocalName,
+    ) -> bool {
+        if self.node_type() == node::Type::Document {
+            false
+        } else {
+            self.local_name() == &local_name.0
+        }
+    }
+
+    fn has_namespace(
+        &self,
+        ns: &<Self::Impl as selectors::SelectorImpl>::BorrowedNamespaceUrl,
+    ) -> bool {
+        self.qual_name().ns() == &ns.0
+    }
+
+    fn is_same_type(&self, other: &Self) -> bool {
+        let name = self.qual_name();
+        let other_name = other.qual_name();
+
+        name.local_name() == other_name.local_name() && name.prefix() == other_name.prefix()
+    }
+
+    fn attr_matches(
+        &self,
+        ns: &selectors::attr::NamespaceConstraint<
+            &<Self::Impl as selectors::SelectorImpl>::NamespaceUrl,
+        >,
+        local_name: &<Self::Impl as selectors::SelectorImpl>::LocalName,
+        operation: &selectors::attr::AttrSelectorOperation<
+            &<Self::Impl as selectors::SelectorImpl>::AttrValue,
+        >,
+    ) -> bool {
+        use selectors::attr::NamespaceConstraint;
+
+        let value = match ns {
+            NamespaceConstraint::Any => self.get_attribute_local(&local_name.0),
+            NamespaceConstraint::Specific(ns) => self.get_attribute_ns(&ns.0, &local_name.0),
+        };
+        let Some(value) = value else {
+            return false;
+        };
+        operation.eval_str(value.as_ref())
+    }
+
+    fn match_non_ts_pseudo_class(
+        &self,
+        pc: &<Self::Impl as selectors::SelectorImpl>::NonTSPseudoClass,
+        _context: &mut selectors::context::MatchingContext<Self::Impl>,
+    ) -> bool {
+        use crate::selectors::PseudoClass;
+
+        match pc {
+            PseudoClass::Link(..) | PseudoClass::AnyLink(..) => self.is_link(),
+        }
+    }
+
+    fn match_pseudo_element(
+        &self,
+        _pe: &<Self::Impl as selectors::SelectorImpl>::PseudoElement,
+        _context: &mut selectors::context::MatchingContext<Self::Impl>,
+    ) -> bool {
+        false
+    }
+
+    fn apply_selector_flags(&self, flags: selectors::matching::ElementSelectorFlags) {
+        self.set_selector_flags(flags.for_self());
+
+        let Some(parent) = Element::parent_element(self) else {
+            return;
+        };
+        parent.set_selector_flags(flags.for_parent());
+    }
+
+    fn is_link(&self) -> bool {
+        if self.node_type() == node::Type::Document {
+            return false;
+        }
+        matches!(
+            self.local_name(),
+            &local_name!("a") | &local_name!("area") | &local_name!("link")
+        ) && self.has_attribute_local(&local_name!("href"))
+    }
+
+    fn is_html_slot_element(&self) -> bool {
+        false
+    }
+
+    fn has_id(
+        &self,
+        id: &<Self::Impl as selectors::SelectorImpl>::Identifier,
+        case_sensitivity: selectors::attr::CaseSensitivity,
+    ) -> bool {
+        let Some(self_id) = self.get_attribute_local(&local_name!("id")) else {
+            return false;
+        };
+        case_sensitivity.eq(id.0.as_bytes(), self_id.as_bytes())
+    }
+
+    fn has_class(
+        &self,
+        name: &<Self::Impl as selectors::SelectorImpl>::Identifier,
+        case_sensitivity: selectors::attr::CaseSensitivity,
+    ) -> bool {
+        if self.node_type() == node::Type::Document {
+            return false;
+        }
+
+        let Some(self_class) = self.get_attribute_local(&local_name!("class")) else {
+            return false;
+        };
+        let name = name.0.as_bytes();
+        self_class
+            .split_whitespace()
+            .any(|c| case_sensitivity.eq(name, c.as_bytes()))
+    }
+
+    fn imported_part(
+        &self,
+        _name: &<Self::Impl as selectors::SelectorImpl>::Identifier,
+    ) -> Option<<Self::Impl as selectors::SelectorImpl>::Identifier> {
+        None
+    }
+
+    fn is_part(&self, _name: &<Self::Impl as selectors::SelectorImpl>::Identifier) -> bool {
+        false
+    }
+
+    fn is_empty(&self) -> bool {
+        self.all_children(|child| match &*child.data {
+            NodeData::Text { contents } => contents.read().trim().is_empty(),
+            _ => false,
+        })
+    }
+
+    fn is_root(&self) -> bool {
+        let Some(parent) = self.parent_node() else {
+            return true;
+        };
+        parent.node_type() == node::Type::Document
+    }
+
+    fn has_custom_state(
+        &self,
+        _name: &<Self::Impl as selectors::SelectorImpl>::Identifier,
+    ) -> bool {
+        false
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn add_element_unique_hashes(&self, filter: &mut selectors::bloom::BloomFilter) -> bool {
+        let mut f = |hash: u32| filter.insert_hash(hash & selectors::bloom::BLOOM_HASH_MASK);
+
+        let local_name_hash = &mut DefaultHasher::default();
+        self.local_name().hash(local_name_hash);
+        f(local_name_hash.finish() as u32);
+
+        let prefix_hash = &mut DefaultHasher::default();
+        self.prefix().hash(prefix_hash);
+        f(prefix_hash.finish() as u32);
+
+        if let Some(id) = self.get_attribute_local(&local_name!("id")) {
+            let id_hash = &mut DefaultHasher::default();
+            id.hash(id_hash);
+            f(id_hash.finish() as u32);
+        }
+
+        for class in self.class_list().iter() {
+            let class_hash = &mut DefaultHasher::default();
+            class.hash(class_hash);
+            f(class_hash.finish() as u32);
+        }
+
+        for attr in self.attributes().into_iter() {
+            let name = attr.name();
+            if matches!(name.local_name().as_ref(), "class" | "id" | "style") {
+                continue;
+            }
+
+            let name_hash = &mut DefaultHasher::default();
+            name.hash(name_hash);
+            f(name_hash.finish() as u32);
+        }
+        true
+    }
+}
+
+impl Document for DocumentArena {
+    type Root = ElementArena;
+
+    fn document_element(&self) -> &Self::Root {
+        &self.0
+    }
+
+    fn create_attribute<'a>(
+        &self,
+        name: <<<Self::Root as Element>::Attributes<'a> as Attributes<'a>>::Attribute as Attr>::Name,
+    ) -> <<Self::Root as Element>::Attributes<'a> as Attributes<'a>>::Attribute {
+        AttributeArena {
+            name,
+            value: Atom::default(),
+        }
+    }
+
+    fn create_c_data_section(&self, data: Atom) -> NodeArena {
+        self.create_node(NodeData::CDataSection {
+            contents: RwLock::new(data),
+        })
+    }
+
+    fn create_element(&self, tag_name: QualName) -> Self::Root {
+        ElementArena {
+            node: self.create_node(NodeData::Element {
+                name: tag_name,
+                attrs: RwLock::new(vec![]),
+            }),
+        }
+    }
+
+    fn create_processing_instruction(&self, target: Atom, data: Atom) -> NodeArena {
+        self.create_node(NodeData::ProcessingInstruction {
+            target,
+            contents: data,
+        })
+    }
+
+    fn create_text_node(&self, data: Atom) -> NodeArena {
+        self.create_node(NodeData::Text {
+            contents: RwLock::new(data),
+        })
+    }
+}
+
+impl DocumentArena {
+    fn create_node(&self, data: NodeData) -> NodeArena {
+        self.0.node.arena.push(data)
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod test {
+    use super::{DocumentArena, ElementArena, NodeArena};
+    use crate::{
+        attribute::Attributes,
+        class_list::ClassList,
+        document::Document,
+        element::Element,
+        name::Name,
+        node::{Node, Type},
+        parse::Node as _,
+    };
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<NodeArena>();
+        assert_send_sync::<ElementArena>();
+        assert_send_sync::<DocumentArena>();
+    }
+
+    #[test]
+    fn parse() -> anyhow::Result<()> {
+        let dom = NodeArena::parse(
+            r##"<!DOCTYPE svg SYSTEM "svg.dtd"><svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+    <!-- comment -->
+    <g id="a" class="b c"><rect/>text &amp; <![CDATA[<data>]]></g>
+    <use xlink:href="#a"/>
+</svg>"##,
+        )?;
+        assert_eq!(dom.node_type(), Type::Document);
+        let doctype = dom.first_child().unwrap();
+        assert_eq!(doctype.node_type(), Type::DocumentType);
+        assert_eq!(doctype.node_name().as_ref(), "svg");
+
+        let root = <ElementArena as Element>::find_element(dom.clone()).unwrap();
+        assert_eq!(root.local_name().as_ref(), "svg");
+        assert_eq!(root.qual_name().ns.as_ref(), "http://www.w3.org/2000/svg");
+        assert_eq!(root.children().len(), 2);
+
+        let group = root.first_element_child().unwrap();
+        assert_eq!(
+            group.get_attribute_local(&"id".into()).as_deref(),
+            Some(&"a".into())
+        );
+        assert_eq!(group.class_list().length(), 2);
+        assert_eq!(group.text_content().as_deref(), Some("text & <data>"));
+        let c_data = group.last_child().unwrap();
+        assert_eq!(c_data.node_type(), Type::CDataSection);
+        assert_eq!(c_data.node_value().as_deref(), Some("<data>"));
+
+        let using = root.last_element_child().unwrap();
+        let href = using.attributes().item(0).unwrap();
+        assert_eq!(href.name.prefix.as_deref(), Some("xlink"));
+        assert_eq!(href.name.ns.as_ref(), "http://www.w3.org/1999/xlink");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "style")]
+    fn presentation() -> anyhow::Result<()> {
+        use crate::{attribute::Attr, style::PresentationAttr};
+
+        let dom = NodeArena::parse(
+            r#"<svg xmlns:x="https://example.com"><rect fill="red" x:fill="red" foo="bar"/></svg>"#,
+        )?;
+        let root = <ElementArena as Element>::find_element(dom.clone()).unwrap();
+        let rect = root.first_element_child().unwrap();
+        let attributes = rect.attributes();
+        assert!(matches!(
+            attributes.item(0).unwrap().presentation(),
+            Some(PresentationAttr::Fill(_))
+        ));
+        assert!(attributes.item(1).unwrap().presentation().is_none());
+        assert!(matches!(
+            attributes.item(2).unwrap().presentation(),
+            Some(PresentationAttr::Unknown(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn mutate() -> anyhow::Result<()> {
+        let dom = NodeArena::parse(r#"<svg><g id="a"><g><rect/></g></g><path/></svg>"#)?;
+        let root = <ElementArena as Element>::find_element(dom.clone()).unwrap();
+
+        let mut group = root.first_element_child().unwrap();
+        group.set_local_name("a".into());
+        group.set_attribute_local("id".into(), "b".into());
+        let inner = group.first_element_child().unwrap();
+        assert_eq!(inner.parent_element(), Some(group.clone()));
+        assert_eq!(inner.child_nodes().len(), 1);
+
+        let path = root.last_element_child().unwrap();
+        path.remove();
+        assert_eq!(root.children().len(), 1);
+        assert!(path.parent_node().is_none());
+
+        let circle = root
+            .as_document()
+            .create_element(Name::new(None, "circle".into()));
+        circle.set_attribute_local("r".into(), "1".into());
+        root.append(circle.as_parent_child());
+        let mut text = root.as_document().create_text_node("text".into());
+        root.clone().as_parent_child().append_child(text.clone());
+        text.set_text_content("new text".into());
+
+        assert_eq!(root.children().len(), 2);
+        assert_eq!(
+            root.last_child().unwrap().node_value().as_deref(),
+            Some("new text")
+        );
+        assert_eq!(
+            root.last_element_child()
+                .unwrap()
+                .get_attribute_local(&"r".into())
+                .as_deref(),
+            Some(&"1".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn serialize() -> anyhow::Result<()> {
+        use crate::serialize::{Node as _, Options};
+
+        let source = r##"<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd"><svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"><!--comment--><g id="a">a &amp; b<![CDATA[<data>]]></g><use xlink:href="#a"/></svg>"##;
+        let dom = NodeArena::parse(source)?;
+        assert_eq!(dom.serialize()?, source);
+
+        let root = <ElementArena as Element>::find_element(dom.clone()).unwrap();
+        root.first_element_child().unwrap().remove();
+        root.set_attribute_local("viewBox".into(), "0 0 1 1".into());
+        assert_eq!(
+            dom.serialize_with_options(Options::new().pretty().indent(2))?,
+            r##"
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 1 1">
+  <!--comment-->
+  <use xlink:href="#a"/>
+</svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn parse_recovery() -> anyhow::Result<()> {
+        use crate::serialize::Node as _;
+
+        let dom = NodeArena::parse("<svg><g></a><rect/></g></g></svg>")?;
+        assert_eq!(dom.serialize()?, "<svg><g><rect/></g></svg>");
+
+        let dom = NodeArena::parse("<svg><!-- a <g/>")?;
+        assert_eq!(dom.serialize()?, "<svg><!-- a <g/>--></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_limits() {
+        use crate::parse::{Error, Options};
+
+        let parse_error = |source: &str, options: &Options| {
+            NodeArena::parse_with_options(source, options)
+                .unwrap_err()
+                .downcast::<Error>()
+                .unwrap()
+        };
+
+        let source = r#"<svg><g a="1" b="2"><g/></g></svg>"#;
+        assert!(matches!(
+            parse_error(source, &Options::new().max_size(Some(10))),
+            Error::InputTooLarge { limit: 10 }
+        ));
+        assert!(matches!(
+            parse_error(source, &Options::new().max_depth(Some(2))),
+            Error::DepthLimitExceeded { limit: 2 }
+        ));
+        assert!(matches!(
+            parse_error(source, &Options::new().max_attributes(Some(1))),
+            Error::AttributeLimitExceeded { limit: 1 }
+        ));
+        assert!(matches!(
+            parse_error(
+                r#"<!DOCTYPE svg [<!ENTITY a "aaaa"><!ENTITY b "&a;&a;">]><svg>&b;</svg>"#,
+                &Options::new().max_entity_expansion(Some(8))
+            ),
+            Error::EntityExpansionLimitExceeded { limit: 8 }
+        ));
+    }
+}
@@ -4,13 +4,12 @@ use std::{
     fmt::Debug,
     hash::{DefaultHasher, Hash, Hasher},
     ops::Deref,
-    rc::Rc,
+    rc::{Rc, Weak},
 };
 
 use markup5ever::{
     local_name, tendril::StrTendril, Attribute, LocalName, Namespace, Prefix, QualName,
 };
-use rcdom::NodeData;
 use xml5ever::{namespace_url, ns};
 
 use crate::{
@@ -47,7 +46,7 @@ pub struct ClassList5Ever<'a> {
 }
 
 #[derive(Clone)]
-pub struct Node5Ever(Rc<rcdom::Node>);
+pub struct Node5Ever(Rc<TreeNode>);
 
 #[derive(Debug)]
 pub struct Node5EverRef(Rc<Node5Ever>);
@@ -61,6 +60,54 @@ pub struct Element5Ever {
 
 pub struct Document5Ever(Element5Ever);
 
+/// A node of the tree, which owns it's children and weakly references it's parent.
+///
+/// This matches `TreeNode`, with node data for CDATA sections and without the HTML specific
+/// element data.
+struct TreeNode {
+    parent: Cell<Option<Weak<TreeNode>>>,
+    children: RefCell<Vec<Rc<TreeNode>>>,
+    data: NodeData,
+}
+
+#[derive(Debug)]
+enum NodeData {
+    Document,
+    Doctype {
+        name: StrTendril,
+        public_id: StrTendril,
+        system_id: StrTendril,
+    },
+    Text {
+        contents: RefCell<StrTendril>,
+    },
+    CDataSection {
+        contents: RefCell<StrTendril>,
+    },
+    Comment {
+        contents: StrTendril,
+    },
+    Element {
+        name: QualName,
+        attrs: RefCell<Vec<Attribute>>,
+    },
+    ProcessingInstruction {
+        target: StrTendril,
+        contents: StrTendril,
+    },
+}
+
+impl Drop for TreeNode {
+    fn drop(&mut self) {
+        // NOTE: Descendants are emptied iteratively to avoid overflowing the stack with deep
+        // trees, so dropping a node also empties the children of descendants shared elsewhere
+        let mut nodes = std::mem::take(&mut *self.children.borrow_mut());
+        while let Some(node) = nodes.pop() {
+            nodes.extend(std::mem::take(&mut *node.children.borrow_mut()));
+        }
+    }
+}
+
 impl Name for QualName {
     type LocalName = LocalName;
     type Prefix = Prefix;
@@ -160,11 +207,13 @@ impl Attr for Attribute {
     }
 
     fn presentation(&self) -> Option<crate::style::PresentationAttr> {
-        self.name.prefix.as_ref()?;
+        if self.name.prefix.is_some() {
+            return None;
+        }
         let id = crate::style::PresentationAttrId::from(self.name.local.as_ref());
         crate::style::PresentationAttr::parse_string(
             id,
-            self.name.local.as_ref(),
+            self.value.as_ref(),
             lightningcss::stylesheet::ParserOptions::default(),
         )
         .ok()
@@ -281,55 +330,7 @@ impl<'a> Attributes<'a> for Attributes5Ever<'a> {
     }
 
     fn sort(&self, order: &[String], xmlns_front: bool) {
-        fn get_ns_priority(name: &QualName, xmlns_front: bool) -> usize {
-            if xmlns_front {
-                if name.prefix.is_none() && name.local == local_name!("xmlns") {
-                    return 3;
-                }
-                if name.prefix.as_ref().is_some_and(|p| p == "xmlns") {
-                    return 2;
-                }
-            }
-            if name.prefix.is_some() {
-                return 1;
-            }
-            0
-        }
-
-        self.0.borrow_mut().sort_by(|a, b| {
-            let a_priority = get_ns_priority(&a.name, xmlns_front);
-            let b_priority = get_ns_priority(&b.name, xmlns_front);
-            let priority_ord = b_priority.cmp(&a_priority);
-            if priority_ord != std::cmp::Ordering::Equal {
-                return priority_ord;
-            }
-
-            let a_part = a
-                .name
-                .local
-                .split_once('-')
-                .map_or_else(|| a.name.local.as_ref(), |p| p.0);
-            let b_part = b
-                .name
-                .local
-                .split_once('-')
-                .map_or_else(|| b.name.local.as_ref(), |p| p.0);
-            if a_part != b_part {
-                let a_in_order = order.iter().position(|x| x == a_part);
-                let b_in_order = order.iter().position(|x| x == b_part);
-                if a_in_order.is_some() && b_in_order.is_some() {
-                    return a_in_order.cmp(&b_in_order);
-                }
-                if a_in_order.is_some() {
-                    return std::cmp::Ordering::Less;
-                }
-                if b_in_order.is_some() {
-                    return std::cmp::Ordering::Greater;
-                }
-            }
-
-            a.name.cmp(&b.name)
-        });
+        crate::attribute::sort(&mut self.0.borrow_mut(), order, xmlns_front);
     }
 
     fn retain<F>(&self, mut f: F)
@@ -531,9 +532,11 @@ impl Node5Ever {
     /// recursive calls.
     ///
     /// > returns the concatenation of the textContent of every child node, excluding comments and processing instructions. (This is an empty string if the node has no children.)
-    fn node_data_text_content(node: &Rc<rcdom::Node>) -> Option<String> {
+    fn node_data_text_content(node: &Rc<TreeNode>) -> Option<String> {
         match &node.data {
-            NodeData::Text { contents } => Some(contents.borrow().to_string()),
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
+                Some(contents.borrow().to_string())
+            }
             NodeData::Doctype { .. } | NodeData::Document => None,
             NodeData::Comment { .. } | NodeData::ProcessingInstruction { .. } => {
                 Some(String::new())
@@ -573,16 +576,12 @@ impl Node5Ever {
             NodeData::Text { contents } => NodeData::Text {
                 contents: contents.clone(),
             },
-            NodeData::Element {
-                name,
-                attrs,
-                template_contents,
-                mathml_annotation_xml_integration_point,
-            } => NodeData::Element {
+            NodeData::CDataSection { contents } => NodeData::CDataSection {
+                contents: contents.clone(),
+            },
+            NodeData::Element { name, attrs } => NodeData::Element {
                 name: name.clone(),
                 attrs: attrs.clone(),
-                template_contents: template_contents.clone(),
-                mathml_annotation_xml_integration_point: *mathml_annotation_xml_integration_point,
             },
         }
     }
@@ -702,6 +701,7 @@ impl Node for Node5Ever {
             NodeData::Element { .. } => node::Type::Element,
             NodeData::ProcessingInstruction { .. } => node::Type::ProcessingInstruction,
             NodeData::Text { .. } => node::Type::Text,
+            NodeData::CDataSection { .. } => node::Type::CDataSection,
         }
     }
 
@@ -744,6 +744,7 @@ impl Node for Node5Ever {
             NodeData::Element { name, .. } => name.local.to_uppercase().into(),
             NodeData::ProcessingInstruction { target, .. } => target.clone(),
             NodeData::Text { .. } => "#text".into(),
+            NodeData::CDataSection { .. } => "#cdata-section".into(),
         }
     }
 
@@ -752,7 +753,9 @@ impl Node for Node5Ever {
             NodeData::Comment { contents } | NodeData::ProcessingInstruction { contents, .. } => {
                 contents.clone()
             }
-            NodeData::Text { contents } => contents.borrow().clone(),
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
+                contents.borrow().clone()
+            }
             _ => return None,
         })
     }
@@ -766,7 +769,7 @@ impl Node for Node5Ever {
 
     fn try_set_node_value(&self, value: Self::Atom) -> Option<()> {
         match &self.0.data {
-            NodeData::Text { contents } => {
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
                 contents.replace(value);
                 Some(())
             }
@@ -784,14 +787,18 @@ impl Node for Node5Ever {
             NodeData::Comment { contents } | NodeData::ProcessingInstruction { contents, .. } => {
                 Some(contents.to_string())
             }
-            NodeData::Text { contents } => Some(contents.borrow().to_string()),
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
+                Some(contents.borrow().to_string())
+            }
             NodeData::Element { .. } => Some(String::new()),
         }
     }
 
     fn set_text_content(&mut self, content: Self::Atom) {
         match &self.0.data {
-            NodeData::Text { contents } => *contents.borrow_mut() = content,
+            NodeData::Text { contents } | NodeData::CDataSection { contents } => {
+                *contents.borrow_mut() = content;
+            }
             NodeData::Element { .. } => {
                 let text = self.text(content);
                 self.empty();
@@ -802,7 +809,7 @@ impl Node for Node5Ever {
     }
 
     fn text(&self, content: Self::Atom) -> Self {
-        Node5Ever(Rc::new(rcdom::Node {
+        Node5Ever(Rc::new(TreeNode {
             parent: Cell::new(None),
             children: RefCell::new(vec![]),
             data: NodeData::Text {
@@ -832,7 +839,7 @@ impl Node for Node5Ever {
 
     fn clone_node(&self) -> Self {
         let children = self.0.children.borrow().iter().cloned().collect();
-        Self(Rc::new(rcdom::Node {
+        Self(Rc::new(TreeNode {
             parent: Cell::new(None),
             data: self.clone_node_data(),
             children: RefCell::new(children),
@@ -885,40 +892,635 @@ impl node::Features for Node5Ever {}
 
 #[cfg(feature = "parse")]
 impl parse::Node for Node5Ever {
-    fn parse(source: &str) -> anyhow::Result<Self> {
-        use xml5ever::{
-            driver::{parse_document, XmlParseOpts},
-            tendril::TendrilSink,
+    fn parse_with_options(source: &str, options: &parse::Options) -> anyhow::Result<Self> {
+        TreeBuilder::new(options).build(source).map(Node5Ever)
+    }
+}
+
+#[cfg(all(feature = "parse", feature = "serialize"))]
+impl Node5Ever {
+    /// Parses the source while recording the markup of each node, so that nodes left untouched
+    /// can be serialized exactly as written with [`Node5Ever::serialize_lossless`].
+    ///
+    /// # Errors
+    ///
+    /// Any error caused by the underlying parser
+    pub fn parse_with_source_map(source: &str) -> anyhow::Result<(Self, serialize::SourceMap)> {
+        let mut builder = TreeBuilder {
+            source_map: Some(serialize::SourceMap::default()),
+            ..TreeBuilder::default()
         };
-        let dom: rcdom::RcDom =
-            parse_document(rcdom::RcDom::default(), XmlParseOpts::default()).one(source);
+        let root = builder.build(source).map(Node5Ever)?;
+        Ok((root, builder.source_map.unwrap_or_default()))
+    }
 
-        Ok(Node5Ever(dom.document))
+    /// Serializes the node, reproducing the original markup of nodes recorded in the source map
+    /// that haven't changed since parsing, including whitespace, quote style, entity references
+    /// and self-closing tags.
+    ///
+    /// Nodes that were edited or created after parsing are written using the `quote` and
+    /// `self_closing` options, without any other formatting.
+    ///
+    /// # Errors
+    /// If the underlying serialization fails
+    pub fn serialize_lossless(
+        &self,
+        options: serialize::Options,
+        source_map: &serialize::SourceMap,
+    ) -> anyhow::Result<String> {
+        use crate::serialize::Serialize as _;
+
+        let mut sink = Vec::new();
+        let mut serializer = serialize::Serializer::new(&mut sink)
+            .options(options)
+            .lossless(source_map.clone());
+        SerializableHandle {
+            handle: self.clone(),
+        }
+        .serialize(
+            &mut serializer,
+            xml5ever::serialize::SerializeOpts::default().traversal_scope,
+        )?;
+        serializer.finish()?;
+        drop(serializer);
+        Ok(String::from_utf8_lossy(&sink).to_string())
+    }
+
+    /// Returns a key representing the content of a node that isn't an element or text, which is
+    /// used to check whether it's changed since parsing.
+    fn source_key(data: &NodeData) -> Option<String> {
+        match data {
+            NodeData::Comment { contents } => Some(contents.to_string()),
+            NodeData::ProcessingInstruction { target, contents } => {
+                Some(format!("{target}\n{contents}"))
+            }
+            NodeData::Doctype { name, .. } => Some(name.to_string()),
+            NodeData::Element { .. }
+            | NodeData::Text { .. }
+            | NodeData::CDataSection { .. }
+            | NodeData::Document => None,
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+#[derive(Default)]
+/// Builds a tree from the events of an xml reader.
+///
+/// Unlike xml5ever's tree builder, every attribute is kept in the order of the source, including
+/// namespace declarations and attributes that share a local name (e.g. `xml:lang` and `lang`).
+struct TreeBuilder {
+    /// The element's the reader is currently within, starting with the document
+    open_elements: Vec<Rc<TreeNode>>,
+    /// The namespaces and entities in scope
+    context: ReaderContext,
+    /// The markup of each node, when recording for lossless serialization
+    #[cfg(feature = "serialize")]
+    source_map: Option<serialize::SourceMap>,
+    /// Whitespace outside of the root element that's yet to be recorded
+    #[cfg(feature = "serialize")]
+    prefix: String,
+}
+
+#[cfg(feature = "parse")]
+impl TreeBuilder {
+    fn new(options: &parse::Options) -> Self {
+        Self {
+            context: ReaderContext::new(options.clone()),
+            ..Self::default()
+        }
     }
 
-    fn parse_file(mut file: &std::fs::File) -> anyhow::Result<Self> {
-        use xml5ever::{
-            driver::{parse_document, XmlParseOpts},
-            tendril::{ReadExt, Tendril, TendrilSink},
+    fn build(&mut self, source: &str) -> anyhow::Result<Rc<TreeNode>> {
+        use quick_xml::events::Event;
+
+        self.context.check_size(source)?;
+        let document = Document5Ever::create_node(NodeData::Document).0;
+        self.open_elements.push(document.clone());
+
+        let source = match source.strip_prefix('\u{feff}') {
+            Some(source) => {
+                self.record_prefix("\u{feff}");
+                source
+            }
+            None => source,
         };
+        let mut reader = quick_xml::Reader::from_str(source);
+        reader.trim_text(false).check_end_names(false);
+        loop {
+            let start_position = reader.buffer_position();
+            let event = ReaderContext::read_event(&mut reader, source)?;
+            let raw = &source[start_position..reader.buffer_position()];
+            match event {
+                Event::Start(start) => {
+                    let element = self.create_element(&start)?;
+                    self.append(element.clone(), raw);
+                    self.open_elements.push(element);
+                }
+                Event::Empty(start) => {
+                    let element = self.create_element(&start)?;
+                    self.append(element, raw);
+                    self.context.close(self.open_elements.len());
+                }
+                Event::End(end) => {
+                    // NOTE: As with xml5ever, an end tag closes any elements left open within
+                    // the element it matches, and is ignored when it matches no open element
+                    let Some(index) = self.open_elements.iter().rposition(|node| {
+                        matches!(&node.data, NodeData::Element { name, .. } if ReaderContext::is_end_of(&end, name))
+                    }) else {
+                        continue;
+                    };
+                    let element = self.open_elements.drain(index..).next();
+                    if let Some(element) = element {
+                        self.record_end(&element, raw);
+                    }
+                    self.context.close(self.open_elements.len());
+                }
+                Event::Text(text) => {
+                    // NOTE: The reader's position is short of text trailing the document, so the
+                    // escaped content is used instead
+                    let text = std::str::from_utf8(&text)?;
+                    let contents = self.context.unescape(text)?;
+                    self.append_text(&contents, text);
+                }
+                Event::CData(c_data) => {
+                    let contents = std::str::from_utf8(&c_data)?;
+                    self.append(
+                        Self::create_node(NodeData::CDataSection {
+                            contents: RefCell::new(contents.into()),
+                        }),
+                        raw,
+                    );
+                }
+                Event::Comment(comment) => {
+                    let contents = std::str::from_utf8(&comment)?;
+                    self.append(
+                        Self::create_node(NodeData::Comment {
+                            contents: contents.into(),
+                        }),
+                        raw,
+                    );
+                }
+                Event::Decl(declaration) => {
+                    let contents = std::str::from_utf8(&declaration)?;
+                    self.append_processing_instruction(contents, raw);
+                }
+                Event::PI(instruction) => {
+                    let contents = std::str::from_utf8(&instruction)?;
+                    self.append_processing_instruction(contents, raw);
+                }
+                Event::DocType(doctype) => {
+                    let doctype = std::str::from_utf8(&doctype)?;
+                    self.context.declare_entities(doctype)?;
+                    let (name, public_id, system_id) = ReaderContext::doctype(doctype);
+                    self.append(
+                        Self::create_node(NodeData::Doctype {
+                            name: name.into(),
+                            public_id: public_id.into(),
+                            system_id: system_id.into(),
+                        }),
+                        raw,
+                    );
+                }
+                Event::Eof => break,
+            }
+        }
+        self.record_suffix(&document);
+        Ok(document)
+    }
+
+    fn create_node(data: NodeData) -> Rc<TreeNode> {
+        Document5Ever::create_node(data).0
+    }
+
+    fn create_element(
+        &mut self,
+        start: &quick_xml::events::BytesStart,
+    ) -> anyhow::Result<Rc<TreeNode>> {
+        let (name, attrs) = self.context.start(start, self.open_elements.len())?;
+        Ok(Self::create_node(NodeData::Element {
+            name,
+            attrs: RefCell::new(
+                attrs
+                    .into_iter()
+                    .map(|(name, value)| Attribute { name, value })
+                    .collect(),
+            ),
+        }))
+    }
 
-        let mut tendril = Tendril::new();
-        file.read_to_tendril(&mut tendril)?;
+    /// Appends the node to the current element, recording the markup it was parsed from
+    fn append(&mut self, node: Rc<TreeNode>, raw: &str) {
+        self.record(&node, raw);
+        let parent = self
+            .open_elements
+            .last()
+            .expect("document should always be open");
+        node.parent.set(Some(Rc::downgrade(parent)));
+        parent.children.borrow_mut().push(node);
+    }
+
+    fn append_text(&mut self, text: &str, raw: &str) {
+        let parent = self
+            .open_elements
+            .last()
+            .expect("document should always be open");
+        if matches!(parent.data, NodeData::Document) && text.trim().is_empty() {
+            self.record_prefix(raw);
+            return;
+        }
+        let last_child = parent.children.borrow().last().cloned();
+        if let Some(last_child) = last_child {
+            if let NodeData::Text { contents } = &last_child.data {
+                contents.borrow_mut().push_slice(text);
+                self.record_text(&last_child, text, raw);
+                return;
+            }
+        }
+        self.append(
+            Self::create_node(NodeData::Text {
+                contents: RefCell::new(text.into()),
+            }),
+            raw,
+        );
+    }
 
-        let mut parser =
-            parse_document(rcdom::RcDom::default(), XmlParseOpts::default()).from_utf8();
-        parser.process(tendril);
-        let dom: rcdom::RcDom = parser.finish();
+    fn append_processing_instruction(&mut self, contents: &str, raw: &str) {
+        let (target, contents) = contents
+            .split_once(char::is_whitespace)
+            .unwrap_or((contents, ""));
+        self.append(
+            Self::create_node(NodeData::ProcessingInstruction {
+                target: target.into(),
+                contents: contents.trim_start().into(),
+            }),
+            raw,
+        );
+    }
+}
+
+#[cfg(feature = "parse")]
+#[derive(Default)]
+/// The namespaces and entities in scope while reading a document, which resolves the names and
+/// references of each node for a tree builder.
+pub(crate) struct ReaderContext {
+    /// The namespaces in scope, along with the depth of the element that declared it
+    namespaces: Vec<(Option<Prefix>, Namespace, usize)>,
+    /// The entities declared by the document type
+    entities: std::collections::HashMap<String, String>,
+    /// The limits of the document being read
+    options: parse::Options,
+    /// The number of bytes references to declared entities have expanded to so far
+    expanded: usize,
+}
+
+#[cfg(feature = "parse")]
+impl ReaderContext {
+    pub(crate) fn new(options: parse::Options) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
 
-        Ok(Node5Ever(dom.document))
+    /// Checks the length of the source against the limits of the context
+    pub(crate) fn check_size(&self, source: &str) -> Result<(), parse::Error> {
+        self.options.check_size(source.len())
     }
 
-    fn parse_path(path: &std::path::Path) -> anyhow::Result<Self> {
-        let file = std::fs::File::open(path)?;
-        Self::parse_file(&file)
+    /// Resolves the name and attributes of an element opened at the given depth, declaring the
+    /// namespaces of any `xmlns` attributes.
+    pub(crate) fn start<V>(
+        &mut self,
+        start: &quick_xml::events::BytesStart,
+        depth: usize,
+    ) -> anyhow::Result<(QualName, Vec<(QualName, V)>)>
+    where
+        V: for<'a> From<&'a str>,
+    {
+        self.options.check_depth(depth)?;
+        let mut attrs = vec![];
+        for attr in start.attributes().with_checks(false) {
+            let attr = attr?;
+            self.options.check_attributes(attrs.len() + 1)?;
+            let mut name = Self::qual_name(attr.key)?;
+            let value = self.unescape(std::str::from_utf8(&attr.value)?)?;
+            match &name.prefix {
+                Some(prefix) if prefix == "xmlns" => {
+                    self.namespaces.push((
+                        Some(name.local.as_ref().into()),
+                        Namespace::from(value.as_ref()),
+                        depth,
+                    ));
+                    name.ns = ns!(xmlns);
+                }
+                None if name.local == local_name!("xmlns") => {
+                    self.namespaces
+                        .push((None, Namespace::from(value.as_ref()), depth));
+                    name.ns = ns!(xmlns);
+                }
+                _ => {}
+            }
+            attrs.push((name, V::from(value.as_ref())));
+        }
+        for (name, _) in &mut attrs {
+            if name.prefix.is_some() && name.ns.is_empty() {
+                name.ns = self.resolve(name.prefix.as_ref());
+            }
+        }
+
+        let mut name = Self::qual_name(start.name())?;
+        name.ns = self.resolve(name.prefix.as_ref());
+        Ok((name, attrs))
+    }
+
+    /// Reads the next event, where a comment left open at the end of the source is read as a
+    /// comment of the remaining source, as with xml5ever
+    pub(crate) fn read_event<'a>(
+        reader: &mut quick_xml::Reader<&'a [u8]>,
+        source: &'a str,
+    ) -> quick_xml::Result<quick_xml::events::Event<'a>> {
+        use quick_xml::events::{BytesText, Event};
+
+        let start = reader.buffer_position();
+        match reader.read_event() {
+            Err(quick_xml::Error::UnexpectedEof(kind)) if kind == "Comment" => {
+                let rest = &source[start..];
+                let contents = rest.strip_prefix("<!--").unwrap_or(rest);
+                Ok(Event::Comment(BytesText::from_escaped(contents)))
+            }
+            event => event,
+        }
+    }
+
+    /// Returns whether the end tag closes an element of the given name
+    pub(crate) fn is_end_of(end: &quick_xml::events::BytesEnd, name: &QualName) -> bool {
+        let end = end.name();
+        end.local_name().as_ref() == name.local.as_bytes()
+            && end.prefix().map(quick_xml::name::Prefix::into_inner)
+                == name.prefix.as_ref().map(|prefix| prefix.as_bytes())
+    }
+
+    /// Removes the namespaces declared by the element that's been closed, where the depth is
+    /// that of the element's parent
+    pub(crate) fn close(&mut self, depth: usize) {
+        while self.namespaces.last().is_some_and(|ns| ns.2 >= depth) {
+            self.namespaces.pop();
+        }
+    }
+
+    fn qual_name(name: quick_xml::name::QName) -> anyhow::Result<QualName> {
+        Ok(QualName {
+            prefix: name
+                .prefix()
+                .map(|prefix| std::str::from_utf8(prefix.into_inner()))
+                .transpose()?
+                .map(Prefix::from),
+            ns: Namespace::default(),
+            local: std::str::from_utf8(name.local_name().into_inner())?.into(),
+        })
+    }
+
+    /// Returns the namespace in scope for the given prefix
+    fn resolve(&self, prefix: Option<&Prefix>) -> Namespace {
+        match prefix.map(AsRef::as_ref) {
+            Some("xml") => return ns!(xml),
+            Some("xmlns") => return ns!(xmlns),
+            _ => {}
+        }
+        self.namespaces
+            .iter()
+            .rev()
+            .find(|(p, ..)| p.as_ref() == prefix)
+            .map(|(_, ns, _)| ns.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the name, public id, and system id of the document type, where a missing id is
+    /// empty
+    pub(crate) fn doctype(doctype: &str) -> (&str, &str, &str) {
+        fn quoted<'a>(rest: &mut &'a str) -> &'a str {
+            let value = rest.trim_start();
+            let Some(quote) = value.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+                return "";
+            };
+            let Some(end) = value[1..].find(quote) else {
+                return "";
+            };
+            *rest = &value[end + 2..];
+            &value[1..=end]
+        }
+
+        let doctype = doctype.trim_start();
+        let (name, rest) = doctype
+            .split_once(|c: char| c.is_whitespace() || c == '[')
+            .unwrap_or((doctype, ""));
+        let rest = rest.trim_start();
+        if let Some(mut rest) = rest.strip_prefix("PUBLIC") {
+            let public_id = quoted(&mut rest);
+            (name, public_id, quoted(&mut rest))
+        } else if let Some(mut rest) = rest.strip_prefix("SYSTEM") {
+            (name, "", quoted(&mut rest))
+        } else {
+            (name, "", "")
+        }
+    }
+
+    /// Collects the internal entities declared within the document type, such as
+    /// `<!ENTITY ns_svg "http://www.w3.org/2000/svg">`
+    pub(crate) fn declare_entities(&mut self, doctype: &str) -> Result<(), parse::Error> {
+        const ENTITY: &str = "<!ENTITY";
+        let mut rest = doctype;
+        while let Some(index) = rest.find(ENTITY) {
+            rest = rest[index + ENTITY.len()..].trim_start();
+            let Some((name, value)) = rest.split_once(char::is_whitespace) else {
+                break;
+            };
+            let value = value.trim_start();
+            let Some(quote) = value.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+                continue;
+            };
+            let Some(end) = value[1..].find(quote) else {
+                break;
+            };
+            if name != "%" {
+                let value = self.unescape(&value[1..=end])?.into_owned();
+                self.entities.insert(name.to_string(), value);
+            }
+            rest = &value[end + 2..];
+        }
+        Ok(())
+    }
+
+    /// Replaces character and entity references in the text, leaving unknown references as-is.
+    ///
+    /// # Errors
+    ///
+    /// If references to declared entities expand beyond the limits of the context
+    pub(crate) fn unescape<'a>(
+        &mut self,
+        text: &'a str,
+    ) -> Result<std::borrow::Cow<'a, str>, parse::Error> {
+        if !text.contains('&') {
+            return Ok(std::borrow::Cow::Borrowed(text));
+        }
+
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(index) = rest.find('&') {
+            result.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+            let reference =
+                rest.split_once(';')
+                    .map(|(reference, _)| reference)
+                    .filter(|reference| {
+                        reference.chars().all(|c| {
+                            c.is_alphanumeric() || matches!(c, '#' | '_' | '-' | '.' | ':')
+                        })
+                    });
+            let Some(reference) = reference else {
+                result.push('&');
+                continue;
+            };
+            if let Some(value) = Self::resolve_reference(reference) {
+                result.push_str(&value);
+            } else if let Some(value) = self.entities.get(reference) {
+                result.push_str(value);
+                self.expanded += value.len();
+                self.options.check_entity_expansion(self.expanded)?;
+            } else {
+                result.push('&');
+                continue;
+            }
+            rest = &rest[reference.len() + 1..];
+        }
+        result.push_str(rest);
+        Ok(std::borrow::Cow::Owned(result))
+    }
+
+    /// Resolves character references and the entities predefined by xml
+    fn resolve_reference(reference: &str) -> Option<std::borrow::Cow<'static, str>> {
+        if let Some(code) = reference.strip_prefix('#') {
+            let code = match code.strip_prefix('x') {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            return char::from_u32(code).map(|c| c.to_string().into());
+        }
+        Some(
+            match reference {
+                "lt" => "<",
+                "gt" => ">",
+                "amp" => "&",
+                "apos" => "'",
+                "quot" => "\"",
+                _ => return None,
+            }
+            .into(),
+        )
     }
 }
 
+#[cfg(all(feature = "parse", feature = "serialize"))]
+impl TreeBuilder {
+    fn record(&mut self, node: &Rc<TreeNode>, raw: &str) {
+        use serialize::{ElementSource, Markup, NodeSource};
+
+        let Some(source_map) = &mut self.source_map else {
+            return;
+        };
+        let markup = match &node.data {
+            NodeData::Element { name, attrs, .. } => {
+                let attrs: Vec<_> = attrs
+                    .borrow()
+                    .iter()
+                    .map(|attr| (attr.name.clone(), attr.value.to_string()))
+                    .collect();
+                match ElementSource::parse(raw, name.clone(), &attrs) {
+                    Some(source) => Markup::Element(source),
+                    None => return,
+                }
+            }
+            NodeData::Text { contents } => Markup::Text {
+                value: contents.borrow().to_string(),
+                raw: raw.to_string(),
+            },
+            data => match Node5Ever::source_key(data) {
+                Some(key) => Markup::Other {
+                    key,
+                    raw: raw.to_string(),
+                },
+                None => return,
+            },
+        };
+        let prefix = std::mem::take(&mut self.prefix);
+        source_map.insert(node, NodeSource { prefix, markup });
+    }
+
+    /// Records whitespace outside of the root element, which will be written before the next node
+    fn record_prefix(&mut self, raw: &str) {
+        if self.source_map.is_some() {
+            self.prefix.push_str(raw);
+        }
+    }
+
+    /// Records text that's been merged into an existing text node
+    fn record_text(&mut self, node: &Rc<TreeNode>, text: &str, raw: &str) {
+        let Some(source_map) = &mut self.source_map else {
+            return;
+        };
+        if let Some(serialize::NodeSource {
+            markup: serialize::Markup::Text { value, raw: source },
+            ..
+        }) = source_map.get_mut(node)
+        {
+            value.push_str(text);
+            source.push_str(raw);
+        }
+    }
+
+    fn record_end(&mut self, element: &Rc<TreeNode>, raw: &str) {
+        let Some(source_map) = &mut self.source_map else {
+            return;
+        };
+        if let Some(serialize::NodeSource {
+            markup: serialize::Markup::Element(source),
+            ..
+        }) = source_map.get_mut(element)
+        {
+            source.end = Some(raw.to_string());
+        }
+    }
+
+    /// Records the whitespace trailing the root element
+    fn record_suffix(&mut self, document: &Rc<TreeNode>) {
+        let Some(source_map) = &mut self.source_map else {
+            return;
+        };
+        let suffix = std::mem::take(&mut self.prefix);
+        source_map.insert(
+            document,
+            serialize::NodeSource {
+                prefix: String::new(),
+                markup: serialize::Markup::Document { suffix },
+            },
+        );
+    }
+}
+
+#[cfg(all(feature = "parse", not(feature = "serialize")))]
+impl TreeBuilder {
+    fn record(&mut self, _node: &Rc<TreeNode>, _raw: &str) {}
+
+    fn record_prefix(&mut self, _raw: &str) {}
+
+    fn record_text(&mut self, _node: &Rc<TreeNode>, _text: &str, _raw: &str) {}
+
+    fn record_end(&mut self, _element: &Rc<TreeNode>, _raw: &str) {}
+
+    fn record_suffix(&mut self, _document: &Rc<TreeNode>) {}
+}
+
 #[cfg(feature = "serialize")]
 struct SerializableHandle {
     handle: Node5Ever,
@@ -927,7 +1529,42 @@ struct SerializableHandle {
 #[cfg(feature = "serialize")]
 enum SerializeOp {
     Open(Node5Ever),
-    Close(QualName),
+    /// Closes the element, with the end tag as written in the source if it's unchanged
+    Close(QualName, Option<String>),
+}
+
+#[cfg(feature = "serialize")]
+impl Node5Ever {
+    /// Returns whether the node, which isn't an element, is unchanged since it's markup was
+    /// recorded
+    fn matches_source(&self, source: &serialize::NodeSource) -> bool {
+        use crate::serialize::Markup;
+
+        match &source.markup {
+            Markup::Text { value, .. } => {
+                matches!(&self.0.data, NodeData::Text { contents } if **contents.borrow() == **value)
+            }
+            Markup::Other { key, .. } => Node5Ever::source_key(&self.0.data).as_ref() == Some(key),
+            Markup::Element(_) | Markup::Document { .. } => false,
+        }
+    }
+
+    /// Returns whether the element should be written as an empty element, where lossless
+    /// serialization keeps the form of the element's source
+    fn is_empty_element(
+        &self,
+        is_lossless: bool,
+        source: Option<&serialize::ElementSource>,
+    ) -> bool {
+        let children = self.0.children.borrow();
+        if is_lossless {
+            return children.is_empty() && source.is_none_or(|source| source.self_closing);
+        }
+        children.iter().all(|child| match child.data {
+            NodeData::Text { ref contents } => contents.borrow().trim().is_empty(),
+            _ => false,
+        })
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -937,8 +1574,16 @@ impl crate::serialize::Serialize for SerializableHandle {
         serializer: &mut crate::serialize::Serializer<Wr>,
         traversal_scope: xml5ever::serialize::TraversalScope,
     ) -> std::io::Result<()> {
+        use crate::serialize::{Markup, NodeSource};
         use xml5ever::serialize::TraversalScope;
+
+        let source_map = serializer.source_map();
+        let source = |node: &Rc<TreeNode>| source_map.as_ref().and_then(|map| map.get(node));
         let mut ops = VecDeque::new();
+        let suffix = match traversal_scope {
+            TraversalScope::ChildrenOnly(_) => source(&self.handle.0).and_then(NodeSource::suffix),
+            TraversalScope::IncludeNode => None,
+        };
         match traversal_scope {
             TraversalScope::IncludeNode => ops.push_back(SerializeOp::Open(self.handle.clone())),
             TraversalScope::ChildrenOnly(_) => ops.extend(
@@ -952,24 +1597,24 @@ impl crate::serialize::Serialize for SerializableHandle {
         while let Some(op) = ops.pop_front() {
             match op {
                 SerializeOp::Open(handle) => {
+                    let source = source(&handle.0);
+                    if let Some(source) = source {
+                        serializer.write_raw(&source.prefix)?;
+                    }
                     match handle.0.data {
                         NodeData::Element {
                             ref name,
                             ref attrs,
                             ..
                         } => {
-                            let children = handle.0.children.borrow();
-                            let is_empty = children.is_empty()
-                                || children.iter().all(|e| match e.data {
-                                    NodeData::Text { ref contents } => {
-                                        contents.borrow().trim().is_empty()
-                                    }
-                                    _ => false,
-                                });
-                            serializer.start_elem(
+                            let element_source = source.and_then(NodeSource::element);
+                            let is_empty =
+                                handle.is_empty_element(serializer.is_lossless(), element_source);
+                            serializer.start_elem_with_source(
                                 name,
                                 attrs.borrow().iter().map(|a| (&a.name, &a.value[..])),
                                 is_empty,
+                                element_source,
                             )?;
                             if is_empty {
                                 continue;
@@ -977,19 +1622,36 @@ impl crate::serialize::Serialize for SerializableHandle {
 
                             let child_len = handle.0.children.borrow().len();
                             ops.reserve(1 + child_len);
-                            if child_len > 0 {
-                                ops.push_front(SerializeOp::Close(name.clone()));
-                            }
+                            let end = element_source
+                                .filter(|source| source.name == *name)
+                                .and_then(|source| source.end.clone());
+                            ops.push_front(SerializeOp::Close(name.clone(), end));
 
                             for child in handle.0.children.borrow().iter().rev() {
                                 ops.push_front(SerializeOp::Open(Node5Ever(child.clone())));
                             }
                         }
-                        NodeData::Doctype { ref name, .. } => serializer.write_doctype(name)?,
+                        _ if source.is_some_and(|source| handle.matches_source(source)) => {
+                            if let Some(NodeSource {
+                                markup: Markup::Text { raw, .. } | Markup::Other { raw, .. },
+                                ..
+                            }) = source
+                            {
+                                serializer.write_raw(raw)?;
+                            }
+                        }
+                        NodeData::Doctype {
+                            ref name,
+                            ref public_id,
+                            ref system_id,
+                        } => serializer.write_doctype_with_ids(name, public_id, system_id)?,
                         NodeData::Text { ref contents, .. } => {
                             serializer.write_text(&contents.borrow())?;
                         }
                         NodeData::Comment { ref contents } => serializer.write_comment(contents)?,
+                        NodeData::CDataSection { ref contents } => {
+                            serializer.write_c_data_section(&contents.borrow())?;
+                        }
                         NodeData::ProcessingInstruction {
                             ref target,
                             ref contents,
@@ -997,12 +1659,15 @@ impl crate::serialize::Serialize for SerializableHandle {
                         NodeData::Document => panic!("Can't serialize Document node itself"),
                     };
                 }
-                SerializeOp::Close(name) => {
-                    serializer.end_elem(&name)?;
+                SerializeOp::Close(name, end) => {
+                    serializer.end_elem_with_source(&name, end.as_deref())?;
                 }
             }
         }
 
+        if let Some(suffix) = suffix {
+            serializer.write_raw(suffix)?;
+        }
         Ok(())
     }
 }
@@ -1014,17 +1679,8 @@ impl serialize::Node for Node5Ever {
     }
 
     fn serialize_with_options(&self, options: serialize::Options) -> anyhow::Result<String> {
-        let mut sink: std::io::BufWriter<_> = std::io::BufWriter::new(Vec::new());
-        crate::serialize::with_options(
-            &mut sink,
-            &SerializableHandle {
-                handle: self.clone(),
-            },
-            xml5ever::serialize::SerializeOpts::default(),
-            options,
-        )?;
-
-        let sink: Vec<_> = sink.into_inner()?;
+        let mut sink = Vec::new();
+        self.serialize_into_with_options(&mut sink, options)?;
         Ok(String::from_utf8_lossy(&sink).to_string())
     }
 
@@ -1039,6 +1695,21 @@ impl serialize::Node for Node5Ever {
             SerializeOpts::default(),
         )?)
     }
+
+    fn serialize_into_with_options<Wr: std::io::Write>(
+        &self,
+        sink: Wr,
+        options: serialize::Options,
+    ) -> anyhow::Result<()> {
+        Ok(crate::serialize::with_options(
+            sink,
+            &SerializableHandle {
+                handle: self.clone(),
+            },
+            xml5ever::serialize::SerializeOpts::default(),
+            options,
+        )?)
+    }
 }
 
 impl Element for Element5Ever {
@@ -1089,15 +1760,10 @@ impl Element for Element5Ever {
 
     fn set_local_name(&mut self, new_name: <Self::Name as Name>::LocalName) {
         let mut data = self.node.clone_node_data();
-        if let rcdom::NodeData::Element { name, .. } = &mut data {
+        if let NodeData::Element { name, .. } = &mut data {
             name.local = new_name;
         };
-        let clone = Node5Ever(Rc::new(rcdom::Node {
-            parent: Cell::new(None),
-            children: self.node.0.children.clone(),
-            data,
-        }));
-        self.replace_with(clone);
+        self.replace_node_data(data);
     }
 
     fn append(&self, node: Self::Child) {
@@ -1109,7 +1775,7 @@ impl Element for Element5Ever {
     }
 
     fn set_attributes(&self, new_attrs: Self::Attributes<'_>) {
-        let rcdom::NodeData::Element { attrs, .. } = &self.node.0.data else {
+        let NodeData::Element { attrs, .. } = &self.node.0.data else {
             unreachable!()
         };
         attrs.replace(new_attrs.0.take());
@@ -1496,6 +2162,27 @@ impl Element5Ever {
         }
     }
 
+    /// Replaces the element in it's parent with a clone using the given data, moving the
+    /// children over to the clone.
+    fn replace_node_data(&mut self, data: NodeData) {
+        // NOTE: Children are taken, rather than shared, as dropping a `TreeNode` will empty
+        // the children of it's descendants
+        let clone = Node5Ever(Rc::new(TreeNode {
+            parent: Cell::new(None),
+            children: RefCell::new(self.node.0.children.take()),
+            data,
+        }));
+        for child in clone.0.children.borrow().iter() {
+            child.parent.replace(Some(Rc::downgrade(&clone.0)));
+        }
+        if let Some(mut parent) = self.node.parent_node() {
+            clone.set_parent_node(&parent);
+            parent.replace_child(clone.clone(), &self.node);
+            self.node.0.parent.set(None);
+        }
+        self.node = clone;
+    }
+
     #[cfg(feature = "selectors")]
     pub fn set_selector_flags(&self, selector_flags: selectors::matching::ElementSelectorFlags) {
         if selector_flags.is_empty() {
@@ -1529,21 +2216,8 @@ impl node::Features for Element5Ever {}
 
 #[cfg(feature = "parse")]
 impl parse::Node for Element5Ever {
-    fn parse(source: &str) -> anyhow::Result<Self> {
-        let root = Node5Ever::parse(source)?;
-        match Node5Ever::find_element(&root) {
-            Some(element) => Ok(element),
-            None => Err(anyhow::Error::new(parse::Error::NoElementInDocument)),
-        }
-    }
-
-    fn parse_path(path: &std::path::Path) -> anyhow::Result<Self> {
-        let file = std::fs::File::open(path)?;
-        Self::parse_file(&file)
-    }
-
-    fn parse_file(file: &std::fs::File) -> anyhow::Result<Self> {
-        let root = Node5Ever::parse_file(file)?;
+    fn parse_with_options(source: &str, options: &parse::Options) -> anyhow::Result<Self> {
+        let root = Node5Ever::parse_with_options(source, options)?;
         match Node5Ever::find_element(&root) {
             Some(element) => Ok(element),
             None => Err(anyhow::Error::new(parse::Error::NoElementInDocument)),
@@ -1575,6 +2249,14 @@ impl serialize::Node for Element5Ever {
     fn serialize_into<Wr: std::io::Write>(&self, sink: Wr) -> anyhow::Result<()> {
         self.node.serialize_into(sink)
     }
+
+    fn serialize_into_with_options<Wr: std::io::Write>(
+        &self,
+        sink: Wr,
+        options: serialize::Options,
+    ) -> anyhow::Result<()> {
+        self.node.serialize_into_with_options(sink, options)
+    }
 }
 
 impl element::Features for Element5Ever {}
@@ -1838,18 +2520,16 @@ impl Document for Document5Ever {
     }
 
     fn create_c_data_section(&self, data: <Self::Root as Node>::Atom) -> Node5Ever {
-        Self::create_node(rcdom::NodeData::Text {
+        Self::create_node(NodeData::CDataSection {
             contents: RefCell::new(data),
         })
     }
 
     fn create_element(&self, tag_name: <Self::Root as Element>::Name) -> Self::Root {
         Element5Ever {
-            node: Self::create_node(rcdom::NodeData::Element {
+            node: Self::create_node(NodeData::Element {
                 name: tag_name,
                 attrs: RefCell::new(vec![]),
-                template_contents: RefCell::new(None),
-                mathml_annotation_xml_integration_point: false,
             }),
             selector_flags: Cell::new(None),
         }
@@ -1860,25 +2540,296 @@ impl Document for Document5Ever {
         target: <Self::Root as Node>::Atom,
         data: <Self::Root as Node>::Atom,
     ) -> <Self::Root as Node>::Child {
-        Self::create_node(rcdom::NodeData::ProcessingInstruction {
+        Self::create_node(NodeData::ProcessingInstruction {
             target,
             contents: data,
         })
     }
 
     fn create_text_node(&self, data: <Self::Root as Node>::Atom) -> <Self::Root as Node>::Child {
-        Self::create_node(rcdom::NodeData::Text {
+        Self::create_node(NodeData::Text {
             contents: RefCell::new(data),
         })
     }
 }
 
 impl Document5Ever {
-    fn create_node(data: rcdom::NodeData) -> Node5Ever {
-        Node5Ever(Rc::new(rcdom::Node {
+    fn create_node(data: NodeData) -> Node5Ever {
+        Node5Ever(Rc::new(TreeNode {
             parent: Cell::new(None),
             children: RefCell::new(vec![]),
             data,
         }))
     }
 }
+
+#[cfg(all(test, feature = "parse"))]
+mod test {
+    use xml5ever::{namespace_url, ns};
+
+    use super::{Element5Ever, Node5Ever, QualName};
+    use crate::{element::Element, node::Node, parse::Node as _};
+
+    #[test]
+    fn set_local_name() -> anyhow::Result<()> {
+        let dom = Node5Ever::parse("<svg><g><g><rect/></g></g></svg>")?;
+        let root = <Element5Ever as Element>::find_element(dom.clone()).unwrap();
+        let mut outer = root.first_element_child().unwrap();
+        outer.set_local_name("a".into());
+
+        // NOTE: With the children shared, dropping the replaced node would empty the inner
+        // group, as `TreeNode` empties the children of it's descendants when dropped
+        let outer = root.first_element_child().unwrap();
+        assert_eq!(outer.local_name().as_ref(), "a");
+        let inner = outer.first_element_child().unwrap();
+        assert_eq!(inner.parent_element(), Some(outer));
+        assert_eq!(inner.child_nodes().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "style")]
+    fn presentation() -> anyhow::Result<()> {
+        use crate::{
+            attribute::{Attr, Attributes},
+            style::PresentationAttr,
+        };
+
+        let dom = Node5Ever::parse(
+            r#"<svg xmlns:x="https://example.com"><rect fill="red" x:fill="red" foo="bar"/></svg>"#,
+        )?;
+        let root = <Element5Ever as Element>::find_element(dom.clone()).unwrap();
+        let rect = root.first_element_child().unwrap();
+        let attributes = rect.attributes();
+        assert!(matches!(
+            attributes.item(0).unwrap().presentation(),
+            Some(PresentationAttr::Fill(_))
+        ));
+        assert!(attributes.item(1).unwrap().presentation().is_none());
+        assert!(matches!(
+            attributes.item(2).unwrap().presentation(),
+            Some(PresentationAttr::Unknown(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn c_data_section() -> anyhow::Result<()> {
+        use crate::{document::Document, node::Type};
+
+        let dom = Node5Ever::parse("<svg><style><![CDATA[a > b {}]]></style></svg>")?;
+        let root = <Element5Ever as Element>::find_element(dom.clone()).unwrap();
+        let mut style = root.first_element_child().unwrap();
+        let c_data = style.first_child().unwrap();
+        assert_eq!(c_data.node_type(), Type::CDataSection);
+        assert_eq!(c_data.node_name().as_ref(), "#cdata-section");
+        assert_eq!(c_data.node_value().as_deref(), Some("a > b {}"));
+        assert_eq!(c_data.processing_instruction(), None);
+        assert_eq!(style.text_content().as_deref(), Some("a > b {}"));
+
+        let mut c_data = c_data;
+        c_data.set_text_content("a {}".into());
+        assert_eq!(style.text_content().as_deref(), Some("a {}"));
+
+        style.append_child(root.as_document().create_c_data_section("b {}".into()));
+        assert_eq!(style.child_node_count(), 2);
+        assert_eq!(style.text_content().as_deref(), Some("a {}b {}"));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn serialize_c_data_section() -> anyhow::Result<()> {
+        use crate::{document::Document, serialize::Node as _};
+
+        let dom = Node5Ever::parse("<svg><style><![CDATA[a > b {}]]></style></svg>")?;
+        assert_eq!(
+            dom.serialize()?,
+            "<svg><style><![CDATA[a > b {}]]></style></svg>"
+        );
+
+        let root = <Element5Ever as Element>::find_element(dom.clone()).unwrap();
+        let mut style = root.first_element_child().unwrap();
+        style.empty();
+        style.append_child(root.as_document().create_c_data_section("b < a {}".into()));
+        assert_eq!(
+            dom.serialize()?,
+            "<svg><style><![CDATA[b < a {}]]></style></svg>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn parse_recovery() -> anyhow::Result<()> {
+        use crate::serialize::Node as _;
+
+        // Stray end tags are ignored
+        let dom = Node5Ever::parse("<svg><g></a><rect/></g></g></svg>")?;
+        assert_eq!(dom.serialize()?, "<svg><g><rect/></g></svg>");
+
+        // End tags close any elements left open within
+        let dom = Node5Ever::parse("<svg><g><rect></g><path/></svg>")?;
+        assert_eq!(dom.serialize()?, "<svg><g><rect/></g><path/></svg>");
+
+        // Unclosed comments run to the end of the document
+        let dom = Node5Ever::parse("<svg><!-- a <g/>\n</svg>")?;
+        assert_eq!(dom.serialize()?, "<svg><!-- a <g/>\n</svg>--></svg>");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn doctype() -> anyhow::Result<()> {
+        use crate::serialize::Node as _;
+
+        let dom = Node5Ever::parse(
+            r#"<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd" [
+    <!ENTITY ns_svg "http://www.w3.org/2000/svg">
+]><svg xmlns="&ns_svg;"/>"#,
+        )?;
+        assert_eq!(
+            dom.serialize()?,
+            r#"<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd"><svg xmlns="http://www.w3.org/2000/svg"/>"#
+        );
+
+        let dom = Node5Ever::parse("<!DOCTYPE svg SYSTEM 'svg.dtd'><svg/>")?;
+        assert_eq!(dom.serialize()?, r#"<!DOCTYPE svg SYSTEM "svg.dtd"><svg/>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn entity_expansion_limited_by_default() {
+        use crate::parse::Error;
+        use std::fmt::Write;
+
+        let mut doctype = String::from("<!DOCTYPE svg [\n<!ENTITY lol0 \"lol\">\n");
+        for i in 1..10 {
+            let previous = format!("&lol{};", i - 1).repeat(10);
+            writeln!(doctype, "<!ENTITY lol{i} \"{previous}\">").unwrap();
+        }
+        let source = format!("{doctype}]><svg><text>&lol9;</text></svg>");
+        let error = Node5Ever::parse(&source).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::EntityExpansionLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_limits() {
+        use crate::parse::{Error, Options};
+
+        let parse_error = |source: &str, options: &Options| {
+            Node5Ever::parse_with_options(source, options)
+                .unwrap_err()
+                .downcast::<Error>()
+                .unwrap()
+        };
+
+        let source = r#"<svg><g a="1" b="2"><g/></g></svg>"#;
+        assert!(Node5Ever::parse_with_options(
+            source,
+            &Options::new()
+                .max_size(Some(source.len()))
+                .max_depth(Some(3))
+                .max_attributes(Some(2))
+        )
+        .is_ok());
+        assert!(matches!(
+            parse_error(source, &Options::new().max_size(Some(source.len() - 1))),
+            Error::InputTooLarge { limit } if limit == source.len() - 1
+        ));
+        assert!(matches!(
+            parse_error(source, &Options::new().max_depth(Some(2))),
+            Error::DepthLimitExceeded { limit: 2 }
+        ));
+        assert!(matches!(
+            parse_error(source, &Options::new().max_attributes(Some(1))),
+            Error::AttributeLimitExceeded { limit: 1 }
+        ));
+
+        let source = r#"<!DOCTYPE svg [
+<!ENTITY a "aaaa">
+<!ENTITY b "&a;&a;&a;&a;">
+]><svg><text>&b;</text><text>&a;</text></svg>"#;
+        // Expanding `&a;` within `b` counts as well as the references in the text
+        let options = Options::new().max_entity_expansion(Some(36));
+        assert!(Node5Ever::parse_with_options(source, &options).is_ok());
+        assert!(matches!(
+            parse_error(source, &options.max_entity_expansion(Some(35))),
+            Error::EntityExpansionLimitExceeded { limit: 35 }
+        ));
+        assert!(matches!(
+            parse_error(source, &Options::untrusted().max_entity_expansion(Some(16))),
+            Error::EntityExpansionLimitExceeded { limit: 16 }
+        ));
+    }
+
+    #[cfg(feature = "serialize")]
+    const LOSSLESS_SOURCE: &str = concat!(
+        "\u{feff}",
+        r#"<?xml version='1.0' encoding="UTF-8"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd" [
+    <!ENTITY ns_svg "http://www.w3.org/2000/svg">
+]>
+<!-- comment -->
+<svg xmlns = "&ns_svg;"   viewBox='0 0 10 10'
+     xmlns:xlink="http://www.w3.org/1999/xlink" >
+  <g id='a' fill="&#x72;ed" >
+    <rect width="1"  height="1"/>
+    <rect width="2" height="2" ></rect >
+    <text>&lt;a &amp; b&gt;&#65;</text>
+  </g>
+  <style><![CDATA[ a > b {} ]]></style>
+  <use xlink:href = '#a' />
+</svg >
+
+"#
+    );
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn lossless_round_trip() -> anyhow::Result<()> {
+        use crate::serialize::Options;
+
+        let (dom, source_map) = Node5Ever::parse_with_source_map(LOSSLESS_SOURCE)?;
+        assert_eq!(
+            dom.serialize_lossless(Options::new(), &source_map)?,
+            LOSSLESS_SOURCE
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn lossless_edits() -> anyhow::Result<()> {
+        use crate::{document::Document, serialize::Options};
+
+        let (dom, source_map) = Node5Ever::parse_with_source_map(LOSSLESS_SOURCE)?;
+        let root = <Element5Ever as Element>::find_element(dom.clone()).unwrap();
+        let group = root.first_element_child().unwrap();
+        group.set_attribute_local("id".into(), "b".into());
+        let rect = group.first_element_child().unwrap();
+        rect.remove_attribute_local(&"height".into());
+        let mut text = group.children().pop().unwrap();
+        text.set_text_content("c".into());
+
+        let circle =
+            root.as_document()
+                .create_element(QualName::new(None, ns!(svg), "circle".into()));
+        circle.set_attribute_local("r".into(), "1".into());
+        root.append(circle.node.clone());
+
+        // Only the edited markup should change, with new nodes written without formatting
+        assert_eq!(
+            dom.serialize_lossless(Options::new(), &source_map)?,
+            LOSSLESS_SOURCE
+                .replace("id='a'", "id='b'")
+                .replace(r#"width="1"  height="1""#, r#"width="1""#)
+                .replace("&lt;a &amp; b&gt;&#65;", "c")
+                .replace("/>\n</svg >", "/>\n<circle r=\"1\"/></svg >")
+        );
+        Ok(())
+    }
+}
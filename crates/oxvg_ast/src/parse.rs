@@ -1,25 +1,186 @@
-use std::{fmt::Display, path::Path};
+use std::{fmt::Display, io::Read, path::Path};
 
 #[derive(Debug)]
 pub enum Error {
     NoElementInDocument,
+    /// The source is larger than [`Options::max_size`]
+    InputTooLarge {
+        /// The maximum number of bytes allowed
+        limit: usize,
+    },
+    /// An element is nested deeper than [`Options::max_depth`]
+    DepthLimitExceeded {
+        /// The maximum depth allowed
+        limit: usize,
+    },
+    /// An element has more attributes than [`Options::max_attributes`]
+    AttributeLimitExceeded {
+        /// The maximum number of attributes allowed
+        limit: usize,
+    },
+    /// References to entities expanded to more than [`Options::max_entity_expansion`]
+    EntityExpansionLimitExceeded {
+        /// The maximum number of bytes allowed
+        limit: usize,
+    },
 }
 
 pub trait Node: Sized {
     /// # Errors
     ///
     /// Any error cause by the underlying parser, or [Error]
-    fn parse(source: &str) -> anyhow::Result<Self>;
+    fn parse(source: &str) -> anyhow::Result<Self> {
+        Self::parse_with_options(source, &Options::default())
+    }
+
+    /// # Errors
+    ///
+    /// Any error cause by the underlying parser, or [Error]
+    fn parse_path(path: &Path) -> anyhow::Result<Self> {
+        Self::parse_path_with_options(path, &Options::default())
+    }
+
+    /// # Errors
+    ///
+    /// Any error cause by the underlying parser, or [Error]
+    fn parse_file(file: &std::fs::File) -> anyhow::Result<Self> {
+        Self::parse_file_with_options(file, &Options::default())
+    }
 
+    /// Parses the source, failing with an [Error] when it goes beyond the limits of the
+    /// options.
+    ///
     /// # Errors
     ///
     /// Any error cause by the underlying parser, or [Error]
-    fn parse_path(path: &Path) -> anyhow::Result<Self>;
+    fn parse_with_options(source: &str, options: &Options) -> anyhow::Result<Self>;
 
     /// # Errors
     ///
     /// Any error cause by the underlying parser, or [Error]
-    fn parse_file(file: &std::fs::File) -> anyhow::Result<Self>;
+    fn parse_path_with_options(path: &Path, options: &Options) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::parse_file_with_options(&file, options)
+    }
+
+    /// Reads and parses the file, where no more than [`Options::max_size`] bytes are read.
+    ///
+    /// # Errors
+    ///
+    /// Any error cause by the underlying parser, or [Error]
+    fn parse_file_with_options(file: &std::fs::File, options: &Options) -> anyhow::Result<Self> {
+        let source = options.read(file)?;
+        Self::parse_with_options(&source, options)
+    }
+}
+
+/// Limits on the input of a parser, for documents that can't be trusted.
+///
+/// By default only the expansion of entities is limited, to 1MB, so that a document type
+/// declaring nested entities (e.g. the "billion laughs" attack) can't exhaust memory.
+///
+/// ```
+/// use oxvg_ast::parse::Options;
+///
+/// let options = Options::new().max_size(Some(1_000_000)).max_depth(Some(256));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Options {
+    size: Option<usize>,
+    depth: Option<usize>,
+    attributes: Option<usize>,
+    entity_expansion: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            size: None,
+            depth: None,
+            attributes: None,
+            entity_expansion: Some(1_000_000),
+        }
+    }
+}
+
+impl Options {
+    /// Creates the default options, which only limit the expansion of entities
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A set of limits suitable for documents from untrusted sources, such as user uploads
+    pub fn untrusted() -> Self {
+        Self {
+            size: Some(10_000_000),
+            depth: Some(256),
+            attributes: Some(1024),
+            entity_expansion: Some(100_000),
+        }
+    }
+
+    /// The maximum number of bytes in the source
+    pub fn max_size(mut self, max_size: Option<usize>) -> Self {
+        self.size = max_size;
+        self
+    }
+
+    /// The maximum number of elements an element can be nested within
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.depth = max_depth;
+        self
+    }
+
+    /// The maximum number of attributes on an element
+    pub fn max_attributes(mut self, max_attributes: Option<usize>) -> Self {
+        self.attributes = max_attributes;
+        self
+    }
+
+    /// The maximum number of bytes that references to entities declared by the document type
+    /// can expand to, in total
+    pub fn max_entity_expansion(mut self, max_entity_expansion: Option<usize>) -> Self {
+        self.entity_expansion = max_entity_expansion;
+        self
+    }
+
+    pub(crate) fn check_size(&self, size: usize) -> Result<(), Error> {
+        match self.size {
+            Some(limit) if size > limit => Err(Error::InputTooLarge { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the depth of an element, where the root element has a depth of 1
+    pub(crate) fn check_depth(&self, depth: usize) -> Result<(), Error> {
+        match self.depth {
+            Some(limit) if depth > limit => Err(Error::DepthLimitExceeded { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_attributes(&self, count: usize) -> Result<(), Error> {
+        match self.attributes {
+            Some(limit) if count > limit => Err(Error::AttributeLimitExceeded { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_entity_expansion(&self, size: usize) -> Result<(), Error> {
+        match self.entity_expansion {
+            Some(limit) if size > limit => Err(Error::EntityExpansionLimitExceeded { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads the source, stopping once it's larger than the maximum size
+    fn read(&self, reader: impl Read) -> anyhow::Result<String> {
+        let limit = self.size.map_or(u64::MAX, |limit| limit as u64 + 1);
+        let mut source = Vec::new();
+        reader.take(limit).read_to_end(&mut source)?;
+        self.check_size(source.len())?;
+        Ok(String::from_utf8(source)?)
+    }
 }
 
 impl std::error::Error for Error {}
@@ -28,6 +189,18 @@ impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::NoElementInDocument => f.write_str("No element in document"),
+            Self::InputTooLarge { limit } => {
+                f.write_fmt(format_args!("Input is larger than {limit} bytes"))
+            }
+            Self::DepthLimitExceeded { limit } => {
+                f.write_fmt(format_args!("Elements are nested deeper than {limit}"))
+            }
+            Self::AttributeLimitExceeded { limit } => {
+                f.write_fmt(format_args!("Element has more than {limit} attributes"))
+            }
+            Self::EntityExpansionLimitExceeded { limit } => f.write_fmt(format_args!(
+                "Entity references expand to more than {limit} bytes"
+            )),
         }
     }
 }
@@ -1,4 +1,11 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
 use xml5ever::{
+    local_name, namespace_url, ns,
     serialize::{AttrRef, SerializeOpts, TraversalScope},
     QualName,
 };
@@ -15,6 +22,16 @@ pub trait Node {
     /// # Errors
     /// If the underlying serialization fails
     fn serialize_into<Wr: std::io::Write>(&self, sink: Wr) -> anyhow::Result<()>;
+
+    /// Writes the node to the sink as it's serialized, rather than collecting it into a string.
+    ///
+    /// # Errors
+    /// If the underlying serialization fails
+    fn serialize_into_with_options<Wr: std::io::Write>(
+        &self,
+        sink: Wr,
+        options: Options,
+    ) -> anyhow::Result<()>;
 }
 
 // WARN: Everything below is licensed from html5ever under the Apache License
@@ -25,6 +42,14 @@ impl NamespaceMapStack {
         NamespaceMapStack(vec![])
     }
 
+    /// Creates a stack where SVG is the implied default namespace, as with SVGs inlined into
+    /// HTML, so it's only declared where the document declares it
+    fn inline() -> NamespaceMapStack {
+        let mut implied = xml5ever::tree_builder::NamespaceMap::empty();
+        implied.insert(&QualName::new(None, ns!(svg), local_name!("")));
+        NamespaceMapStack(vec![implied])
+    }
+
     fn push(&mut self, namespace: xml5ever::tree_builder::NamespaceMap) {
         self.0.push(namespace);
     }
@@ -34,9 +59,57 @@ impl NamespaceMapStack {
     }
 }
 
+#[derive(Clone)]
+// NOTE: Each flag is an independent setting of the builder, rather than a state
+#[allow(clippy::struct_excessive_bools)]
 pub struct Options {
     indent: usize,
     pretty: bool,
+    quote: Quote,
+    self_closing: bool,
+    final_newline: bool,
+    eol: LineEnding,
+    line_width: Option<usize>,
+    xml_declaration: bool,
+    inline: bool,
+}
+
+/// The character used to quote attribute values
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Quote {
+    #[default]
+    /// Quote values with `"`
+    Double,
+    /// Quote values with `'`
+    Single,
+}
+
+/// The characters written for the end of a line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    /// Unix-style `\n` line endings
+    Lf,
+    /// Windows-style `\r\n` line endings
+    Crlf,
+}
+
+impl Quote {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Double => b"\"",
+            Self::Single => b"'",
+        }
+    }
+}
+
+impl LineEnding {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Lf => b"\n",
+            Self::Crlf => b"\r\n",
+        }
+    }
 }
 
 impl Options {
@@ -44,6 +117,13 @@ impl Options {
         Self {
             indent: 4,
             pretty: false,
+            quote: Quote::default(),
+            self_closing: true,
+            final_newline: false,
+            eol: LineEnding::default(),
+            line_width: None,
+            xml_declaration: false,
+            inline: false,
         }
     }
 }
@@ -64,18 +144,229 @@ impl Options {
         self.indent = indent;
         self
     }
+
+    /// Sets the character used to quote attribute values, which is `"` by default.
+    pub fn quote(mut self, quote: Quote) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Whether elements without children are written as `<x/>` instead of `<x></x>`, which is
+    /// enabled by default.
+    pub fn self_closing(mut self, self_closing: bool) -> Self {
+        self.self_closing = self_closing;
+        self
+    }
+
+    /// Whether to end the document with a line ending.
+    pub fn final_newline(mut self, final_newline: bool) -> Self {
+        self.final_newline = final_newline;
+        self
+    }
+
+    /// Sets the line ending written between nodes when pretty printing, and at the end of the
+    /// document when `final_newline` is set.
+    pub fn eol(mut self, eol: LineEnding) -> Self {
+        self.eol = eol;
+        self
+    }
+
+    /// When pretty printing, places each attribute of a start tag on it's own line if the tag
+    /// would otherwise be wider than the given number of characters.
+    pub fn line_width(mut self, line_width: Option<usize>) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Whether to start the document with `<?xml version="1.0" encoding="UTF-8"?>` when it
+    /// doesn't already have an xml declaration, quoted with the `quote` option.
+    pub fn xml_declaration(mut self, xml_declaration: bool) -> Self {
+        self.xml_declaration = xml_declaration;
+        self
+    }
+
+    /// Whether the document is written to be inlined into HTML, where SVG is the implied default
+    /// namespace.
+    ///
+    /// By default, an element in the SVG namespace is written with an `xmlns` declaration when
+    /// one isn't in scope, so the document can be used standalone.
+    pub fn inline(mut self, inline: bool) -> Self {
+        self.inline = inline;
+        self
+    }
+}
+
+/// A record of the markup each node was parsed from, which is used by
+/// `Node5Ever::serialize_lossless` to write untouched nodes byte-for-byte.
+///
+/// The source map is kept separate from [`Options`], so that options can be shared between
+/// threads.
+///
+/// Entries are keyed by the address of each node's reference counted allocation, so a source
+/// map only applies to the markup5ever implementation, which it's recorded by with
+/// `Node5Ever::parse_with_source_map`.
+#[derive(Clone, Default)]
+pub struct SourceMap(Rc<HashMap<usize, SourceEntry>>);
+
+/// The recorded markup of a node, along with a weak reference that reserves it's address
+type SourceEntry = (Weak<dyn Any>, NodeSource);
+
+impl SourceMap {
+    /// Records the markup of the given node.
+    pub(crate) fn insert<T: 'static>(&mut self, node: &Rc<T>, source: NodeSource) {
+        let weak: Weak<dyn Any> = Rc::<T>::downgrade(node);
+        Rc::make_mut(&mut self.0).insert(Self::key(node), (weak, source));
+    }
+
+    /// Returns the recorded markup of the given node.
+    pub(crate) fn get<T: 'static>(&self, node: &Rc<T>) -> Option<&NodeSource> {
+        // NOTE: The weak reference keeps the allocation of the node, so the key can't be reused
+        // by another node
+        self.0.get(&Self::key(node)).map(|(_, source)| source)
+    }
+
+    pub(crate) fn get_mut<T: 'static>(&mut self, node: &Rc<T>) -> Option<&mut NodeSource> {
+        Rc::make_mut(&mut self.0)
+            .get_mut(&Self::key(node))
+            .map(|(_, source)| source)
+    }
+
+    fn key<T>(node: &Rc<T>) -> usize {
+        Rc::as_ptr(node).addr()
+    }
+}
+
+/// The markup a node was parsed from.
+#[derive(Clone)]
+pub(crate) struct NodeSource {
+    /// Whitespace preceding a node outside of the root element, which isn't kept as text
+    pub prefix: String,
+    pub markup: Markup,
+}
+
+impl NodeSource {
+    /// Returns the markup of the start tag, if the node is an element
+    pub fn element(&self) -> Option<&ElementSource> {
+        match &self.markup {
+            Markup::Element(source) => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Returns the whitespace trailing the document, if the node is a document
+    pub fn suffix(&self) -> Option<&str> {
+        match &self.markup {
+            Markup::Document { suffix } => Some(suffix),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum Markup {
+    Element(ElementSource),
+    /// Text, with the value of the text when parsed and the escaped text it was parsed from
+    Text {
+        value: String,
+        raw: String,
+    },
+    /// Any other node, with a key representing it's content when parsed and the markup it was
+    /// parsed from
+    Other {
+        key: String,
+        raw: String,
+    },
+    /// The whitespace trailing the document
+    Document {
+        suffix: String,
+    },
+}
+
+#[derive(Clone)]
+pub(crate) struct ElementSource {
+    pub name: QualName,
+    pub attributes: Vec<AttributeSource>,
+    /// Whitespace between the last attribute and the end of the start tag
+    pub tail: String,
+    pub self_closing: bool,
+    /// The end tag as written, such as `</g >`
+    pub end: Option<String>,
+}
+
+#[derive(Clone)]
+pub(crate) struct AttributeSource {
+    pub name: QualName,
+    pub value: String,
+    /// The markup preceding the value, such as ` id = `
+    pub before_value: String,
+    /// The quoted value, including any entity references
+    pub raw_value: String,
+}
+
+impl ElementSource {
+    /// Parses the markup of a start tag, such as `<rect x='1' />`.
+    ///
+    /// Returns `None` if the tag can't be split into attributes
+    pub fn parse(raw: &str, name: QualName, attributes: &[(QualName, String)]) -> Option<Self> {
+        let raw = raw.strip_prefix('<')?.strip_suffix('>')?;
+        let (raw, self_closing) = match raw.strip_suffix('/') {
+            Some(raw) => (raw, true),
+            None => (raw, false),
+        };
+        let mut rest = raw.trim_start_matches(|c: char| !c.is_whitespace());
+        let mut sources = Vec::with_capacity(attributes.len());
+        for (name, value) in attributes {
+            let start = rest.len() - rest.trim_start().len();
+            let equals = rest.find('=')?;
+            let after_equals = &rest[equals + 1..];
+            let quote_start = equals + 1 + after_equals.len() - after_equals.trim_start().len();
+            let quote = rest[quote_start..]
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '"' | '\''))?;
+            let quote_end = quote_start + 1 + rest[quote_start + 1..].find(quote)?;
+            if start == 0 || rest[start..equals].trim_end().is_empty() {
+                return None;
+            }
+            sources.push(AttributeSource {
+                name: name.clone(),
+                value: value.clone(),
+                before_value: rest[..quote_start].to_string(),
+                raw_value: rest[quote_start..=quote_end].to_string(),
+            });
+            rest = &rest[quote_end + 1..];
+        }
+        if !rest.trim().is_empty() {
+            return None;
+        }
+        Some(Self {
+            name,
+            attributes: sources,
+            tail: rest.to_string(),
+            self_closing,
+            end: None,
+        })
+    }
+
+    fn attribute(&self, name: &QualName) -> Option<&AttributeSource> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name.prefix == name.prefix && attr.name.local == name.local)
+    }
 }
 
 #[derive(Default)]
 pub struct State {
     indent_level: usize,
     text_content: Option<String>,
+    has_begun: bool,
 }
 
 pub struct Serializer<Wr> {
     writer: Wr,
     namespace_stack: NamespaceMapStack,
     options: Options,
+    source_map: Option<SourceMap>,
     state: State,
 }
 
@@ -96,24 +387,44 @@ impl<Wr: std::io::Write> Serializer<Wr> {
             writer,
             namespace_stack: NamespaceMapStack::new(),
             options: Options::new(),
+            source_map: None,
             state: State::default(),
         }
     }
 
     pub fn options(mut self, options: Options) -> Self {
+        self.namespace_stack = if options.inline {
+            NamespaceMapStack::inline()
+        } else {
+            NamespaceMapStack::new()
+        };
         self.options = options;
         self
     }
 
-    #[inline(always)]
-    fn qual_name(&mut self, name: &QualName) -> std::io::Result<()> {
-        self.find_or_insert_ns(name);
-        write_qual_name(&mut self.writer, name)
+    /// Reproduces the original markup of nodes recorded in the source map that haven't changed
+    /// since parsing, including whitespace, quote style, entity references and self-closing
+    /// tags.
+    ///
+    /// Nodes that were edited or created after parsing are written using the `quote` and
+    /// `self_closing` options, without any other formatting.
+    pub(crate) fn lossless(mut self, source_map: SourceMap) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    /// Returns the source map used for lossless serialization, if any.
+    pub(crate) fn source_map(&self) -> Option<SourceMap> {
+        self.source_map.clone()
+    }
+
+    /// Whether the serializer is writing untouched nodes as they were parsed
+    pub fn is_lossless(&self) -> bool {
+        self.source_map.is_some()
     }
 
     #[inline(always)]
-    fn qual_attr_name(&mut self, name: &QualName) -> std::io::Result<()> {
-        self.find_or_insert_ns(name);
+    fn qual_name(&mut self, name: &QualName) -> std::io::Result<()> {
         write_qual_name(&mut self.writer, name)
     }
 
@@ -128,12 +439,28 @@ impl<Wr: std::io::Write> Serializer<Wr> {
         found
     }
 
-    fn find_or_insert_ns(&mut self, name: &QualName) {
+    /// Inserts the namespace of the name into the current scope, returning whether it wasn't
+    /// already declared.
+    fn find_or_insert_ns(&mut self, name: &QualName) -> bool {
         if (name.prefix.is_some() || !name.ns.is_empty()) && !self.find_uri(name) {
             if let Some(last_ns) = self.namespace_stack.0.last_mut() {
                 last_ns.insert(name);
+                return true;
             }
         }
+        false
+    }
+
+    /// Inserts the namespace declared by an `xmlns` attribute into the current scope.
+    fn declare_ns(&mut self, name: &QualName, value: &str) {
+        let prefix = match &name.prefix {
+            Some(prefix) if prefix.as_ref() == "xmlns" => Some(name.local.as_ref().into()),
+            None if name.local.as_ref() == "xmlns" => None,
+            _ => return,
+        };
+        if let Some(last_ns) = self.namespace_stack.0.last_mut() {
+            last_ns.insert(&QualName::new(prefix, value.into(), name.local.clone()));
+        }
     }
 }
 
@@ -177,43 +504,114 @@ impl<Wr: std::io::Write> Serializer<Wr> {
         attrs: AttrIter,
         is_empty: bool,
     ) -> std::io::Result<()>
+    where
+        AttrIter: Iterator<Item = AttrRef<'a>>,
+    {
+        self.start_elem_with_source(name, attrs, is_empty, None)
+    }
+
+    /// Serializes given start element, reusing the markup of the attributes and whitespace of
+    /// the element's source where it's unchanged.
+    ///
+    /// # Errors
+    /// If the writer fails
+    pub(crate) fn start_elem_with_source<'a, AttrIter>(
+        &mut self,
+        name: &QualName,
+        attrs: AttrIter,
+        is_empty: bool,
+        source: Option<&ElementSource>,
+    ) -> std::io::Result<()>
     where
         AttrIter: Iterator<Item = AttrRef<'a>>,
     {
         self.namespace_stack
             .push(xml5ever::tree_builder::NamespaceMap::empty());
-
+        let attrs: Vec<_> = attrs.collect();
+        for (name, value) in &attrs {
+            self.declare_ns(name, value);
+        }
+        // NOTE: Namespaces are declared by attributes, so this is only needed when the element
+        // was moved outside of the scope of it's namespace. Where the element declares it's own
+        // prefix, the attribute is kept as-is, even if it was changed after parsing.
+        let is_declared_by_attrs = self
+            .namespace_stack
+            .0
+            .last()
+            .is_some_and(|namespaces| namespaces.get(&name.prefix).is_some());
+        let is_undeclared = !is_declared_by_attrs && self.find_or_insert_ns(name);
+
+        self.begin(false)?;
         self.create_indent()?;
+        let mut attributes = Vec::with_capacity(attrs.len() + 1);
+        if is_undeclared {
+            let mut attribute = b"xmlns".to_vec();
+            if let Some(ref p) = name.prefix {
+                attribute.extend_from_slice(b":");
+                attribute.extend_from_slice(p.as_bytes());
+            }
+            attribute.extend_from_slice(b"=");
+            attribute.extend_from_slice(self.options.quote.as_bytes());
+            attribute.extend_from_slice(name.ns.as_bytes());
+            attribute.extend_from_slice(self.options.quote.as_bytes());
+            attributes.push((false, attribute));
+        }
+        for (name, value) in attrs {
+            let mut attribute = vec![];
+            let attr_source = source.and_then(|source| source.attribute(name));
+            let quote = if let Some(attr) = attr_source {
+                attribute.extend_from_slice(attr.before_value.as_bytes());
+                if attr.value == value {
+                    attribute.extend_from_slice(attr.raw_value.as_bytes());
+                    attributes.push((true, attribute));
+                    continue;
+                }
+                &attr.raw_value.as_bytes()[..1]
+            } else {
+                write_qual_name(&mut attribute, name)?;
+                attribute.push(b'=');
+                self.options.quote.as_bytes()
+            };
+            attribute.extend_from_slice(quote);
+            write_to_buf_escaped(&mut attribute, value, true)?;
+            attribute.extend_from_slice(quote);
+            attributes.push((attr_source.is_some(), attribute));
+        }
+
+        let wrap = source.is_none()
+            && self.options.pretty
+            && self.options.line_width.is_some_and(|line_width| {
+                let width = self.options.indent * self.state.indent_level
+                    + 1
+                    + name.prefix.as_ref().map_or(0, |p| p.len() + 1)
+                    + name.local.len()
+                    + attributes.iter().map(|(_, a)| a.len() + 1).sum::<usize>()
+                    + if is_empty { 2 } else { 1 };
+                width > line_width
+            });
         self.state.indent_level += 1;
         self.writer.write_all(b"<")?;
         self.qual_name(name)?;
-        if let Some(current_namespace) = self.namespace_stack.0.last() {
-            for (prefix, url_opt) in current_namespace.get_scope_iter() {
-                self.writer.write_all(b" xmlns")?;
-                if let Some(ref p) = *prefix {
-                    self.writer.write_all(b":")?;
-                    self.writer.write_all(p.as_bytes())?;
-                }
-
-                self.writer.write_all(b"=\"")?;
-                let url = if let Some(ref a) = *url_opt {
-                    a.as_bytes()
-                } else {
-                    b""
-                };
-                self.writer.write_all(url)?;
-                self.writer.write_all(b"\"")?;
+        for (has_whitespace, attribute) in attributes {
+            if wrap {
+                self.writer.write_all(self.options.eol.as_bytes())?;
+                let indent = b" ".repeat(self.options.indent * self.state.indent_level);
+                self.writer.write_all(&indent)?;
+            } else if !has_whitespace {
+                self.writer.write_all(b" ")?;
             }
+            self.writer.write_all(&attribute)?;
         }
-        for (name, value) in attrs {
-            self.writer.write_all(b" ")?;
-            self.qual_attr_name(name)?;
-            self.writer.write_all(b"=\"")?;
-            write_to_buf_escaped(&mut self.writer, value, true)?;
-            self.writer.write_all(b"\"")?;
+        if let Some(source) = source {
+            self.writer.write_all(source.tail.as_bytes())?;
         }
         if is_empty {
             self.state.indent_level -= 1;
+            if source.is_none() && !self.options.self_closing {
+                self.writer.write_all(b"></")?;
+                self.qual_name(name)?;
+                return self.writer.write_all(b">");
+            }
             self.writer.write_all(b"/")?;
         }
         self.writer.write_all(b">")?;
@@ -225,10 +623,25 @@ impl<Wr: std::io::Write> Serializer<Wr> {
     /// # Errors
     /// If the writer fails
     pub fn end_elem(&mut self, name: &QualName) -> std::io::Result<()> {
+        self.end_elem_with_source(name, None)
+    }
+
+    /// Serializes given end element, using the end tag as written in the source if given.
+    ///
+    /// # Errors
+    /// If the writer fails
+    pub(crate) fn end_elem_with_source(
+        &mut self,
+        name: &QualName,
+        source: Option<&str>,
+    ) -> std::io::Result<()> {
         self.namespace_stack.pop();
         if self.state.indent_level > 0 {
             self.state.indent_level -= 1;
         }
+        if let Some(source) = source {
+            return self.writer.write_all(source.as_bytes());
+        }
         self.create_indent()?;
         self.writer.write_all(b"</")?;
         self.qual_name(name)?;
@@ -240,6 +653,7 @@ impl<Wr: std::io::Write> Serializer<Wr> {
     /// # Errors
     /// If the writer fails
     pub fn write_comment(&mut self, text: &str) -> std::io::Result<()> {
+        self.begin(false)?;
         self.create_indent()?;
         self.writer.write_all(b"<!--")?;
         self.writer.write_all(text.as_bytes())?;
@@ -251,9 +665,37 @@ impl<Wr: std::io::Write> Serializer<Wr> {
     /// # Errors
     /// If the writer fails
     pub fn write_doctype(&mut self, name: &str) -> std::io::Result<()> {
+        self.write_doctype_with_ids(name, "", "")
+    }
+
+    /// Serializes given doctype along with it's external id, where an empty id is omitted
+    ///
+    /// # Errors
+    /// If the writer fails
+    pub fn write_doctype_with_ids(
+        &mut self,
+        name: &str,
+        public_id: &str,
+        system_id: &str,
+    ) -> std::io::Result<()> {
+        self.begin(false)?;
         self.create_indent()?;
         self.writer.write_all(b"<!DOCTYPE ")?;
         self.writer.write_all(name.as_bytes())?;
+        if !public_id.is_empty() {
+            self.writer.write_all(b" PUBLIC \"")?;
+            self.writer.write_all(public_id.as_bytes())?;
+            self.writer.write_all(b"\"")?;
+        } else if !system_id.is_empty() {
+            self.writer.write_all(b" SYSTEM")?;
+        }
+        if !system_id.is_empty() {
+            let quote: &[u8] = if system_id.contains('"') { b"'" } else { b"\"" };
+            self.writer.write_all(b" ")?;
+            self.writer.write_all(quote)?;
+            self.writer.write_all(system_id.as_bytes())?;
+            self.writer.write_all(quote)?;
+        }
         self.writer.write_all(b">")
     }
 
@@ -262,14 +704,31 @@ impl<Wr: std::io::Write> Serializer<Wr> {
     /// # Errors
     /// If the writer fails
     pub fn write_text(&mut self, text: &str) -> std::io::Result<()> {
+        if self.is_lossless() {
+            return write_to_buf_escaped(&mut self.writer, text, false);
+        }
         let text = text.trim();
         if text.is_empty() {
             return Ok(());
         }
+        self.begin(false)?;
         self.create_indent()?;
         write_to_buf_escaped(&mut self.writer, text.trim(), false)
     }
 
+    /// Serializes given CDATA section, splitting the section wherever the text contains `]]>`
+    ///
+    /// # Errors
+    /// If the writer fails
+    pub fn write_c_data_section(&mut self, text: &str) -> std::io::Result<()> {
+        self.begin(false)?;
+        self.create_indent()?;
+        self.writer.write_all(b"<![CDATA[")?;
+        self.writer
+            .write_all(text.replace("]]>", "]]]]><![CDATA[>").as_bytes())?;
+        self.writer.write_all(b"]]>")
+    }
+
     /// Serializes given processing instruction.
     ///
     /// # Errors
@@ -279,6 +738,7 @@ impl<Wr: std::io::Write> Serializer<Wr> {
         target: &str,
         data: &str,
     ) -> std::io::Result<()> {
+        self.begin(target == "xml")?;
         self.writer.write_all(b"<?")?;
         self.writer.write_all(target.as_bytes())?;
         self.writer.write_all(b" ")?;
@@ -286,9 +746,50 @@ impl<Wr: std::io::Write> Serializer<Wr> {
         self.writer.write_all(b"?>")
     }
 
+    /// Writes the given markup as-is.
+    ///
+    /// # Errors
+    /// If the writer fails
+    pub(crate) fn write_raw(&mut self, raw: &str) -> std::io::Result<()> {
+        self.writer.write_all(raw.as_bytes())
+    }
+
+    /// Writes the xml declaration, if enabled, before the first node of the document unless
+    /// it's the xml declaration itself.
+    fn begin(&mut self, is_declaration: bool) -> std::io::Result<()> {
+        if self.state.has_begun {
+            return Ok(());
+        }
+        self.state.has_begun = true;
+        if self.options.xml_declaration && !is_declaration && !self.is_lossless() {
+            let quote = self.options.quote.as_bytes();
+            self.writer.write_all(b"<?xml version=")?;
+            self.writer.write_all(quote)?;
+            self.writer.write_all(b"1.0")?;
+            self.writer.write_all(quote)?;
+            self.writer.write_all(b" encoding=")?;
+            self.writer.write_all(quote)?;
+            self.writer.write_all(b"UTF-8")?;
+            self.writer.write_all(quote)?;
+            self.writer.write_all(b"?>")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the final newline, if enabled.
+    ///
+    /// # Errors
+    /// If the writer fails
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        if self.options.final_newline && !self.is_lossless() {
+            self.writer.write_all(self.options.eol.as_bytes())?;
+        }
+        Ok(())
+    }
+
     fn create_indent(&mut self) -> std::io::Result<()> {
-        if self.options.pretty && self.state.text_content.is_none() {
-            self.writer.write_all(b"\n")?;
+        if self.options.pretty && self.state.text_content.is_none() && !self.is_lossless() {
+            self.writer.write_all(self.options.eol.as_bytes())?;
             let indent = b" ".repeat(self.options.indent * self.state.indent_level);
             self.writer.write_all(&indent)
         } else {
@@ -322,7 +823,8 @@ where
     T: Serialize,
 {
     let mut ser = Serializer::new(writer);
-    node.serialize(&mut ser, opts.traversal_scope)
+    node.serialize(&mut ser, opts.traversal_scope)?;
+    ser.finish()
 }
 
 /// # Errors
@@ -338,5 +840,109 @@ where
     T: Serialize,
 {
     let mut ser = Serializer::new(writer).options(options);
-    node.serialize(&mut ser, opts.traversal_scope)
+    node.serialize(&mut ser, opts.traversal_scope)?;
+    ser.finish()
+}
+
+#[cfg(all(test, feature = "markup5ever", feature = "parse"))]
+mod test {
+    use super::{LineEnding, Node as _, Options, Quote};
+    use crate::{implementations::markup5ever::Node5Ever, parse::Node as _};
+
+    const SOURCE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><g id="a"><rect/></g></svg>"#;
+
+    #[test]
+    fn options() -> anyhow::Result<()> {
+        let dom = Node5Ever::parse(SOURCE)?;
+        let serialize = |options: Options| dom.serialize_with_options(options).unwrap();
+
+        assert_eq!(serialize(Options::new()), SOURCE);
+        assert_eq!(
+            serialize(Options::new().quote(Quote::Single)),
+            "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 10 10'><g id='a'><rect/></g></svg>"
+        );
+        assert_eq!(
+            serialize(Options::new().self_closing(false)),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><g id="a"><rect></rect></g></svg>"#
+        );
+        assert_eq!(
+            serialize(Options::new().final_newline(true)),
+            format!("{SOURCE}\n")
+        );
+        assert_eq!(
+            serialize(Options::new().final_newline(true).eol(LineEnding::Crlf)),
+            format!("{SOURCE}\r\n")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn xml_declaration() -> anyhow::Result<()> {
+        let dom = Node5Ever::parse(SOURCE)?;
+        assert_eq!(
+            dom.serialize_with_options(Options::new().xml_declaration(true))?,
+            format!(r#"<?xml version="1.0" encoding="UTF-8"?>{SOURCE}"#)
+        );
+        assert_eq!(
+            dom.serialize_with_options(Options::new().xml_declaration(true).quote(Quote::Single))?,
+            "<?xml version='1.0' encoding='UTF-8'?><svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 10 10'><g id='a'><rect/></g></svg>"
+        );
+
+        // An existing declaration isn't repeated
+        let dom = Node5Ever::parse(&format!(r#"<?xml version="1.0"?>{SOURCE}"#))?;
+        assert_eq!(
+            dom.serialize_with_options(Options::new().xml_declaration(true))?,
+            format!(r#"<?xml version="1.0"?>{SOURCE}"#)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn namespace() -> anyhow::Result<()> {
+        use crate::{element::Element as _, implementations::markup5ever::Element5Ever};
+
+        let dom = Node5Ever::parse(SOURCE)?;
+        let root = Element5Ever::find_element(dom.clone()).unwrap();
+        root.remove_attribute_local(&"xmlns".into());
+
+        // The SVG namespace is declared where it's missing, so the document works standalone
+        assert_eq!(dom.serialize()?, SOURCE);
+        assert_eq!(
+            dom.serialize_with_options(Options::new().inline(true))?,
+            r#"<svg viewBox="0 0 10 10"><g id="a"><rect/></g></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pretty() -> anyhow::Result<()> {
+        let dom = Node5Ever::parse(SOURCE)?;
+        let serialize = |options: Options| dom.serialize_with_options(options.pretty()).unwrap();
+
+        assert_eq!(
+            serialize(Options::new().indent(2)),
+            r#"
+<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+  <g id="a">
+    <rect/>
+  </g>
+</svg>"#
+        );
+        assert_eq!(
+            serialize(Options::new().eol(LineEnding::Crlf)),
+            "\r\n<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\">\r\n    <g id=\"a\">\r\n        <rect/>\r\n    </g>\r\n</svg>"
+        );
+        assert_eq!(
+            serialize(Options::new().line_width(Some(40))),
+            r#"
+<svg
+    xmlns="http://www.w3.org/2000/svg"
+    viewBox="0 0 10 10">
+    <g id="a">
+        <rect/>
+    </g>
+</svg>"#
+        );
+        Ok(())
+    }
 }
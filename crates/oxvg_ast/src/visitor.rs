@@ -139,6 +139,9 @@ pub trait Visitor<E: Element> {
 
     /// Visits a text or cdata node
     ///
+    /// The two can be distinguished by the node's type, being either [`node::Type::Text`] or
+    /// [`node::Type::CDataSection`]
+    ///
     /// # Errors
     /// Whether the visitor fails
     fn text_or_cdata(&mut self, node: &mut <E as Node>::Child) -> Result<(), Self::Error> {
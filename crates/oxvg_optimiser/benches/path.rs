@@ -1,6 +1,9 @@
-use std::time::{Duration, Instant};
+use std::{
+    fmt::Write,
+    time::{Duration, Instant},
+};
 
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use oxvg_ast::{
     element::Element,
     implementations::markup5ever::{Element5Ever, Node5Ever},
@@ -8,6 +11,7 @@ use oxvg_ast::{
     visitor::{Info, Visitor},
 };
 use oxvg_optimiser::ConvertPathData;
+use oxvg_path::{parser::Commands, writer::Writer, Path};
 
 /// # Panics
 /// Hopefully never, maybe if svg can't be parsed
@@ -46,5 +50,59 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, criterion_benchmark);
+/// Compares parsing a path into commands and formatting it, with streaming each command from the
+/// parser through a writer.
+///
+/// # Panics
+/// If the generated path can't be parsed
+pub fn streaming_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stream path");
+    for count in [1_000, 100_000] {
+        // Paths in the order of megabytes are found in maps and charts
+        let mut definition = String::from("M 0 0");
+        for i in 0..count {
+            let n = f64::from(i % 1000);
+            write!(
+                definition,
+                " L {} {} C {} {} {} {} {} {}",
+                n * 0.5,
+                -n,
+                n,
+                n + 0.25,
+                n - 0.125,
+                n * 2.0,
+                n + 1.0,
+                -0.5
+            )
+            .unwrap();
+        }
+
+        group.throughput(Throughput::Bytes(definition.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("parse and format", definition.len()),
+            &definition,
+            |b, definition| b.iter(|| Path::parse(black_box(definition)).unwrap().to_string()),
+        );
+        let mut output = String::with_capacity(definition.len());
+        group.bench_with_input(
+            BenchmarkId::new("stream", definition.len()),
+            &definition,
+            |b, definition| {
+                b.iter(|| {
+                    output.clear();
+                    let mut writer = Writer::new(&mut output);
+                    for command in Commands::new(black_box(definition)) {
+                        let command = command.unwrap();
+                        writer
+                            .write_command(&command.data, command.implicit)
+                            .unwrap();
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark, streaming_benchmark);
 criterion_main!(benches);
@@ -20,7 +20,7 @@ use oxvg_ast::{
     visitor::{Context, ContextFlags, PrepareOutcome, Visitor},
 };
 use oxvg_collections::{collections, regex::REFERENCES_URL};
-use oxvg_path::{command::Data, convert, Path};
+use oxvg_path::{convert, geometry, Path};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Default, Clone, Debug)]
@@ -135,7 +135,7 @@ impl<E: Element> Visitor<E> for ApplyTransforms {
             }
         }
 
-        apply_matrix_to_path_data(&mut path, &matrix);
+        path.transform(&geometry::Matrix(matrix));
         let path = convert::cleanup_unpositioned(&path).to_string().into();
         log::debug!("new d <- {path}");
         element.set_attribute_local(d_localname, path);
@@ -246,208 +246,6 @@ fn matrix32_to_slice(matrix: &Matrix<f32>) -> [f64; 6] {
     ]
 }
 
-#[allow(clippy::too_many_lines)]
-fn apply_matrix_to_path_data(path_data: &mut Path, matrix: &[f64; 6]) {
-    log::debug!("applying matrix: {:?}", matrix);
-    let mut start = [0.0; 2];
-    let mut cursor = [0.0; 2];
-    if let Some(data) = path_data.0.get_mut(0) {
-        if let Data::MoveBy(args) = data {
-            *data = Data::MoveTo(*args);
-        }
-    }
-
-    path_data.0.iter_mut().for_each(|data| {
-        if let Data::Implicit(_) = data {
-            *data = data.as_explicit().clone();
-        };
-        match data {
-            Data::HorizontalLineTo(args) => *data = Data::LineTo([args[0], cursor[1]]),
-            Data::HorizontalLineBy(args) => *data = Data::LineBy([args[0], 0.0]),
-            Data::VerticalLineTo(args) => *data = Data::LineTo([cursor[0], args[0]]),
-            Data::VerticalLineBy(args) => *data = Data::LineBy([0.0, args[0]]),
-            _ => {}
-        };
-        match data {
-            Data::MoveTo(args) => {
-                cursor[0] = args[0];
-                cursor[1] = args[1];
-                start[0] = cursor[0];
-                start[1] = cursor[1];
-                *args = transform_absolute_point(matrix, args[0], args[1]);
-            }
-            Data::MoveBy(args) => {
-                cursor[0] += args[0];
-                cursor[1] += args[1];
-                start[0] = cursor[0];
-                start[1] = cursor[1];
-                *args = transform_relative_point(matrix, args[0], args[1]);
-            }
-            Data::LineTo(args) | Data::SmoothQuadraticBezierTo(args) => {
-                cursor[0] = args[0];
-                cursor[1] = args[1];
-                *args = transform_absolute_point(matrix, args[0], args[1]);
-            }
-            Data::LineBy(args) | Data::SmoothQuadraticBezierBy(args) => {
-                cursor[0] += args[0];
-                cursor[1] += args[1];
-                *args = transform_relative_point(matrix, args[0], args[1]);
-            }
-            Data::CubicBezierTo(args) => {
-                cursor[0] = args[4];
-                cursor[1] = args[5];
-                let p1 = transform_absolute_point(matrix, args[0], args[1]);
-                let p2 = transform_absolute_point(matrix, args[2], args[3]);
-                let p = transform_absolute_point(matrix, args[4], args[5]);
-                *args = [p1[0], p1[1], p2[0], p2[1], p[0], p[1]];
-            }
-            Data::CubicBezierBy(args) => {
-                cursor[0] += args[4];
-                cursor[1] += args[5];
-                let p1 = transform_relative_point(matrix, args[0], args[1]);
-                let p2 = transform_relative_point(matrix, args[2], args[3]);
-                let p = transform_relative_point(matrix, args[4], args[5]);
-                *args = [p1[0], p1[1], p2[0], p2[1], p[0], p[1]];
-            }
-            Data::SmoothBezierTo(args) | Data::QuadraticBezierTo(args) => {
-                cursor[0] = args[2];
-                cursor[1] = args[3];
-                let p1 = transform_absolute_point(matrix, args[0], args[1]);
-                let p = transform_absolute_point(matrix, args[2], args[3]);
-                *args = [p1[0], p1[1], p[0], p[1]];
-            }
-            Data::SmoothBezierBy(args) | Data::QuadraticBezierBy(args) => {
-                cursor[0] += args[2];
-                cursor[1] += args[3];
-                let p1 = transform_relative_point(matrix, args[0], args[1]);
-                let p = transform_relative_point(matrix, args[2], args[3]);
-                *args = [p1[0], p1[1], p[0], p[1]];
-            }
-            Data::ArcTo(args) => {
-                transform_arc(cursor, args, matrix);
-                cursor[0] = args[5];
-                cursor[1] = args[6];
-                if f64::abs(args[2]) > 80.0 {
-                    args.swap(0, 1);
-                    args[2] += if args[2] > 0.0 { -90.0 } else { 90.0 };
-                }
-                let p = transform_absolute_point(matrix, args[5], args[6]);
-                args[5] = p[0];
-                args[6] = p[1];
-            }
-            Data::ArcBy(args) => {
-                transform_arc([0.0; 2], args, matrix);
-                cursor[0] += args[5];
-                cursor[1] += args[6];
-                if f64::abs(args[2]) > 80.0 {
-                    args.swap(0, 1);
-                    args[2] += if args[2] > 0.0 { -90.0 } else { 90.0 };
-                }
-                let p = transform_relative_point(matrix, args[5], args[6]);
-                args[5] = p[0];
-                args[6] = p[1];
-            }
-            Data::ClosePath => {
-                cursor[0] = start[0];
-                cursor[1] = start[1];
-            }
-            Data::HorizontalLineBy(_)
-            | Data::HorizontalLineTo(_)
-            | Data::VerticalLineBy(_)
-            | Data::VerticalLineTo(_)
-            | Data::Implicit(_) => {
-                unreachable!("Reached destroyed command type")
-            }
-        }
-    });
-}
-
-fn transform_absolute_point(matrix: &[f64; 6], x: f64, y: f64) -> [f64; 2] {
-    [
-        matrix[0] * x + matrix[2] * y + matrix[4],
-        matrix[1] * x + matrix[3] * y + matrix[5],
-    ]
-}
-
-fn transform_relative_point(matrix: &[f64; 6], x: f64, y: f64) -> [f64; 2] {
-    [matrix[0] * x + matrix[2] * y, matrix[1] * x + matrix[3] * y]
-}
-
-fn transform_arc(cursor: [f64; 2], args: &mut [f64; 7], matrix: &[f64; 6]) {
-    let x = args[5] - cursor[0];
-    let y = args[6] - cursor[1];
-    let [a, b, cos, sin] = rotated_ellipse(args, [x, y]);
-
-    let ellipse = [a * cos, a * sin, -b * sin, b * cos, 0.0, 0.0];
-    let new_matrix = multiply_transform_matrices(matrix, ellipse);
-    let last_col = new_matrix[2] * new_matrix[2] + new_matrix[3] * new_matrix[3];
-    let square_sum = new_matrix[0] * new_matrix[0] + new_matrix[1] * new_matrix[1] + last_col;
-    let root = f64::hypot(new_matrix[0] - new_matrix[3], new_matrix[1] + new_matrix[2])
-        * f64::hypot(new_matrix[0] + new_matrix[3], new_matrix[1] - new_matrix[2]);
-
-    if root == 0.0 {
-        args[0] = f64::sqrt(square_sum / 2.0);
-        args[1] = args[0];
-        args[2] = 0.0;
-    } else {
-        let major_axis_square = (square_sum + root) / 2.0;
-        let minor_axis_square = (square_sum - root) / 2.0;
-        let major = f64::abs(major_axis_square - last_col) > 1e-6;
-        let sub = if major {
-            major_axis_square
-        } else {
-            minor_axis_square
-        } - last_col;
-        let rows_sum = new_matrix[0] * new_matrix[2] + new_matrix[1] * new_matrix[3];
-        let term_1 = new_matrix[0] * sub + new_matrix[2] * rows_sum;
-        let term_2 = new_matrix[1] * sub + new_matrix[3] * rows_sum;
-        let term = if major { term_1 } else { term_2 };
-        args[0] = major_axis_square.sqrt();
-        args[1] = minor_axis_square.sqrt();
-        let term_sign = if (major && term_2 < 0.0) || (!major && term_1 > 0.0) {
-            -1.0
-        } else {
-            1.0
-        };
-        args[2] = (term_sign * f64::acos(term / f64::hypot(term_1, term_2)) * 180.0)
-            / std::f64::consts::PI;
-    }
-
-    if (matrix[0] < 0.0) != (matrix[3] < 0.0) {
-        args[4] = 1.0 - args[4];
-    }
-}
-
-fn rotated_ellipse(args: &mut [f64; 7], point: [f64; 2]) -> [f64; 4] {
-    let rotation = (args[2] * std::f64::consts::PI) / 180.0;
-    let cos = f64::cos(rotation);
-    let sin = f64::sin(rotation);
-
-    let mut a = args[0];
-    let mut b = args[1];
-    if a > 0.0 && b > 0.0 {
-        let h = (point[0] * cos + point[1] * sin).powi(2) / (4.0 * a * a)
-            + (point[1] * cos - point[0] * sin).powi(2) / (4.0 * b * b);
-        if h > 1.0 {
-            let h = h.sqrt();
-            a *= h;
-            b *= h;
-        }
-    }
-    [a, b, cos, sin]
-}
-
-fn multiply_transform_matrices(matrix: &[f64; 6], ellipse: [f64; 6]) -> [f64; 6] {
-    [
-        matrix[0] * ellipse[0] + matrix[2] * ellipse[1],
-        matrix[1] * ellipse[0] + matrix[3] * ellipse[1],
-        matrix[0] * ellipse[2] + matrix[2] * ellipse[3],
-        matrix[1] * ellipse[2] + matrix[3] * ellipse[3],
-        matrix[0] * ellipse[4] + matrix[2] * ellipse[5] + matrix[4],
-        matrix[1] * ellipse[4] + matrix[3] * ellipse[5] + matrix[5],
-    ]
-}
-
 lazy_static! {
     static ref TRANSFORM_ID: style::Id<'static> = style::Id::Attr(PresentationAttrId::Transform);
 }
@@ -0,0 +1,501 @@
+use std::collections::{HashMap, HashSet};
+
+use derive_where::derive_where;
+use lightningcss::traits::Parse;
+use oxvg_ast::{
+    attribute::{Attr, Attributes},
+    element::Element,
+    style::SVGTransformList,
+    visitor::{Context, ContextFlags, PrepareOutcome, Visitor},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{
+    find_references,
+    transform::{smart_round, Matrix},
+};
+
+const FLOAT_PRECISION: i32 = 3;
+
+#[derive_where(Default, Clone, Debug)]
+/// Normalises `<linearGradient>` and `<radialGradient>` elements.
+///
+/// - Gradients inheriting from another gradient via `href` or `xlink:href` are merged with
+///   their template when the template isn't used anywhere else.
+/// - A `gradientTransform` made up of only translations, rotations, reflections, and uniform
+///   scaling is applied to the endpoints of the gradient.
+/// - Attributes equal to their default values, such as `x1="0"` or `spreadMethod="pad"`,
+///   are removed.
+///
+/// Documents with scripts or stylesheets are skipped, as references within them can't be
+/// reliably counted.
+pub struct CleanupGradients<E: Element> {
+    enabled: bool,
+    gradients: Vec<E>,
+    ids: HashMap<String, E>,
+    reference_counts: HashMap<String, usize>,
+}
+
+impl<E: Element> Visitor<E> for CleanupGradients<E> {
+    type Error = String;
+
+    fn prepare(&mut self, document: &E, context_flags: &mut ContextFlags) -> PrepareOutcome {
+        if !self.enabled {
+            return PrepareOutcome::skip;
+        }
+        context_flags.query_has_script(document);
+        context_flags.query_has_stylesheet(document);
+        if context_flags.contains(ContextFlags::has_script_ref)
+            || context_flags.contains(ContextFlags::has_stylesheet)
+        {
+            log::debug!("CleanupGradients::prepare: skipping document with script or style");
+            return PrepareOutcome::skip;
+        }
+        PrepareOutcome::none
+    }
+
+    fn element(&mut self, element: &mut E, _context: &mut Context<E>) -> Result<(), String> {
+        for attr in element.attributes().into_iter() {
+            let Some(matches) = find_references(attr.local_name().as_ref(), attr.value().as_ref())
+            else {
+                continue;
+            };
+            for id in matches.filter_map(|item| item.get(1)) {
+                *self
+                    .reference_counts
+                    .entry(id.as_str().to_string())
+                    .or_default() += 1;
+            }
+        }
+
+        if gradient_kind(element).is_none() {
+            return Ok(());
+        }
+        if let Some(id) = element.get_attribute_local(&"id".into()) {
+            self.ids.insert(id.to_string(), element.clone());
+        }
+        self.gradients.push(element.clone());
+        Ok(())
+    }
+
+    fn exit_document(&mut self, _document: &mut E, _context: &Context<E>) -> Result<(), String> {
+        let mut removed = HashSet::new();
+        for gradient in &self.gradients {
+            if removed.contains(&gradient.as_ptr_byte()) {
+                continue;
+            }
+            self.inline_templates(gradient, &mut removed);
+        }
+
+        let templates: HashSet<_> = self
+            .gradients
+            .iter()
+            .filter(|gradient| !removed.contains(&gradient.as_ptr_byte()))
+            .filter_map(|gradient| href(gradient).map(|(_, id)| id))
+            .collect();
+        for gradient in &self.gradients {
+            if removed.contains(&gradient.as_ptr_byte()) || href(gradient).is_some() {
+                continue;
+            }
+            if gradient
+                .get_attribute_local(&"id".into())
+                .is_some_and(|id| templates.contains(id.as_ref()))
+            {
+                continue;
+            }
+            apply_gradient_transform(gradient);
+            remove_defaults(gradient);
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum GradientKind {
+    Linear,
+    Radial,
+}
+
+impl<E: Element> CleanupGradients<E> {
+    /// Follows the `href` of the gradient, merging any templates only referenced by this gradient
+    fn inline_templates(&self, gradient: &E, removed: &mut HashSet<usize>) {
+        let id_name = "id".into();
+        while let Some((href_name, id)) = href(gradient) {
+            let Some(template) = self.ids.get(&id) else {
+                return;
+            };
+            if template.ptr_eq(gradient)
+                || removed.contains(&template.as_ptr_byte())
+                || self.reference_counts.get(&id).copied().unwrap_or_default() != 1
+            {
+                return;
+            }
+            log::debug!("CleanupGradients: inlining #{id}");
+
+            let is_same_kind = gradient_kind(gradient) == gradient_kind(template);
+            for attr in template.attributes().into_iter() {
+                if attr.prefix().is_none() && attr.local_name() == &id_name {
+                    continue;
+                }
+                if is_href(&*attr) || gradient.get_attribute(attr.name()).is_some() {
+                    continue;
+                }
+                if !is_same_kind
+                    && !matches!(
+                        attr.local_name().as_ref(),
+                        "gradientUnits" | "gradientTransform" | "spreadMethod"
+                    )
+                {
+                    continue;
+                }
+                gradient.set_attribute(attr.name().clone(), attr.value().clone());
+            }
+            if !gradient.has_child_elements() {
+                gradient.replace_children(template.child_nodes());
+            }
+
+            gradient.remove_attribute(&href_name);
+            if let Some((template_href_name, template_id)) = href(template) {
+                gradient.set_attribute(template_href_name, format!("#{template_id}").into());
+            }
+            template.remove();
+            removed.insert(template.as_ptr_byte());
+        }
+    }
+}
+
+fn gradient_kind<E: Element>(element: &E) -> Option<GradientKind> {
+    if element.prefix().is_some() {
+        return None;
+    }
+    match element.local_name().as_ref() {
+        "linearGradient" => Some(GradientKind::Linear),
+        "radialGradient" => Some(GradientKind::Radial),
+        _ => None,
+    }
+}
+
+fn is_href<A: Attr>(attr: &A) -> bool {
+    attr.local_name().as_ref() == "href"
+        && attr
+            .prefix()
+            .as_ref()
+            .is_none_or(|prefix| prefix.as_ref() == "xlink")
+}
+
+/// Returns the name of the element's `href` attribute and the id it points to
+fn href<E: Element>(element: &E) -> Option<(<E::Attr as Attr>::Name, String)> {
+    element.attributes().into_iter().find_map(|attr| {
+        if !is_href(&*attr) {
+            return None;
+        }
+        let id = attr.value().as_ref().strip_prefix('#')?.to_string();
+        Some((attr.name().clone(), id))
+    })
+}
+
+fn is_object_bounding_box<E: Element>(element: &E) -> bool {
+    element
+        .get_attribute_local(&"gradientUnits".into())
+        .is_none_or(|units| units.as_ref() != "userSpaceOnUse")
+}
+
+/// Parses a coordinate of the gradient, where percentages are only understood for
+/// `objectBoundingBox` units
+fn get_coord<E: Element>(element: &E, name: &str, default: Option<f32>) -> Option<f32> {
+    let Some(value) = element.get_attribute_local(&name.into()) else {
+        return default;
+    };
+    let value = value.as_ref().trim();
+    if let Some(percentage) = value.strip_suffix('%') {
+        if !is_object_bounding_box(element) {
+            return None;
+        }
+        return percentage.parse::<f32>().ok().map(|n| n / 100.0);
+    }
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .parse::<f32>()
+        .ok()
+        .filter(|n| n.is_finite())
+}
+
+fn set_coord<E: Element>(element: &E, name: &str, value: f32) {
+    let mut value = smart_round(FLOAT_PRECISION, value);
+    if value == 0.0 {
+        value = 0.0;
+    }
+    element.set_attribute_local(name.into(), value.to_string().into());
+}
+
+fn apply_gradient_transform<E: Element>(element: &E) {
+    let transform_name = "gradientTransform".into();
+    let Some(matrix) = element
+        .get_attribute_local(&transform_name)
+        .and_then(|value| {
+            SVGTransformList::parse_string(value.as_ref())
+                .ok()?
+                .to_matrix_2d()
+        })
+        .map(Matrix)
+    else {
+        return;
+    };
+    let Some(scale) = matrix.similarity_scale() else {
+        log::debug!("CleanupGradients: cannot apply transform with skew or non-uniform scale");
+        return;
+    };
+
+    let is_bbox = is_object_bounding_box(element);
+    let default = |value: f32| if is_bbox { Some(value) } else { None };
+    match gradient_kind(element) {
+        Some(GradientKind::Linear) => {
+            let (Some(x1), Some(y1), Some(x2), Some(y2)) = (
+                get_coord(element, "x1", Some(0.0)),
+                get_coord(element, "y1", Some(0.0)),
+                get_coord(element, "x2", default(1.0)),
+                get_coord(element, "y2", Some(0.0)),
+            ) else {
+                return;
+            };
+            let (x1, y1) = matrix.apply(x1, y1);
+            let (x2, y2) = matrix.apply(x2, y2);
+            set_coord(element, "x1", x1);
+            set_coord(element, "y1", y1);
+            set_coord(element, "x2", x2);
+            set_coord(element, "y2", y2);
+        }
+        Some(GradientKind::Radial) => {
+            let (Some(cx), Some(cy), Some(r), Some(fr)) = (
+                get_coord(element, "cx", default(0.5)),
+                get_coord(element, "cy", default(0.5)),
+                get_coord(element, "r", default(0.5)),
+                get_coord(element, "fr", Some(0.0)),
+            ) else {
+                return;
+            };
+            let (Some(fx), Some(fy)) = (
+                get_coord(element, "fx", Some(cx)),
+                get_coord(element, "fy", Some(cy)),
+            ) else {
+                return;
+            };
+            let (cx, cy) = matrix.apply(cx, cy);
+            let (fx, fy) = matrix.apply(fx, fy);
+            set_coord(element, "cx", cx);
+            set_coord(element, "cy", cy);
+            set_coord(element, "r", r * scale);
+            set_coord(element, "fx", fx);
+            set_coord(element, "fy", fy);
+            if fr != 0.0 {
+                set_coord(element, "fr", fr * scale);
+            }
+        }
+        None => return,
+    }
+    log::debug!("CleanupGradients: applied gradientTransform");
+    element.remove_attribute_local(&transform_name);
+}
+
+fn remove_defaults<E: Element>(element: &E) {
+    let is_bbox = is_object_bounding_box(element);
+    let is_value = |name: &str, percentage: f32| {
+        let Some(value) = element.get_attribute_local(&name.into()) else {
+            return false;
+        };
+        let value = value.as_ref().trim();
+        if let Some(value) = value.strip_suffix('%') {
+            return value
+                .parse::<f32>()
+                .is_ok_and(|n| (n - percentage).abs() < f32::EPSILON);
+        }
+        let expected = percentage / 100.0;
+        (is_bbox || expected == 0.0)
+            && value
+                .parse::<f32>()
+                .is_ok_and(|n| (n - expected).abs() < f32::EPSILON)
+    };
+
+    let mut defaults = vec![];
+    match gradient_kind(element) {
+        Some(GradientKind::Linear) => {
+            for (name, percentage) in [("x1", 0.0), ("y1", 0.0), ("x2", 100.0), ("y2", 0.0)] {
+                if is_value(name, percentage) {
+                    defaults.push(name);
+                }
+            }
+        }
+        Some(GradientKind::Radial) => {
+            for (name, percentage) in [("cx", 50.0), ("cy", 50.0), ("r", 50.0), ("fr", 0.0)] {
+                if is_value(name, percentage) {
+                    defaults.push(name);
+                }
+            }
+            for (focal, centre) in [("fx", "cx"), ("fy", "cy")] {
+                if !element.has_attribute_local(&focal.into()) {
+                    continue;
+                }
+                let default = if is_bbox { Some(0.5) } else { None };
+                let is_default = match (
+                    get_coord(element, focal, None),
+                    get_coord(element, centre, default),
+                ) {
+                    (Some(focal_value), Some(centre_value)) => {
+                        (focal_value - centre_value).abs() < f32::EPSILON
+                    }
+                    _ => {
+                        element.get_attribute_local(&focal.into()).as_deref()
+                            == element.get_attribute_local(&centre.into()).as_deref()
+                    }
+                };
+                if is_default {
+                    defaults.push(focal);
+                }
+            }
+        }
+        None => return,
+    }
+    if element
+        .get_attribute_local(&"spreadMethod".into())
+        .is_some_and(|value| value.as_ref() == "pad")
+    {
+        defaults.push("spreadMethod");
+    }
+    if element
+        .get_attribute_local(&"gradientUnits".into())
+        .is_some_and(|value| value.as_ref() == "objectBoundingBox")
+    {
+        defaults.push("gradientUnits");
+    }
+
+    for name in defaults {
+        log::debug!("CleanupGradients: removing default {name}");
+        element.remove_attribute_local(&name.into());
+    }
+}
+
+impl<'de, E: Element> Deserialize<'de> for CleanupGradients<E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let enabled = bool::deserialize(deserializer)?;
+        Ok(Self {
+            enabled,
+            ..Self::default()
+        })
+    }
+}
+
+impl<E: Element> Serialize for CleanupGradients<E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.enabled.serialize(serializer)
+    }
+}
+
+#[test]
+#[allow(clippy::too_many_lines)]
+fn cleanup_gradients() -> anyhow::Result<()> {
+    use crate::test_config;
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "cleanupGradients": true }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 100 100">
+    <!-- Inline templates only used by a single gradient -->
+    <defs>
+        <linearGradient id="a">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        <linearGradient id="b" xlink:href="#a" x2="0" y2="1"/>
+        <radialGradient id="c" xlink:href="#b" spreadMethod="reflect"/>
+    </defs>
+    <rect width="10" height="10" fill="url(#c)"/>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "cleanupGradients": true }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 100 100">
+    <!-- Keep templates used by many gradients -->
+    <defs>
+        <linearGradient id="a" x1="0" x2="1">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        <linearGradient id="b" xlink:href="#a" gradientTransform="rotate(90)"/>
+        <linearGradient id="c" href="#a" spreadMethod="repeat"/>
+    </defs>
+    <rect width="10" height="10" fill="url(#b)"/>
+    <rect width="10" height="10" fill="url(#c)"/>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "cleanupGradients": true }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- Apply transforms without skew or non-uniform scale -->
+    <defs>
+        <linearGradient id="a" gradientTransform="rotate(90)">
+            <stop offset="0" stop-color="red"/>
+        </linearGradient>
+        <linearGradient id="b" gradientUnits="userSpaceOnUse" x1="10" y1="10" x2="90" y2="10" gradientTransform="translate(5 5) scale(0.5)">
+            <stop offset="0" stop-color="red"/>
+        </linearGradient>
+        <radialGradient id="c" gradientTransform="translate(0.25 0) scale(0.5)">
+            <stop offset="0" stop-color="red"/>
+        </radialGradient>
+        <linearGradient id="d" gradientTransform="skewX(30)">
+            <stop offset="0" stop-color="red"/>
+        </linearGradient>
+        <radialGradient id="e" gradientTransform="scale(1 2)">
+            <stop offset="0" stop-color="red"/>
+        </radialGradient>
+        <linearGradient id="f" gradientUnits="userSpaceOnUse" gradientTransform="rotate(45)">
+            <stop offset="0" stop-color="red"/>
+        </linearGradient>
+    </defs>
+    <rect width="10" height="10" fill="url(#a)"/>
+    <rect width="10" height="10" fill="url(#b)"/>
+    <rect width="10" height="10" fill="url(#c)"/>
+    <rect width="10" height="10" fill="url(#d)"/>
+    <rect width="10" height="10" fill="url(#e)"/>
+    <rect width="10" height="10" fill="url(#f)"/>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "cleanupGradients": true }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- Remove default attributes -->
+    <defs>
+        <linearGradient id="a" x1="0" y1="0%" x2="100%" y2="0" spreadMethod="pad" gradientUnits="objectBoundingBox">
+            <stop offset="0" stop-color="red"/>
+        </linearGradient>
+        <radialGradient id="b" cx=".5" cy="50%" r="50%" fx="0.5" fy="0.25" fr="0">
+            <stop offset="0" stop-color="red"/>
+        </radialGradient>
+        <linearGradient id="c" gradientUnits="userSpaceOnUse" x1="0" x2="1" spreadMethod="reflect">
+            <stop offset="0" stop-color="red"/>
+        </linearGradient>
+    </defs>
+    <rect width="10" height="10" fill="url(#a)"/>
+    <rect width="10" height="10" fill="url(#b)"/>
+    <rect width="10" height="10" fill="url(#c)"/>
+</svg>"#
+        ),
+    )?);
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ use std::mem;
 
 use lightningcss::{
     error::PrinterError,
-    printer::{Printer, PrinterOptions},
+    printer::PrinterOptions,
     properties::{
         border::{BorderBlockColor, BorderInlineColor, GenericBorder},
         custom::{CustomProperty, TokenList, TokenOrValue},
@@ -19,13 +19,17 @@ use oxvg_ast::{
     element::Element,
     visitor::{Context, PrepareOutcome, Visitor},
 };
+use oxvg_collections::collections::{COLORS_NAMES, COLORS_PROPS, COLORS_SHORT_NAMES};
 use serde::{Deserialize, Serialize};
 
 use super::ContextFlags;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub enum ConvertCase {
+    #[serde(alias = "Upper")]
     Upper,
+    #[serde(alias = "Lower")]
     Lower,
 }
 
@@ -35,13 +39,25 @@ pub enum Method {
     #[default]
     Lightning,
     CurrentColor,
-    /// WARN: These options don't do anything right now, but exist in SVGO and may reluctantly be
-    /// implemented here too
+    /// Converts the colors of presentation attributes in the same way as SVGO, with each
+    /// conversion being applied in the order listed.
+    ///
+    /// Unlike the other methods, colors within `style` attributes are left as is.
     Value {
+        /// Converts color names to long hex, such as `red` to `#ff0000`
+        #[serde(rename = "names2hex", alias = "names_2_hex", default = "default_true")]
         names_2_hex: bool,
+        /// Converts `rgb()` colors to long hex, such as `rgb(255, 0, 0)` to `#FF0000`
+        #[serde(rename = "rgb2hex", alias = "rgb_2_hex", default = "default_true")]
         rgb_2_hex: bool,
+        /// Converts the case of colors, other than `currentColor` and url references
+        #[serde(rename = "convertCase", default = "default_convert_case")]
         convert_case: Option<ConvertCase>,
+        /// Converts long hex to short hex, such as `#ff0000` to `#f00`
+        #[serde(rename = "shorthex", alias = "short_hex", default = "default_true")]
         short_hex: bool,
+        /// Converts hex to a color name, when the name is shorter, such as `#f00` to `red`
+        #[serde(rename = "shortname", alias = "short_name", default = "default_true")]
         short_name: bool,
     },
 }
@@ -82,11 +98,25 @@ impl<E: Element> Visitor<E> for ConvertColors {
     }
 
     fn element(&mut self, element: &mut E, _context: &mut Context<E>) -> Result<(), String> {
+        if let Some(method @ Method::Value { .. }) = &self.method {
+            for mut attr in element.attributes().into_iter_mut() {
+                if attr.prefix().is_some() || !COLORS_PROPS.contains(attr.local_name().as_ref()) {
+                    continue;
+                }
+                let value = method.convert_value(attr.value().as_ref());
+                attr.set_value(value.into());
+            }
+            return Ok(());
+        }
+
         let mask_localname = &"mask".into();
         let is_masked = element.local_name() == mask_localname
             || element.closest_local(mask_localname).is_some();
 
         for mut attr in element.attributes().into_iter_mut() {
+            if attr.prefix().is_some() || attr.local_name().as_ref() == "xmlns" {
+                continue;
+            }
             let is_style = attr.local_name().as_ref() == "style";
             let style = if is_style {
                 attr.value().to_string()
@@ -107,7 +137,7 @@ impl<E: Element> Visitor<E> for ConvertColors {
                 method = Method::Lightning;
             }
             method.convert_style(&mut style);
-            let mut minified_style = method.to_css(&style).unwrap();
+            let mut minified_style = Method::to_css(&style).unwrap();
             if !is_style {
                 if let Some((_, value)) = minified_style.split_once(':') {
                     minified_style = value.trim_start().to_string();
@@ -148,51 +178,70 @@ impl Method {
         };
     }
 
-    fn to_css(&self, style: &StyleAttribute) -> Result<String, PrinterError> {
-        let printer_options = PrinterOptions::default();
-        // NOTE: Useless destructure, maybe we'll use this in the future?
-        let (..) = match self {
-            Self::Value {
-                names_2_hex,
-                rgb_2_hex,
-                convert_case,
-                short_hex,
-                short_name,
-            } if !names_2_hex
-                || !rgb_2_hex
-                || convert_case.is_some()
-                || !short_hex
-                || !short_name =>
-            {
-                (names_2_hex, rgb_2_hex, convert_case, short_hex, short_name)
-            }
-            _ => return Ok(style.to_css(printer_options)?.code),
+    fn to_css(style: &StyleAttribute) -> Result<String, PrinterError> {
+        Ok(style.to_css(PrinterOptions::default())?.code)
+    }
+
+    /// Converts the value of a color attribute, in the same way as SVGO.
+    fn convert_value(&self, value: &str) -> String {
+        let Self::Value {
+            names_2_hex,
+            rgb_2_hex,
+            convert_case,
+            short_hex,
+            short_name,
+        } = self
+        else {
+            return value.to_string();
         };
+        let mut value = value.to_string();
 
-        let mut s = String::with_capacity(1);
-        let mut dest = Printer::new(&mut s, PrinterOptions::default());
-        let len =
-            style.declarations.declarations.len() + style.declarations.important_declarations.len();
-        let mut i = 0;
-
-        macro_rules! write {
-            ($decls: expr, $important: literal) => {
-                for decl in &$decls {
-                    decl.to_css(&mut dest, $important)?;
-                    // TODO: Intercept and ensure restrictions are met
-                    // Is this even possible?
-                    if i != len - 1 {
-                        dest.write_char(';')?;
-                        dest.whitespace()?;
-                    }
-                    i += 1;
-                }
-            };
+        if *names_2_hex {
+            if let Some(hex) = COLORS_NAMES.get(value.to_lowercase().as_str()) {
+                value = (*hex).to_string();
+            }
         }
-
-        write!(style.declarations.declarations, false);
-        write!(style.declarations.important_declarations, true);
-        todo!("Restrictions on color conversions are not supported");
+        if *rgb_2_hex {
+            if let Some(arguments) = rgb_arguments(&value) {
+                let [r, g, b] = arguments.map(|n| {
+                    let n = if let Some(percentage) = n.strip_suffix('%') {
+                        (percentage.parse::<f64>().unwrap_or_default() * 2.55).round()
+                    } else {
+                        n.parse::<f64>().unwrap_or_default().trunc()
+                    };
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let n = n.clamp(0.0, 255.0) as u8;
+                    n
+                });
+                value = format!("#{r:02X}{g:02X}{b:02X}");
+            }
+        }
+        if let Some(convert_case) = convert_case {
+            if !value.contains("url(") && value != "currentColor" {
+                value = match convert_case {
+                    ConvertCase::Lower => value.to_lowercase(),
+                    ConvertCase::Upper => value.to_uppercase(),
+                };
+            }
+        }
+        if *short_hex {
+            let bytes = value.as_bytes();
+            if bytes.len() == 7
+                && bytes[0] == b'#'
+                && bytes[1..].iter().all(u8::is_ascii_hexdigit)
+                && bytes[1] == bytes[2]
+                && bytes[3] == bytes[4]
+                && bytes[5] == bytes[6]
+            {
+                value = format!("#{}{}{}", &value[1..2], &value[3..4], &value[5..6]);
+            }
+        }
+        if *short_name {
+            if let Some(name) = COLORS_SHORT_NAMES.get(value.to_lowercase().as_str()) {
+                value = (*name).to_string();
+            }
+        }
+        value
     }
 }
 
@@ -268,7 +317,48 @@ impl<'a> Color<'a> {
     }
 }
 
+const fn default_true() -> bool {
+    true
+}
+
+#[allow(clippy::unnecessary_wraps)]
+const fn default_convert_case() -> Option<ConvertCase> {
+    Some(ConvertCase::Lower)
+}
+
+/// Returns the arguments of an `rgb()` color with numbers or percentages separated by either
+/// a comma or whitespace
+fn rgb_arguments(value: &str) -> Option<[&str; 3]> {
+    let arguments = value.strip_prefix("rgb(")?.strip_suffix(')')?;
+    let mut separated = vec![];
+    for part in arguments.split(',') {
+        let len = separated.len();
+        separated.extend(part.split_whitespace());
+        if separated.len() == len {
+            // Empty or repeated comma
+            return None;
+        }
+    }
+    let arguments = separated;
+    let [r, g, b] = arguments.as_slice() else {
+        return None;
+    };
+    let is_number = |n: &str| {
+        let n = n.strip_suffix('%').unwrap_or(n);
+        let n = n.strip_prefix(['+', '-']).unwrap_or(n);
+        let (integer, fraction) = n.split_once('.').unwrap_or((n, ""));
+        (!integer.is_empty() || !fraction.is_empty())
+            && integer.bytes().all(|b| b.is_ascii_digit())
+            && fraction.bytes().all(|b| b.is_ascii_digit())
+    };
+    [*r, *g, *b]
+        .into_iter()
+        .all(is_number)
+        .then_some([*r, *g, *b])
+}
+
 #[test]
+#[allow(clippy::too_many_lines)]
 fn convert_colors() -> anyhow::Result<()> {
     use crate::test_config;
 
@@ -361,5 +451,44 @@ fn convert_colors() -> anyhow::Result<()> {
         )
     )?);
 
+    insta::assert_snapshot!(test_config(
+        r#"{ "convertColors": { "method": { "value": {} } } }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg">
+    <!-- Should convert values in the same way as SVGO -->
+    <g color="black"/>
+    <g color="BLACK"/>
+    <g color="rgb(64, 64, 64)"/>
+    <g color="rgb(64 64 64)"/>
+    <g color="rgb( 64,64 , 64 )"/>
+    <g color="rgb(64,,64,64)"/>
+    <g color="rgb(86.27451%,86.666667%,87.058824%)"/>
+    <g color="rgb(-255,100,500)"/>
+    <g color="#FF0000"/>
+    <g color="#aabbcc" stop-color="#FFE4C4"/>
+    <g color="currentColor" fill="url(#Aa)" stroke="none"/>
+    <g style="color: #ff0000" id="RED"/>
+</svg>"##
+        )
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "convertColors": { "method": { "value": {
+            "names2hex": false,
+            "rgb2hex": true,
+            "convertCase": "upper",
+            "shorthex": false,
+            "shortname": false
+        } } } }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg">
+    <!-- Should only apply the enabled conversions -->
+    <g color="black"/>
+    <g color="rgb(64, 64, 64)"/>
+    <g color="#ff0000"/>
+</svg>"##
+        )
+    )?);
+
     Ok(())
 }
@@ -36,6 +36,8 @@ pub struct ConvertPathData {
     float_precision: Precision,
     #[serde(default = "flag_default_true")]
     utilize_absolute: bool,
+    #[serde(default = "Option::default")]
+    simplify: Option<f64>,
     // TODO: Do we want to have apply_transforms as an option, or is it better to have this as a plugin
     // just *before* this one
     // apply_transforms: Option<bool>,
@@ -67,13 +69,11 @@ impl<E: Element> Visitor<E> for ConvertPathData {
         let style_info = convert::StyleInfo::gather(&context.computed_styles);
         log::debug!("ConvertPathData::run: gained style info {style_info:?}");
 
-        let path = match Path::parse(d.as_ref()) {
-            Ok(path) => path,
-            Err(e) => {
-                log::error!("failed to parse path: {e}\n{}", d.as_ref());
-                return Ok(());
-            }
-        };
+        // NOTE: Browsers render up to the first error, so the valid commands can be kept
+        let (path, error) = Path::parse_partial(d.as_ref());
+        if let Some(e) = error {
+            log::error!("failed to parse path: {e}\n{}", d.as_ref());
+        }
         drop(d);
         if path.0.is_empty() {
             return Ok(());
@@ -85,6 +85,7 @@ impl<E: Element> Visitor<E> for ConvertPathData {
                 flags: self.into(),
                 make_arcs: self.make_arcs.clone(),
                 precision: self.float_precision.0,
+                simplify: self.simplify,
             },
             &style_info,
         );
@@ -566,5 +567,31 @@ fn convert_path_data() -> anyhow::Result<()> {
         )
     )?);
 
+    insta::assert_snapshot!(test_config(
+        r#"{ "convertPathData": { "simplify": 0.5 } }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- simplify redundant vertices within the tolerance -->
+    <path d="M10 10L20 10.2L30 9.9L40 10.1L50 10L50 20.3L50.2 30L49.9 40L50 50Z"/>
+    <path d="M10 60C10 70 17.5 75 25 75C32.5 75 40 70 40 60"/>
+    <path d="M10 60C10 65.52 14.48 70 20 70C25.52 70 30 65.52 30 60C30 54.48 34.48 50 40 50C45.52 50 50 54.48 50 60"/>
+    <!-- keep vertices with markers -->
+    <path d="M10 10L20 10.2L30 9.9L40 10.1L50 10" marker-mid="url(#m)"/>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "convertPathData": {} }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <!-- keep the commands before an error -->
+    <path d="M 10,50 L 20,30 L 30"/>
+    <path d="M 10,50 L 20,30 L 30,,40"/>
+    <path d="M 10,50 L 20,30 A 10 10 0 2 1 40 50"/>
+</svg>"#
+        ),
+    )?);
+
     Ok(())
 }
@@ -0,0 +1,550 @@
+use lightningcss::{
+    printer::PrinterOptions,
+    properties::{
+        svg::{Marker, SVGPaint, StrokeDasharray, StrokeLinecap, StrokeLinejoin},
+        Property, PropertyId,
+    },
+    traits::ToCss,
+    values::{
+        alpha::AlphaValue, length::LengthPercentage, percentage::DimensionPercentage,
+        shape::FillRule,
+    },
+};
+use oxvg_ast::{
+    attribute::{Attr, Attributes},
+    document::Document,
+    element::Element,
+    get_computed_styles_factory,
+    name::Name,
+    style::{ComputedStyles, Id, PresentationAttr, PresentationAttrId, Static, Style},
+    visitor::{Context, ContextFlags, PrepareOutcome, Visitor},
+};
+use oxvg_path::{command::Data, convert, stroke, Path};
+use serde::{Deserialize, Serialize};
+
+use super::convert_path_data::Precision;
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+/// Converts the stroke of shapes into filled `<path>` outlines, so that the result only uses
+/// fills.
+///
+/// The outline follows the computed `stroke-width`, `stroke-linecap`, `stroke-linejoin`,
+/// `stroke-miterlimit`, `stroke-dasharray`, and `stroke-dashoffset`. When the shape is also
+/// filled, the outline is added as a new `<path>` just after it.
+///
+/// Strokes that can't be reproduced by a fill are left as is, such as strokes with a
+/// `vector-effect`, markers, or a paint server.
+pub struct ConvertStrokeToFill {
+    float_precision: Option<Precision>,
+}
+
+impl<E: Element> Visitor<E> for ConvertStrokeToFill {
+    type Error = String;
+
+    fn prepare(&mut self, document: &E, context_flags: &mut ContextFlags) -> PrepareOutcome {
+        context_flags.query_has_script(document);
+        context_flags.query_has_stylesheet(document);
+        if context_flags.intersects(ContextFlags::has_stylesheet | ContextFlags::has_script_ref) {
+            PrepareOutcome::skip
+        } else {
+            PrepareOutcome::use_style
+        }
+    }
+
+    fn use_style(&mut self, element: &E) -> bool {
+        element.prefix().is_none()
+            && matches!(
+                element.local_name().as_ref(),
+                "path" | "line" | "polyline" | "polygon" | "rect" | "circle" | "ellipse"
+            )
+    }
+
+    fn element(&mut self, element: &mut E, context: &mut Context<E>) -> Result<(), String> {
+        if !context.flags.contains(ContextFlags::use_style) {
+            return Ok(());
+        }
+        if element.has_child_elements()
+            || ["style", "vector-effect", "pathLength"]
+                .into_iter()
+                .any(|name| element.has_attribute_local(&name.into()))
+        {
+            log::debug!("ConvertStrokeToFill: stroke may not be static");
+            return Ok(());
+        }
+
+        let computed_styles = &context.computed_styles;
+        let Some(stroke) = StrokeStyle::new(computed_styles) else {
+            log::debug!("ConvertStrokeToFill: cannot outline stroke");
+            return Ok(());
+        };
+        let Some(path) = shape_to_path(element) else {
+            return Ok(());
+        };
+        let is_filled = is_filled(computed_styles);
+        if is_filled
+            && (["opacity", "filter", "mask"]
+                .into_iter()
+                .any(|name| element.has_attribute_local(&name.into()))
+                || computed_styles
+                    .attr
+                    .contains_key(&PresentationAttrId::PaintOrder)
+                || computed_styles
+                    .inherited
+                    .contains_key(&Id::Attr(PresentationAttrId::PaintOrder)))
+        {
+            log::debug!("ConvertStrokeToFill: cannot split fill and stroke");
+            return Ok(());
+        }
+
+        let options = convert::Options {
+            precision: self.float_precision.unwrap_or_default().0,
+            ..convert::Options::default()
+        };
+        let mut outline = stroke::outline(
+            &path,
+            &stroke::Options {
+                tolerance: options.error(),
+                ..stroke.options
+            },
+        );
+        options.round_path(&mut outline, options.error());
+
+        let outline_element = if is_filled {
+            let outline_element = context
+                .root
+                .as_document()
+                .create_element(E::Name::new(None, "path".into()));
+            for attr in element.attributes().into_iter() {
+                if attr.prefix().is_some() || !is_geometry_or_paint(attr.local_name().as_ref()) {
+                    outline_element.set_attribute(attr.name().clone(), attr.value().clone());
+                }
+            }
+            outline_element.remove_attribute_local(&"id".into());
+            element.after(outline_element.as_parent_child());
+            outline_element
+        } else {
+            element.attributes().retain(|attr| {
+                attr.prefix().is_some() || !is_geometry_or_paint(attr.local_name().as_ref())
+            });
+            element.set_local_name("path".into());
+            element.clone()
+        };
+
+        outline_element.set_attribute_local("d".into(), outline.to_string().into());
+        outline_element.set_attribute_local("fill".into(), stroke.paint.into());
+        if inherited_fill_opacity(computed_styles) != stroke.opacity {
+            outline_element.set_attribute_local("fill-opacity".into(), stroke.opacity.into());
+        }
+        if is_fill_rule_inherited(computed_styles) {
+            outline_element.set_attribute_local("fill-rule".into(), "nonzero".into());
+        }
+
+        element.attributes().retain(|attr| {
+            attr.prefix().is_some() || !attr.local_name().as_ref().starts_with("stroke")
+        });
+        if is_stroke_inherited(computed_styles) {
+            element.set_attribute_local("stroke".into(), "none".into());
+            if is_filled {
+                outline_element.set_attribute_local("stroke".into(), "none".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The resolved stroke properties of an element
+struct StrokeStyle {
+    paint: String,
+    opacity: String,
+    options: stroke::Options,
+}
+
+impl StrokeStyle {
+    #[allow(clippy::too_many_lines)]
+    fn new(computed_styles: &ComputedStyles) -> Option<Self> {
+        get_computed_styles_factory!(computed_styles);
+        let (Static::Attr(PresentationAttr::Stroke(paint)) | Static::Css(Property::Stroke(paint))) =
+            static_style(get_computed_styles!(Stroke))??
+        else {
+            return None;
+        };
+        if !matches!(paint, SVGPaint::Color(_)) {
+            return None;
+        }
+        let paint = paint.to_css_string(PrinterOptions::default()).ok()?;
+
+        let opacity = match static_style(get_computed_styles!(StrokeOpacity))? {
+            Some(
+                Static::Attr(PresentationAttr::StrokeOpacity(AlphaValue(opacity)))
+                | Static::Css(Property::StrokeOpacity(AlphaValue(opacity))),
+            ) => opacity,
+            None => 1.0,
+            Some(_) => return None,
+        };
+
+        let mut options = stroke::Options::default();
+        match static_style(get_computed_styles!(StrokeWidth))? {
+            Some(
+                Static::Attr(PresentationAttr::StrokeWidth(width))
+                | Static::Css(Property::StrokeWidth(width)),
+            ) => options.width = length_to_px(&width)?,
+            None => {}
+            Some(_) => return None,
+        }
+        if options.width <= 0.0 {
+            return None;
+        }
+        match static_style(get_computed_styles!(StrokeLinecap))? {
+            Some(
+                Static::Attr(PresentationAttr::StrokeLinecap(line_cap))
+                | Static::Css(Property::StrokeLinecap(line_cap)),
+            ) => {
+                options.line_cap = match line_cap {
+                    StrokeLinecap::Butt => stroke::LineCap::Butt,
+                    StrokeLinecap::Round => stroke::LineCap::Round,
+                    StrokeLinecap::Square => stroke::LineCap::Square,
+                }
+            }
+            None => {}
+            Some(_) => return None,
+        }
+        match static_style(get_computed_styles!(StrokeLinejoin))? {
+            Some(
+                Static::Attr(PresentationAttr::StrokeLinejoin(line_join))
+                | Static::Css(Property::StrokeLinejoin(line_join)),
+            ) => {
+                options.line_join = match line_join {
+                    StrokeLinejoin::Miter => stroke::LineJoin::Miter,
+                    StrokeLinejoin::Round => stroke::LineJoin::Round,
+                    StrokeLinejoin::Bevel => stroke::LineJoin::Bevel,
+                    StrokeLinejoin::MiterClip | StrokeLinejoin::Arcs => return None,
+                }
+            }
+            None => {}
+            Some(_) => return None,
+        }
+        match static_style(get_computed_styles!(StrokeMiterlimit))? {
+            Some(
+                Static::Attr(PresentationAttr::StrokeMiterlimit(miter_limit))
+                | Static::Css(Property::StrokeMiterlimit(miter_limit)),
+            ) => options.miter_limit = f64::from(miter_limit),
+            None => {}
+            Some(_) => return None,
+        }
+        match static_style(get_computed_styles!(StrokeDasharray))? {
+            Some(
+                Static::Attr(PresentationAttr::StrokeDasharray(dash_array))
+                | Static::Css(Property::StrokeDasharray(dash_array)),
+            ) => {
+                if let StrokeDasharray::Values(values) = dash_array {
+                    options.dash_array = values.iter().map(length_to_px).collect::<Option<_>>()?;
+                }
+            }
+            None => {}
+            Some(_) => return None,
+        }
+        match static_style(get_computed_styles!(StrokeDashoffset))? {
+            Some(
+                Static::Attr(PresentationAttr::StrokeDashoffset(dash_offset))
+                | Static::Css(Property::StrokeDashoffset(dash_offset)),
+            ) => options.dash_offset = length_to_px(&dash_offset)?,
+            None => {}
+            Some(_) => return None,
+        }
+
+        for marker in [
+            get_computed_styles!(MarkerStart),
+            get_computed_styles!(MarkerMid),
+            get_computed_styles!(MarkerEnd),
+            computed_styles.attr.get(&PresentationAttrId::Marker),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !matches!(
+                marker.inner(),
+                Static::Attr(
+                    PresentationAttr::MarkerStart(Marker::None)
+                        | PresentationAttr::MarkerMid(Marker::None)
+                        | PresentationAttr::MarkerEnd(Marker::None)
+                        | PresentationAttr::Marker(Marker::None)
+                ) | Static::Css(
+                    Property::MarkerStart(Marker::None)
+                        | Property::MarkerMid(Marker::None)
+                        | Property::MarkerEnd(Marker::None)
+                )
+            ) {
+                return None;
+            }
+        }
+
+        Some(Self {
+            paint,
+            opacity: opacity.to_string(),
+            options,
+        })
+    }
+}
+
+/// Returns the inner value of a style, or `None` when it's dynamic
+#[allow(clippy::option_option)]
+fn static_style<'i>(style: Option<&Style<'i>>) -> Option<Option<Static<'i>>> {
+    match style {
+        Some(style) if style.is_static() => Some(Some(style.inner())),
+        Some(_) => None,
+        None => Some(None),
+    }
+}
+
+fn length_to_px(length: &LengthPercentage) -> Option<f64> {
+    match length {
+        DimensionPercentage::Dimension(length) => length.to_px().map(f64::from),
+        _ => None,
+    }
+}
+
+fn is_filled(computed_styles: &ComputedStyles) -> bool {
+    get_computed_styles_factory!(computed_styles);
+    get_computed_styles!(Fill).is_none_or(|fill| {
+        !matches!(
+            fill.inner(),
+            Static::Attr(PresentationAttr::Fill(SVGPaint::None))
+                | Static::Css(Property::Fill(SVGPaint::None))
+        )
+    })
+}
+
+/// Returns the `fill-opacity` the outline would inherit, as a string
+fn inherited_fill_opacity(computed_styles: &ComputedStyles) -> String {
+    let opacity = computed_styles
+        .inherited
+        .get(&Id::CSS(PropertyId::FillOpacity))
+        .or_else(|| {
+            computed_styles
+                .inherited
+                .get(&Id::Attr(PresentationAttrId::FillOpacity))
+        })
+        .map_or(1.0, |style| match style.inner() {
+            Static::Attr(PresentationAttr::FillOpacity(AlphaValue(opacity)))
+            | Static::Css(Property::FillOpacity(AlphaValue(opacity))) => opacity,
+            _ => f32::NAN,
+        });
+    opacity.to_string()
+}
+
+fn is_fill_rule_inherited(computed_styles: &ComputedStyles) -> bool {
+    computed_styles
+        .inherited
+        .get(&Id::CSS(PropertyId::FillRule))
+        .or_else(|| {
+            computed_styles
+                .inherited
+                .get(&Id::Attr(PresentationAttrId::FillRule))
+        })
+        .is_some_and(|style| {
+            !matches!(
+                style.inner(),
+                Static::Attr(PresentationAttr::FillRule(FillRule::Nonzero))
+                    | Static::Css(Property::FillRule(FillRule::Nonzero))
+            )
+        })
+}
+
+fn is_stroke_inherited(computed_styles: &ComputedStyles) -> bool {
+    computed_styles
+        .inherited
+        .get(&Id::CSS(PropertyId::Stroke))
+        .or_else(|| {
+            computed_styles
+                .inherited
+                .get(&Id::Attr(PresentationAttrId::Stroke))
+        })
+        .is_some_and(|style| {
+            !matches!(
+                style.inner(),
+                Static::Attr(PresentationAttr::Stroke(SVGPaint::None))
+                    | Static::Css(Property::Stroke(SVGPaint::None))
+            )
+        })
+}
+
+/// Returns whether the attribute would conflict with the outline's geometry or paint
+fn is_geometry_or_paint(name: &str) -> bool {
+    name.starts_with("stroke")
+        || name.starts_with("fill")
+        || name.starts_with("marker")
+        || matches!(
+            name,
+            "d" | "x"
+                | "y"
+                | "width"
+                | "height"
+                | "rx"
+                | "ry"
+                | "cx"
+                | "cy"
+                | "r"
+                | "x1"
+                | "y1"
+                | "x2"
+                | "y2"
+                | "points"
+        )
+}
+
+/// Returns the path equivalent of a basic shape or path element
+fn shape_to_path<E: Element>(element: &E) -> Option<Path> {
+    let get = |name: &str| -> Option<f64> {
+        element
+            .get_attribute_local(&name.into())
+            .map_or(Some(0.0), |value| value.parse().ok())
+    };
+    let path = match element.local_name().as_ref() {
+        "path" => Path::parse(element.get_attribute_local(&"d".into())?.as_ref()).ok()?,
+        "line" => Path(vec![
+            Data::MoveTo([get("x1")?, get("y1")?]),
+            Data::LineTo([get("x2")?, get("y2")?]),
+        ]),
+        "polyline" | "polygon" => {
+            let points = element.get_attribute_local(&"points".into())?;
+            let coords = oxvg_collections::regex::NUMERIC_VALUES
+                .find_iter(points.as_ref())
+                .map(|item| item.as_str().parse::<f64>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            let mut data: Vec<_> = coords
+                .chunks_exact(2)
+                .enumerate()
+                .map(|(i, point)| {
+                    if i == 0 {
+                        Data::MoveTo([point[0], point[1]])
+                    } else {
+                        Data::LineTo([point[0], point[1]])
+                    }
+                })
+                .collect();
+            if element.local_name().as_ref() == "polygon" {
+                data.push(Data::ClosePath);
+            }
+            Path(data)
+        }
+        "rect" => {
+            let (x, y, width, height) = (get("x")?, get("y")?, get("width")?, get("height")?);
+            if width <= 0.0 || height <= 0.0 {
+                return None;
+            }
+            let auto = |name: &str| -> Option<Option<f64>> {
+                match element.get_attribute_local(&name.into()) {
+                    Some(value) if value.as_ref() != "auto" => value.parse().ok().map(Some),
+                    _ => Some(None),
+                }
+            };
+            let (rx, ry) = match (auto("rx")?, auto("ry")?) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(r), None) | (None, Some(r)) => (r, r),
+                (None, None) => (0.0, 0.0),
+            };
+            let (rx, ry) = (rx.clamp(0.0, width / 2.0), ry.clamp(0.0, height / 2.0));
+            if rx == 0.0 || ry == 0.0 {
+                Path(vec![
+                    Data::MoveTo([x, y]),
+                    Data::HorizontalLineTo([x + width]),
+                    Data::VerticalLineTo([y + height]),
+                    Data::HorizontalLineTo([x]),
+                    Data::ClosePath,
+                ])
+            } else {
+                let arc = |x: f64, y: f64| Data::ArcTo([rx, ry, 0.0, 0.0, 1.0, x, y]);
+                Path(vec![
+                    Data::MoveTo([x + rx, y]),
+                    Data::HorizontalLineTo([x + width - rx]),
+                    arc(x + width, y + ry),
+                    Data::VerticalLineTo([y + height - ry]),
+                    arc(x + width - rx, y + height),
+                    Data::HorizontalLineTo([x + rx]),
+                    arc(x, y + height - ry),
+                    Data::VerticalLineTo([y + ry]),
+                    arc(x + rx, y),
+                    Data::ClosePath,
+                ])
+            }
+        }
+        "circle" | "ellipse" => {
+            let (cx, cy) = (get("cx")?, get("cy")?);
+            let (rx, ry) = if element.local_name().as_ref() == "circle" {
+                (get("r")?, get("r")?)
+            } else {
+                (get("rx")?, get("ry")?)
+            };
+            if rx <= 0.0 || ry <= 0.0 {
+                return None;
+            }
+            Path(vec![
+                Data::MoveTo([cx, cy - ry]),
+                Data::ArcTo([rx, ry, 0.0, 1.0, 0.0, cx, cy + ry]),
+                Data::ArcTo([rx, ry, 0.0, 1.0, 0.0, cx, cy - ry]),
+                Data::ClosePath,
+            ])
+        }
+        _ => return None,
+    };
+    Some(path)
+}
+
+#[test]
+#[allow(clippy::too_many_lines)]
+fn convert_stroke_to_fill() -> anyhow::Result<()> {
+    use crate::test_config;
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "convertStrokeToFill": {} }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+    <!-- Outline unfilled strokes in place -->
+    <path d="M2 2h20v20" fill="none" stroke="red" stroke-width="2"/>
+    <line x1="2" y1="12" x2="22" y2="12" fill="none" stroke="#000" stroke-linecap="round"/>
+    <polyline points="2 2 12 12 22 2" fill="none" stroke="#000" stroke-linejoin="bevel" stroke-opacity=".5"/>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "convertStrokeToFill": {} }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+    <!-- Use inherited strokes and dashes -->
+    <g fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="square">
+        <rect x="4" y="4" width="16" height="16"/>
+        <path d="M4 12h16" stroke-dasharray="4 2"/>
+    </g>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "convertStrokeToFill": {} }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+    <!-- Add the outline after filled shapes -->
+    <rect id="a" x="4" y="4" width="16" height="16" fill="blue" stroke="red" transform="rotate(45 12 12)"/>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "convertStrokeToFill": {} }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+    <!-- Keep strokes that can't be reproduced by a fill -->
+    <path d="M2 2h20" stroke="red" vector-effect="non-scaling-stroke"/>
+    <path d="M2 2h20" stroke="red" marker-end="url(#a)"/>
+    <path d="M2 2h20" stroke="url(#b)"/>
+    <path d="M2 2h20" stroke="red" stroke-width="10%"/>
+    <path d="M2 2h20" stroke="red" stroke-linejoin="miter-clip"/>
+    <path d="M2 2h20" stroke="red" opacity=".5"/>
+</svg>"#
+        ),
+    )?);
+
+    Ok(())
+}
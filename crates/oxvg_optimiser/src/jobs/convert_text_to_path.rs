@@ -0,0 +1,775 @@
+use std::{
+    path::{Path as FilePath, PathBuf},
+    sync::Arc,
+};
+
+use lightningcss::{
+    printer::PrinterOptions,
+    properties::{
+        font::{
+            AbsoluteFontSize, AbsoluteFontWeight, FontFamily, FontSize, FontStyle, FontWeight,
+            RelativeFontSize,
+        },
+        text::Spacing,
+        Property, PropertyId,
+    },
+    traits::{Parse, ToCss},
+    values::{
+        length::{Length, LengthPercentage, LengthValue},
+        percentage::DimensionPercentage,
+    },
+};
+use oxvg_ast::{
+    attribute::{Attr, Attributes},
+    document::Document,
+    element::Element,
+    get_computed_styles_factory,
+    name::Name,
+    node::{self, Node},
+    style::{ComputedStyles, Id, PresentationAttr, PresentationAttrId, Static, TextAnchor},
+    visitor::{Context, ContextFlags, PrepareOutcome, Visitor},
+};
+use oxvg_path::{command::Data, convert, Path};
+use serde::{Deserialize, Serialize};
+use ttf_parser::{name_id, Face, GlyphId, OutlineBuilder};
+
+use super::convert_path_data::Precision;
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+/// Converts `<text>` elements, and the `<tspan>` elements within them, into `<path>` outlines.
+///
+/// Fonts are only loaded from the configured `fontDirectory`, so that the output doesn't
+/// depend on the fonts installed on the system. TrueType and OpenType fonts and collections are
+/// matched against the computed `font-family`, `font-weight`, and `font-style` of the text.
+/// Fonts without a family name are matched by their file name.
+///
+/// The layout honours `x`, `y`, `dx`, `dy`, `font-size`, `text-anchor`, and `letter-spacing`.
+/// Text that can't be laid out, such as text with a `rotate`, `textLength`, or `<textPath>`,
+/// or text without a matching font, is left as is.
+pub struct ConvertTextToPath {
+    font_directory: Option<PathBuf>,
+    float_precision: Option<Precision>,
+    #[serde(skip)]
+    fonts: Vec<LoadedFont>,
+}
+
+#[derive(Clone)]
+struct LoadedFont {
+    families: Vec<String>,
+    weight: u16,
+    is_italic: bool,
+    index: u32,
+    data: Arc<[u8]>,
+}
+
+impl std::fmt::Debug for LoadedFont {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadedFont")
+            .field("families", &self.families)
+            .field("weight", &self.weight)
+            .field("is_italic", &self.is_italic)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The resolved text styles of a `<text>` or `<tspan>`
+#[derive(Clone)]
+struct TextStyle {
+    families: Vec<String>,
+    size: f32,
+    weight: u16,
+    is_italic: bool,
+    letter_spacing: f32,
+    anchor: TextAnchor,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            families: vec![],
+            size: 16.0,
+            weight: 400,
+            is_italic: false,
+            letter_spacing: 0.0,
+            anchor: TextAnchor::Start,
+        }
+    }
+}
+
+/// An element that owns some of the characters of the text
+struct Owner<E: Element> {
+    element: E,
+    style: TextStyle,
+    font: usize,
+}
+
+#[derive(Default)]
+struct Characters {
+    chars: Vec<(char, usize)>,
+    x: Vec<Option<f32>>,
+    y: Vec<Option<f32>>,
+    dx: Vec<Option<f32>>,
+    dy: Vec<Option<f32>>,
+    is_space_collapsed: bool,
+    is_space_preserved: bool,
+}
+
+impl<E: Element> Visitor<E> for ConvertTextToPath {
+    type Error = String;
+
+    fn prepare(&mut self, _document: &E, _context_flags: &mut ContextFlags) -> PrepareOutcome {
+        let Some(font_directory) = &self.font_directory else {
+            return PrepareOutcome::skip;
+        };
+        if self.fonts.is_empty() {
+            self.fonts = load_fonts(font_directory);
+        }
+        if self.fonts.is_empty() {
+            log::warn!("ConvertTextToPath: no fonts found in {font_directory:?}");
+            return PrepareOutcome::skip;
+        }
+        PrepareOutcome::use_style
+    }
+
+    fn element(&mut self, element: &mut E, context: &mut Context<E>) -> Result<(), String> {
+        if element.prefix().is_some() || element.local_name().as_ref() != "text" {
+            return Ok(());
+        }
+
+        let mut owners = vec![];
+        let mut characters = Characters {
+            is_space_collapsed: true,
+            is_space_preserved: element.attributes().into_iter().any(|attr| {
+                attr.prefix()
+                    .as_ref()
+                    .is_some_and(|prefix| prefix.as_ref() == "xml")
+                    && attr.local_name().as_ref() == "space"
+                    && attr.value().as_ref() == "preserve"
+            }),
+            ..Characters::default()
+        };
+        if self
+            .collect(
+                element,
+                &TextStyle::default(),
+                context,
+                &mut owners,
+                &mut characters,
+            )
+            .is_none()
+        {
+            log::debug!("ConvertTextToPath: cannot convert text");
+            return Ok(());
+        }
+        if !characters.is_space_preserved
+            && characters
+                .chars
+                .last()
+                .is_some_and(|(char, _)| *char == ' ')
+        {
+            characters.chars.pop();
+            characters.x.pop();
+            characters.y.pop();
+            characters.dx.pop();
+            characters.dy.pop();
+        }
+        if characters.chars.is_empty() {
+            return Ok(());
+        }
+
+        let Some(paths) = self.layout(&owners, &characters) else {
+            log::debug!("ConvertTextToPath: cannot layout text");
+            return Ok(());
+        };
+        let options = convert::Options {
+            precision: self.float_precision.unwrap_or_default().0,
+            ..convert::Options::default()
+        };
+        let d_name = "d".into();
+        let mut paths = paths.into_iter().map(|mut path| {
+            options.round_path(&mut path, options.error());
+            path.to_string()
+        });
+
+        remove_text_attributes(element);
+        if owners.len() == 1 {
+            let d = paths.next().unwrap_or_default();
+            element.set_local_name("path".into());
+            element.replace_children(vec![]);
+            element.set_attribute_local(d_name, d.into());
+        } else {
+            let document = context.root.as_document();
+            let mut children = vec![];
+            for (owner, d) in owners.iter().zip(paths) {
+                if d.is_empty() {
+                    continue;
+                }
+                let path = document.create_element(E::Name::new(None, "path".into()));
+                if !owner.element.ptr_eq(element) {
+                    let mut tspans = vec![];
+                    let mut tspan = Some(owner.element.clone());
+                    while let Some(current) = tspan.filter(|current| !current.ptr_eq(element)) {
+                        tspan = Element::parent_element(&current);
+                        tspans.push(current);
+                    }
+                    for tspan in tspans.iter().rev() {
+                        for attr in tspan.attributes().into_iter() {
+                            path.set_attribute(attr.name().clone(), attr.value().clone());
+                        }
+                    }
+                    remove_text_attributes(&path);
+                }
+                path.set_attribute_local(d_name.clone(), d.into());
+                children.push(path.as_child());
+            }
+            element.set_local_name("g".into());
+            element.replace_children(children);
+        }
+        context.flags.set(ContextFlags::skip_children, true);
+        Ok(())
+    }
+}
+
+impl ConvertTextToPath {
+    /// Gathers the characters of the element and it's descendants, along with their styles
+    /// and positions
+    fn collect<E: Element>(
+        &self,
+        element: &E,
+        parent_style: &TextStyle,
+        context: &Context<E>,
+        owners: &mut Vec<Owner<E>>,
+        characters: &mut Characters,
+    ) -> Option<()> {
+        if ["rotate", "textLength", "lengthAdjust"]
+            .into_iter()
+            .any(|name| element.has_attribute_local(&name.into()))
+        {
+            return None;
+        }
+
+        let style = text_style(element, parent_style, context)?;
+        let font = self.find_font(&style)?;
+        let owner = owners.len();
+        owners.push(Owner {
+            element: element.clone(),
+            style,
+            font,
+        });
+
+        let start = characters.chars.len();
+        for child in element.child_nodes() {
+            match child.node_type() {
+                node::Type::Text | node::Type::CDataSection => {
+                    let text = child.text_content().unwrap_or_default();
+                    for char in text.chars() {
+                        characters.push(char, owner);
+                    }
+                }
+                node::Type::Element => {
+                    let child = E::new(child)?;
+                    if child.prefix().is_some() {
+                        return None;
+                    }
+                    match child.local_name().as_ref() {
+                        "tspan" => {
+                            let style = owners[owner].style.clone();
+                            self.collect(&child, &style, context, owners, characters)?;
+                        }
+                        "title" | "desc" | "metadata" => {}
+                        _ => return None,
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let end = characters.chars.len();
+        let size = owners[owner].style.size;
+        for (name, positions) in [
+            ("x", &mut characters.x),
+            ("y", &mut characters.y),
+            ("dx", &mut characters.dx),
+            ("dy", &mut characters.dy),
+        ] {
+            let Some(value) = element.get_attribute_local(&name.into()) else {
+                continue;
+            };
+            for (position, value) in positions[start..end].iter_mut().zip(
+                value
+                    .as_ref()
+                    .split([' ', ',', '\t', '\n', '\r'])
+                    .filter(|v| !v.is_empty()),
+            ) {
+                if position.is_none() {
+                    *position = Some(parse_length(value, size)?);
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// Positions each character and returns the outlines for each owner
+    fn layout<E: Element>(
+        &self,
+        owners: &[Owner<E>],
+        characters: &Characters,
+    ) -> Option<Vec<Path>> {
+        let faces = self
+            .fonts
+            .iter()
+            .map(|font| Face::parse(&font.data, font.index).ok())
+            .collect::<Vec<_>>();
+
+        let mut glyphs = Vec::with_capacity(characters.chars.len());
+        let mut chunks: Vec<(usize, f32, f32)> = vec![];
+        let (mut pen_x, mut pen_y) = (0.0, 0.0);
+        let mut previous = None;
+        for (i, (char, owner_index)) in characters.chars.iter().enumerate() {
+            let owner = &owners[*owner_index];
+            let face = faces[owner.font].as_ref()?;
+            let scale = owner.style.size / f32::from(face.units_per_em());
+
+            let is_new_chunk = characters.x[i].is_some() || characters.y[i].is_some();
+            if is_new_chunk {
+                previous = None;
+            }
+            pen_x = characters.x[i].unwrap_or(pen_x) + characters.dx[i].unwrap_or(0.0);
+            pen_y = characters.y[i].unwrap_or(pen_y) + characters.dy[i].unwrap_or(0.0);
+            if is_new_chunk || i == 0 {
+                chunks.push((i, pen_x, pen_x));
+            }
+
+            // NOTE: Missing whitespace is skipped over, but other missing glyphs would need a
+            // fallback font that we can't predict
+            let glyph = match face.glyph_index(*char) {
+                Some(glyph) => glyph,
+                None if char.is_whitespace() => GlyphId(0),
+                None => return None,
+            };
+            if let Some((previous_font, previous_glyph)) = previous {
+                if previous_font == owner.font {
+                    pen_x += f32::from(kerning(face, previous_glyph, glyph)) * scale;
+                }
+            }
+            glyphs.push((glyph, pen_x, pen_y, scale));
+            pen_x += f32::from(face.glyph_hor_advance(glyph).unwrap_or_default()) * scale
+                + owner.style.letter_spacing;
+            previous = Some((owner.font, glyph));
+            if let Some(chunk) = chunks.last_mut() {
+                chunk.2 = pen_x;
+            }
+        }
+
+        let mut paths = vec![Path(vec![]); owners.len()];
+        let mut chunks = chunks.into_iter().peekable();
+        let mut shift = 0.0;
+        for (i, (glyph, x, y, scale)) in glyphs.into_iter().enumerate() {
+            let owner_index = characters.chars[i].1;
+            let owner = &owners[owner_index];
+            if chunks.peek().is_some_and(|chunk| chunk.0 == i) {
+                let (_, start, end) = chunks.next()?;
+                shift = match owner.style.anchor {
+                    TextAnchor::Start => 0.0,
+                    TextAnchor::Middle => (start - end) / 2.0,
+                    TextAnchor::End => start - end,
+                };
+            }
+            if glyph.0 == 0 {
+                continue;
+            }
+            let face = faces[owner.font].as_ref()?;
+            let mut builder = PathBuilder {
+                path: &mut paths[owner_index],
+                x: f64::from(x + shift),
+                y: f64::from(y),
+                scale: f64::from(scale),
+            };
+            face.outline_glyph(glyph, &mut builder);
+        }
+        Some(paths)
+    }
+
+    /// Returns the index of the loaded font that best matches the style
+    fn find_font(&self, style: &TextStyle) -> Option<usize> {
+        style.families.iter().find_map(|family| {
+            self.fonts
+                .iter()
+                .enumerate()
+                .filter(|(_, font)| {
+                    font.families
+                        .iter()
+                        .any(|name| name.eq_ignore_ascii_case(family))
+                })
+                .min_by_key(|(_, font)| {
+                    (
+                        font.is_italic != style.is_italic,
+                        font.weight.abs_diff(style.weight),
+                    )
+                })
+                .map(|(index, _)| index)
+        })
+    }
+}
+
+impl Characters {
+    fn push(&mut self, char: char, owner: usize) {
+        let char = match char {
+            '\n' | '\r' if !self.is_space_preserved => return,
+            '\n' | '\r' | '\t' => ' ',
+            char => char,
+        };
+        if char == ' ' && !self.is_space_preserved {
+            if self.is_space_collapsed {
+                return;
+            }
+            self.is_space_collapsed = true;
+        } else {
+            self.is_space_collapsed = false;
+        }
+        self.chars.push((char, owner));
+        self.x.push(None);
+        self.y.push(None);
+        self.dx.push(None);
+        self.dy.push(None);
+    }
+}
+
+struct PathBuilder<'a> {
+    path: &'a mut Path,
+    x: f64,
+    y: f64,
+    scale: f64,
+}
+
+impl PathBuilder<'_> {
+    fn point(&self, x: f32, y: f32) -> [f64; 2] {
+        [
+            self.x + f64::from(x) * self.scale,
+            self.y - f64::from(y) * self.scale,
+        ]
+    }
+}
+
+impl OutlineBuilder for PathBuilder<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.path.0.push(Data::MoveTo(self.point(x, y)));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.path.0.push(Data::LineTo(self.point(x, y)));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let [x1, y1] = self.point(x1, y1);
+        let [x, y] = self.point(x, y);
+        self.path.0.push(Data::QuadraticBezierTo([x1, y1, x, y]));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let [x1, y1] = self.point(x1, y1);
+        let [x2, y2] = self.point(x2, y2);
+        let [x, y] = self.point(x, y);
+        self.path
+            .0
+            .push(Data::CubicBezierTo([x1, y1, x2, y2, x, y]));
+    }
+
+    fn close(&mut self) {
+        self.path.0.push(Data::ClosePath);
+    }
+}
+
+fn kerning(face: &Face, left: GlyphId, right: GlyphId) -> i16 {
+    face.tables()
+        .kern
+        .and_then(|kern| {
+            kern.subtables
+                .into_iter()
+                .filter(|subtable| {
+                    subtable.horizontal && !subtable.variable && !subtable.has_cross_stream
+                })
+                .find_map(|subtable| subtable.glyphs_kerning(left, right))
+        })
+        .unwrap_or_default()
+}
+
+/// Reads each font file within the directory and it's subdirectories
+fn load_fonts(directory: &FilePath) -> Vec<LoadedFont> {
+    let mut fonts = vec![];
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        log::warn!("ConvertTextToPath: failed to read {directory:?}");
+        return fonts;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            fonts.extend(load_fonts(&path));
+            continue;
+        }
+        let is_font = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                ["ttf", "otf", "ttc", "otc"]
+                    .iter()
+                    .any(|font_extension| extension.eq_ignore_ascii_case(font_extension))
+            });
+        if !is_font {
+            continue;
+        }
+        let Ok(data) = std::fs::read(&path) else {
+            log::warn!("ConvertTextToPath: failed to read {path:?}");
+            continue;
+        };
+        let data: Arc<[u8]> = data.into();
+        let file_name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        for index in 0..ttf_parser::fonts_in_collection(&data).unwrap_or(1) {
+            let Ok(face) = Face::parse(&data, index) else {
+                log::warn!("ConvertTextToPath: failed to parse {path:?}");
+                continue;
+            };
+            let mut families: Vec<_> = face
+                .names()
+                .into_iter()
+                .filter(|name| {
+                    matches!(name.name_id, name_id::FAMILY | name_id::TYPOGRAPHIC_FAMILY)
+                })
+                .filter_map(|name| name.to_string())
+                .collect();
+            if families.is_empty() {
+                families.push(file_name.to_string());
+            }
+            log::debug!("ConvertTextToPath: loaded {families:?} from {path:?}");
+            fonts.push(LoadedFont {
+                families,
+                weight: face.weight().to_number(),
+                is_italic: face.is_italic() || face.is_oblique(),
+                index,
+                data: data.clone(),
+            });
+        }
+    }
+    fonts
+}
+
+/// Resolves the text styles of the element, falling back to the styles of the parent
+fn text_style<E: Element>(
+    element: &E,
+    parent_style: &TextStyle,
+    context: &Context<E>,
+) -> Option<TextStyle> {
+    let computed_styles =
+        ComputedStyles::default().with_all(element, &context.stylesheet, context.element_styles);
+    get_computed_styles_factory!(computed_styles);
+    let mut style = parent_style.clone();
+
+    if let Some(family) = get_computed_styles!(FontFamily) {
+        let (Static::Attr(PresentationAttr::FontFamily(families))
+        | Static::Css(Property::FontFamily(families))) = family.inner()
+        else {
+            return None;
+        };
+        style.families = families
+            .iter()
+            .filter_map(|family| match family {
+                FontFamily::FamilyName(name) => name
+                    .to_css_string(PrinterOptions::default())
+                    .ok()
+                    .map(|name| name.trim_matches(['"', '\'']).to_string()),
+                FontFamily::Generic(_) => None,
+            })
+            .collect();
+    }
+    if let Some(size) = get_computed_styles!(FontSize) {
+        let (Static::Attr(PresentationAttr::FontSize(size))
+        | Static::Css(Property::FontSize(size))) = size.inner()
+        else {
+            return None;
+        };
+        style.size = font_size_to_px(&size, parent_style.size)?;
+    }
+    if let Some(weight) = get_computed_styles!(FontWeight) {
+        let (Static::Attr(PresentationAttr::FontWeight(weight))
+        | Static::Css(Property::FontWeight(weight))) = weight.inner()
+        else {
+            return None;
+        };
+        style.weight = match weight {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // clamped to u16
+            FontWeight::Absolute(AbsoluteFontWeight::Weight(weight)) => {
+                weight.clamp(1.0, 1000.0) as u16
+            }
+            FontWeight::Absolute(AbsoluteFontWeight::Normal) => 400,
+            FontWeight::Absolute(AbsoluteFontWeight::Bold) => 700,
+            FontWeight::Bolder => parent_style.weight.saturating_add(300).min(900),
+            FontWeight::Lighter => parent_style.weight.saturating_sub(300).max(100),
+        };
+    }
+    if let Some(font_style) = get_computed_styles!(FontStyle) {
+        let (Static::Attr(PresentationAttr::FontStyle(font_style))
+        | Static::Css(Property::FontStyle(font_style))) = font_style.inner()
+        else {
+            return None;
+        };
+        style.is_italic = !matches!(font_style, FontStyle::Normal);
+    }
+    if let Some(letter_spacing) = get_computed_styles!(LetterSpacing) {
+        let (Static::Attr(PresentationAttr::LetterSpacing(letter_spacing))
+        | Static::Css(Property::LetterSpacing(letter_spacing))) = letter_spacing.inner()
+        else {
+            return None;
+        };
+        style.letter_spacing = match letter_spacing {
+            Spacing::Normal => 0.0,
+            Spacing::Length(Length::Value(length)) => length_to_px(&length, style.size)?,
+            Spacing::Length(Length::Calc(_)) => return None,
+        };
+    }
+    if let Some(anchor) = computed_styles
+        .attr
+        .get(&PresentationAttrId::TextAnchor)
+        .or_else(|| {
+            computed_styles
+                .inherited
+                .get(&Id::Attr(PresentationAttrId::TextAnchor))
+        })
+    {
+        let Static::Attr(PresentationAttr::TextAnchor(anchor)) = anchor.inner() else {
+            return None;
+        };
+        style.anchor = anchor.clone();
+    }
+    Some(style)
+}
+
+fn font_size_to_px(size: &FontSize, parent_size: f32) -> Option<f32> {
+    Some(match size {
+        FontSize::Length(DimensionPercentage::Dimension(length)) => {
+            length_to_px(length, parent_size)?
+        }
+        FontSize::Length(DimensionPercentage::Percentage(percentage)) => percentage.0 * parent_size,
+        FontSize::Length(DimensionPercentage::Calc(_)) => return None,
+        FontSize::Absolute(size) => match size {
+            AbsoluteFontSize::XXSmall => 9.0,
+            AbsoluteFontSize::XSmall => 10.0,
+            AbsoluteFontSize::Small => 13.0,
+            AbsoluteFontSize::Medium => 16.0,
+            AbsoluteFontSize::Large => 18.0,
+            AbsoluteFontSize::XLarge => 24.0,
+            AbsoluteFontSize::XXLarge => 32.0,
+            AbsoluteFontSize::XXXLarge => 48.0,
+        },
+        FontSize::Relative(RelativeFontSize::Larger) => parent_size * 1.2,
+        FontSize::Relative(RelativeFontSize::Smaller) => parent_size / 1.2,
+    })
+}
+
+fn length_to_px(length: &LengthValue, font_size: f32) -> Option<f32> {
+    match length {
+        LengthValue::Em(em) => Some(em * font_size),
+        LengthValue::Rem(rem) => Some(rem * 16.0),
+        length => length.to_px(),
+    }
+}
+
+fn parse_length(value: &str, font_size: f32) -> Option<f32> {
+    let value = value.trim();
+    if let Ok(value) = value.parse() {
+        return Some(value);
+    }
+    match LengthPercentage::parse_string(value).ok()? {
+        DimensionPercentage::Dimension(length) => length_to_px(&length, font_size),
+        _ => None,
+    }
+}
+
+fn remove_text_attributes<E: Element>(element: &E) {
+    element.attributes().retain(|attr| {
+        if attr
+            .prefix()
+            .as_ref()
+            .is_some_and(|prefix| prefix.as_ref() == "xml")
+        {
+            return attr.local_name().as_ref() != "space";
+        }
+        attr.prefix().is_some()
+            || !matches!(
+                attr.local_name().as_ref(),
+                "x" | "y"
+                    | "dx"
+                    | "dy"
+                    | "font"
+                    | "font-family"
+                    | "font-size"
+                    | "font-size-adjust"
+                    | "font-stretch"
+                    | "font-style"
+                    | "font-variant"
+                    | "font-weight"
+                    | "letter-spacing"
+                    | "word-spacing"
+                    | "text-anchor"
+                    | "dominant-baseline"
+                    | "alignment-baseline"
+                    | "baseline-shift"
+                    | "writing-mode"
+                    | "direction"
+                    | "unicode-bidi"
+                    | "text-rendering"
+            )
+    });
+}
+
+#[test]
+fn convert_text_to_path() -> anyhow::Result<()> {
+    use crate::test_config;
+
+    let config = format!(
+        r#"{{ "convertTextToPath": {{ "fontDirectory": "{}/tests/fonts" }} }}"#,
+        env!("CARGO_MANIFEST_DIR")
+    );
+
+    insta::assert_snapshot!(test_config(
+        &config,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- Convert text into a path -->
+    <text x="10" y="50" font-family="demo" font-size="20" fill="red">AA</text>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        &config,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- Honour text-anchor, letter-spacing, and tspan positions -->
+    <text x="50" y="50" style="font-family: demo" font-size="10" text-anchor="middle" letter-spacing="2">
+        A
+        <tspan dx="5 5" fill="blue">AA</tspan>
+    </text>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        &config,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- Keep text without a matching font or with an unsupported layout -->
+    <text x="10" y="50" font-family="Unknown Sans">AA</text>
+    <text x="10" y="50" font-family="demo">AB</text>
+    <text x="10" y="50" font-family="demo" rotate="45">AA</text>
+    <text x="10" y="50" font-family="demo"><textPath href="#a">AA</textPath></text>
+</svg>"##
+        ),
+    )?);
+
+    Ok(())
+}
@@ -0,0 +1,534 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use lightningcss::{
+    printer::PrinterOptions,
+    rules::{CssRule, CssRuleList},
+    stylesheet::{ParserOptions, StyleAttribute, StyleSheet},
+    traits::ToCss,
+    visit_types,
+    visitor::Visit,
+};
+use oxvg_ast::{
+    attribute::{Attr, Attributes},
+    document::Document,
+    element::Element,
+    name::Name,
+    node::{self, Node},
+    parse,
+    visitor::{Context, ContextFlags, PrepareOutcome, Visitor},
+};
+use serde::{Deserialize, Serialize};
+
+use super::inline_styles;
+
+/// How many stylesheets deep `@import` rules are inlined
+const MAX_IMPORT_DEPTH: usize = 16;
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+/// Embeds the local files a document references, so that it's self-contained.
+///
+/// - `<image>` and `<feImage>` references are replaced with data urls
+/// - `<use>` references to another file copy the referenced element into the document's `<defs>`
+/// - `@import` rules in `<style>` elements are replaced with the imported stylesheet
+/// - `url()`s in styles are replaced with data urls
+///
+/// References are resolved against `directory`, or otherwise the directory of the document
+/// being optimised, and files outside of that directory aren't embedded. References that are
+/// fragments, absolute, or have a scheme, such as `https:`, are left as is, as are images and
+/// `url()`s with a fragment, such as `url(icons.svg#a)`. Elements copied for `<use>` are copied
+/// without the other elements they may reference.
+pub struct EmbedExternalResources {
+    directory: Option<PathBuf>,
+    #[serde(skip)]
+    ids: HashSet<String>,
+    #[serde(skip)]
+    imported: HashMap<(PathBuf, Option<String>), String>,
+    /// The canonical directory files must be within, once it's been resolved for the document
+    #[serde(skip)]
+    base: OnceLock<Option<PathBuf>>,
+}
+
+struct CssVisitor<'a> {
+    base: &'a Path,
+    directory: &'a Path,
+    changed: bool,
+}
+
+impl<E: Element> Visitor<E> for EmbedExternalResources {
+    type Error = String;
+
+    fn prepare(&mut self, document: &E, _context_flags: &mut ContextFlags) -> PrepareOutcome {
+        self.ids = document
+            .breadth_first()
+            .filter_map(|element| {
+                element
+                    .get_attribute_local(&"id".into())
+                    .map(|id| id.to_string())
+            })
+            .collect();
+        PrepareOutcome::none
+    }
+
+    fn element(&mut self, element: &mut E, context: &mut Context<E>) -> Result<(), String> {
+        // NOTE: The path of the document is only known from the context, so the base is resolved
+        // for the first element rather than while preparing
+        let base = self.base.get_or_init(|| {
+            let directory = match &self.directory {
+                Some(directory) => directory.as_path(),
+                None => context.info.path.as_ref()?.parent()?,
+            };
+            directory.canonicalize().ok()
+        });
+        let Some(base) = base.clone() else {
+            return Ok(());
+        };
+        self.embed(element, &context.root, &base, &base);
+        Ok(())
+    }
+}
+
+impl EmbedExternalResources {
+    /// Embeds the resources referenced by the element, where `base` is the directory files must
+    /// be within, and `directory` is the one references are relative to.
+    fn embed<E: Element>(&mut self, element: &E, root: &E, base: &Path, directory: &Path) {
+        if element.prefix().is_none() {
+            match element.local_name().as_ref() {
+                "image" | "feImage" => Self::embed_image(element, base, directory),
+                "use" => self.embed_use(element, root, base, directory),
+                "style" => Self::embed_style_content(element, base, directory),
+                _ => {}
+            }
+        }
+        Self::embed_style_attr(element, base, directory);
+    }
+
+    fn embed_image<E: Element>(element: &E, base: &Path, directory: &Path) {
+        for mut attr in element.attributes().into_iter_mut() {
+            if !is_href(attr.name()) {
+                continue;
+            }
+            let Some((path, None)) = resolve(base, directory, attr.value()) else {
+                continue;
+            };
+            if let Some(data_url) = data_url(&path) {
+                attr.set_value(data_url.into());
+            }
+        }
+    }
+
+    fn embed_use<E: Element>(&mut self, element: &E, root: &E, base: &Path, directory: &Path) {
+        for mut attr in element.attributes().into_iter_mut() {
+            if !is_href(attr.name()) {
+                continue;
+            }
+            let Some((path, fragment)) = resolve(base, directory, attr.value()) else {
+                continue;
+            };
+            if let Some(id) = self.import(root, base, path, fragment) {
+                attr.set_value(format!("#{id}").into());
+            }
+        }
+    }
+
+    /// Copies the element of the file with the given id, or it's root element, into the `<defs>`
+    /// of the document, returning the id of the copy.
+    fn import<E: Element>(
+        &mut self,
+        root: &E,
+        base: &Path,
+        path: PathBuf,
+        fragment: Option<String>,
+    ) -> Option<String> {
+        let key = (path, fragment);
+        if let Some(id) = self.imported.get(&key) {
+            return Some(id.clone());
+        }
+        let (path, fragment) = &key;
+
+        // NOTE: The document is kept until the copy is made, as dropping a markup5ever document
+        // empties the children of it's descendants
+        let document = match <E::ParentChild as parse::Node>::parse_path(path) {
+            Ok(document) => document,
+            Err(error) => {
+                log::debug!("failed to load {path:?}: {error}");
+                return None;
+            }
+        };
+        let document_element = <E as Element>::find_element(document.clone())?;
+        let source = match fragment {
+            Some(id) => find_by_id(&document_element, id)?,
+            None => document_element,
+        };
+
+        let stem = path.file_stem().and_then(OsStr::to_str).unwrap_or("use");
+        let id = self.unique_id(fragment.as_deref().unwrap_or(stem), stem);
+        self.imported.insert(key.clone(), id.clone());
+
+        let copy = import_element(&root.as_document(), &source);
+        drop(document);
+        copy.set_attribute_local("id".into(), id.as_str().into());
+        defs(root)?.append(copy.as_child());
+
+        let directory = key.0.parent().unwrap_or(Path::new(""));
+        self.embed(&copy, root, base, directory);
+        for element in copy.breadth_first() {
+            self.embed(&element, root, base, directory);
+        }
+        Some(id)
+    }
+
+    /// Returns the id if it's not used by the document, or otherwise a variant of it that isn't
+    fn unique_id(&mut self, id: &str, stem: &str) -> String {
+        let mut unique = id.to_string();
+        let mut index = 0;
+        while self.ids.contains(&unique) {
+            index += 1;
+            unique = format!("{stem}-{id}-{index}");
+        }
+        self.ids.insert(unique.clone());
+        unique
+    }
+
+    fn embed_style_content<E: Element>(element: &E, base: &Path, directory: &Path) {
+        let Some(css) = element.text_content() else {
+            return;
+        };
+        let Some((css, true)) = embed_stylesheet(&css, base, directory, 0) else {
+            return;
+        };
+        inline_styles::set_style_content(element, css.into());
+    }
+
+    fn embed_style_attr<E: Element>(element: &E, base: &Path, directory: &Path) {
+        let style_name = "style".into();
+        let Some(mut style) = element.get_attribute_node_local_mut(&style_name) else {
+            return;
+        };
+        let Ok(mut css_source) =
+            StyleAttribute::parse(style.value().as_ref(), ParserOptions::default())
+        else {
+            return;
+        };
+        let mut visitor = CssVisitor {
+            base,
+            directory,
+            changed: false,
+        };
+        let _ = css_source.visit(&mut visitor);
+        if !visitor.changed {
+            return;
+        }
+
+        let Ok(css) = css_source.to_css(PrinterOptions {
+            minify: true,
+            ..PrinterOptions::default()
+        }) else {
+            return;
+        };
+        let css_atom = css.code.into();
+        drop(css_source);
+        style.set_value(css_atom);
+    }
+}
+
+impl<'i> lightningcss::visitor::Visitor<'i> for CssVisitor<'_> {
+    type Error = ();
+
+    fn visit_types(&self) -> lightningcss::visitor::VisitTypes {
+        visit_types!(URLS)
+    }
+
+    fn visit_url(
+        &mut self,
+        url: &mut lightningcss::values::url::Url<'i>,
+    ) -> Result<(), Self::Error> {
+        let Some((path, None)) = resolve(self.base, self.directory, &url.url) else {
+            return Ok(());
+        };
+        if let Some(data_url) = data_url(&path) {
+            url.url = data_url.into();
+            self.changed = true;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the stylesheet with it's local imports inlined and urls embedded, along with whether
+/// anything was embedded.
+fn embed_stylesheet(
+    css: &str,
+    base: &Path,
+    directory: &Path,
+    depth: usize,
+) -> Option<(String, bool)> {
+    let (imports, rules, changed) = embed_imports(css, base, directory, depth)?;
+    Some((format!("{imports}{rules}"), changed))
+}
+
+/// Returns the `@import` rules that couldn't be inlined, and the rest of the stylesheet with it's
+/// local imports inlined and urls embedded, along with whether anything was embedded.
+///
+/// The imports that an inlined stylesheet keeps are hoisted into the returned imports, since
+/// `@import` rules that follow other rules are ignored.
+fn embed_imports(
+    css: &str,
+    base: &Path,
+    directory: &Path,
+    depth: usize,
+) -> Option<(String, String, bool)> {
+    let mut css = match StyleSheet::parse(css, ParserOptions::default()) {
+        Ok(css) => css,
+        Err(error) => {
+            log::debug!("failed to parse stylesheet: {error}");
+            return None;
+        }
+    };
+    let options = || PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+    };
+
+    let mut imports = String::new();
+    let mut inlined = String::new();
+    let mut rules = vec![];
+    for rule in std::mem::take(&mut css.rules.0) {
+        let CssRule::Import(import) = rule else {
+            rules.push(rule);
+            continue;
+        };
+        let imported = if depth < MAX_IMPORT_DEPTH
+            && import.layer.is_none()
+            && import.supports.is_none()
+            && import.media.media_queries.is_empty()
+        {
+            resolve(base, directory, &import.url).and_then(|(path, _)| {
+                let css = std::fs::read_to_string(&path)
+                    .inspect_err(|error| log::debug!("failed to load {path:?}: {error}"))
+                    .ok()?;
+                let directory = path.parent().unwrap_or(Path::new(""));
+                embed_imports(&css, base, directory, depth + 1)
+            })
+        } else {
+            None
+        };
+        match imported {
+            Some((nested_imports, css, _)) => {
+                imports.push_str(&nested_imports);
+                inlined.push_str(&css);
+            }
+            None => imports.push_str(&import.to_css_string(options()).ok()?),
+        }
+    }
+    let mut rules = CssRuleList(rules);
+    let mut visitor = CssVisitor {
+        base,
+        directory,
+        changed: !inlined.is_empty(),
+    };
+    let _ = rules.visit(&mut visitor);
+
+    let rules = rules.to_css_string(options()).ok()?;
+    Some((imports, format!("{inlined}{rules}"), visitor.changed))
+}
+
+fn is_href<N: Name>(name: &N) -> bool {
+    name.local_name().as_ref() == "href"
+        && name
+            .prefix()
+            .as_ref()
+            .is_none_or(|prefix| prefix.as_ref() == "xlink")
+}
+
+/// Returns the path a url refers to relative to the directory, along with it's fragment.
+///
+/// Urls that are only a fragment, are absolute, have a scheme, or refer to a file outside of the
+/// canonical `base` directory, such as through `../` or a symlink, aren't resolved.
+fn resolve(base: &Path, directory: &Path, url: &str) -> Option<(PathBuf, Option<String>)> {
+    let url = url.trim();
+    if url.starts_with(['#', '/', '\\']) {
+        return None;
+    }
+    if url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    }) {
+        return None;
+    }
+
+    let (path, fragment) = match url.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment.to_string())),
+        None => (url, None),
+    };
+    let path = path.split('?').next().unwrap_or_default();
+    if path.is_empty() {
+        return None;
+    }
+    let path = urlencoding::decode(path).ok()?;
+    let path = directory.join(path.as_ref()).canonicalize().ok()?;
+    if !path.starts_with(base) {
+        log::debug!("not embedding {path:?}: outside of {base:?}");
+        return None;
+    }
+    Some((path, fragment))
+}
+
+fn data_url(path: &Path) -> Option<String> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(error) => {
+            log::debug!("failed to load {path:?}: {error}");
+            return None;
+        }
+    };
+    let extension = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase);
+    let mime_type = match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("bmp") => "image/bmp",
+        Some("ico") => "image/x-icon",
+        Some("svg") => "image/svg+xml",
+        Some("css") => "text/css",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        _ => "application/octet-stream",
+    };
+    Some(format!("data:{mime_type};base64,{}", STANDARD.encode(data)))
+}
+
+fn find_by_id<E: Element>(element: &E, id: &str) -> Option<E> {
+    element.breadth_first().find(|element| {
+        element
+            .get_attribute_local(&"id".into())
+            .is_some_and(|value| value.as_ref() == id)
+    })
+}
+
+/// Returns the first `<defs>` of the root element, creating one if it doesn't exist
+fn defs<E: Element>(root: &E) -> Option<E> {
+    let root = if root.node_type() == node::Type::Document {
+        root.children().into_iter().next()?
+    } else {
+        root.clone()
+    };
+    if let Some(defs) = root
+        .children()
+        .into_iter()
+        .find(|child| child.prefix().is_none() && child.local_name().as_ref() == "defs")
+    {
+        return Some(defs);
+    }
+
+    let defs = root.as_document().create_element(Name::parse("defs"));
+    root.clone().insert(0, defs.as_child());
+    Some(defs)
+}
+
+/// Copies the element from another document.
+///
+/// As the namespaces of the other document may not be declared, `xlink:href` is copied as `href`
+/// and other prefixed elements and attributes, aside from `xml:`, aren't copied.
+fn import_element<E: Element>(document: &impl Document<Root = E>, source: &E) -> E {
+    let copy = document.create_element(Name::parse(&source.qual_name().formatter().to_string()));
+    for attr in source.attributes().into_iter() {
+        let name = attr.name();
+        let name = match name.prefix().as_ref().map(AsRef::as_ref) {
+            None if name.local_name().as_ref() == "xmlns" => continue,
+            None | Some("xml") => name.formatter().to_string(),
+            Some("xlink") if is_href(name) => String::from("href"),
+            Some(_) => continue,
+        };
+        copy.set_attribute(Name::parse(&name), attr.value().as_ref().into());
+    }
+    source.for_each_child(|child| match child.node_type() {
+        node::Type::Element => {
+            if let Some(child) = E::new(child).filter(|child| child.prefix().is_none()) {
+                copy.append(import_element(document, &child).as_child());
+            }
+        }
+        node::Type::Text | node::Type::CDataSection => {
+            if let Some(text) = child.text_content() {
+                copy.append(document.create_text_node(text.into()));
+            }
+        }
+        _ => {}
+    });
+    copy
+}
+
+#[test]
+fn embed_external_resources() -> anyhow::Result<()> {
+    use crate::test_config;
+
+    let config = format!(
+        r#"{{ "embedExternalResources": {{ "directory": "{}/tests/embed" }} }}"#,
+        env!("CARGO_MANIFEST_DIR")
+    );
+
+    insta::assert_snapshot!(test_config(
+        &config,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+    <!-- local images are embedded -->
+    <image href="image.png"/>
+    <image xlink:href="./image%20copy.svg"/>
+    <filter id="filter"><feImage href="image.png"/></filter>
+    <!-- other references are left as is -->
+    <image href="https://example.com/image.png"/>
+    <image href="data:image/png;base64,..."/>
+    <image href="missing.png"/>
+    <image href="image.svg#a"/>
+    <image href="../fonts/demo.ttf"/>
+    <image href="../embed/../../tests/fonts/demo.ttf"/>
+    <use href="#filter"/>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        &config,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <!-- referenced elements are copied -->
+    <use href="icons.svg#circle"/>
+    <use href="icons.svg#circle" x="10"/>
+    <use href="icons.svg#square"/>
+    <!-- with a unique id -->
+    <rect id="square"/>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        &config,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <defs>
+        <style>
+            @import "style.css";
+            @import "https://example.com/style.css" screen;
+            .b { fill: url(image.svg#a); }
+        </style>
+    </defs>
+    <rect style="background: url(image.png)"/>
+</svg>"#
+        ),
+    )?);
+
+    Ok(())
+}
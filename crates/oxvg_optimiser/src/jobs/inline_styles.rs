@@ -7,8 +7,10 @@ use oxvg_ast::{
     atom::Atom,
     attribute::Attr,
     class_list::ClassList,
+    document::Document,
     element::Element,
     name::Name,
+    node::{self, Node},
     visitor::{Context, ContextFlags, Visitor},
 };
 use oxvg_collections::collections::{PRESENTATION, PSEUDO_FUNCTIONAL, PSEUDO_TREE_STRUCTURAL};
@@ -130,7 +132,7 @@ impl<E: Element> Visitor<E> for InlineStyles<E> {
                 minify: true,
                 ..printer::PrinterOptions::default()
             }) {
-                element.clone().set_text_content(css.into());
+                set_style_content(element, css.into());
             }
             log::debug!("Not merging style: foreign-object");
             return Ok(());
@@ -173,7 +175,7 @@ impl<E: Element> Visitor<E> for InlineStyles<E> {
             log::debug!("all styles removed from element");
             element.remove();
         } else {
-            element.clone().set_text_content(css.into());
+            set_style_content(element, css.into());
         };
         Ok(())
     }
@@ -475,6 +477,19 @@ impl<E: Element> InlineStyles<E> {
     }
 }
 
+/// Replaces the content of a style element with the given css, keeping it within a CDATA section
+/// if the element's content was one.
+pub fn set_style_content<E: Element>(element: &E, css: E::Atom) {
+    let mut element = element.clone();
+    if element.any_child(|child| child.node_type() == node::Type::CDataSection) {
+        let c_data = element.as_document().create_c_data_section(css);
+        element.empty();
+        element.append_child(c_data);
+    } else {
+        element.set_text_content(css);
+    }
+}
+
 fn flatten_media(css: rules::CssRuleList) -> rules::CssRuleList {
     rules::CssRuleList(
         css.0
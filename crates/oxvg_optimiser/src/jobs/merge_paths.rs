@@ -419,5 +419,20 @@ fn merge_paths() -> anyhow::Result<()> {
         ),
     )?);
 
+    insta::assert_snapshot!(test_config(
+        r#"{ "mergePaths": {} }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- merge paths within concave paths or outside of curves -->
+    <path d="M0 0H30V30H20V10H10V30H0z"/>
+    <path d="M12 12H18V28H12z"/>
+    <path d="M50 0C50 40 90 40 90 0z"/>
+    <path d="M50 26H54V30H50z"/>
+    <!-- don't merge paths that overlap -->
+    <path d="M60 20H70V40H60z"/>
+</svg>"#
+        ),
+    )?);
+
     Ok(())
 }
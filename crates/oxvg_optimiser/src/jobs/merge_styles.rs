@@ -100,7 +100,7 @@ impl<E: Element> Visitor<E> for MergeStyles<E> {
             style.remove();
             return Ok(());
         };
-        style.for_each_child(|child| child.remove());
+        style.empty();
         let c_data = document.as_document().create_c_data_section(text.into());
         style.append_child(c_data);
         Ok(())
@@ -312,22 +312,21 @@ fn merge_styles() -> anyhow::Result<()> {
         ),
     )?);
 
-    // WARN: CData not supported by rcdom implementation
-    // insta::assert_snapshot!(test_config(
-    //     r#"{ "mergeStyles": true }"#,
-    //     Some(
-    //         r#"<svg id="test" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
-    //     <style>
-    //         .st0 { fill: yellow; }
-    //     </style>
-    //     <style>
-    //         <![CDATA[
-    //             .st1 { fill: red; }
-    //         ]]>
-    //     </style>
-    // </svg>"#
-    //     ),
-    // )?);
+    insta::assert_snapshot!(test_config(
+        r#"{ "mergeStyles": true }"#,
+        Some(
+            r#"<svg id="test" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <style>
+        .st0 { fill: yellow; }
+    </style>
+    <style>
+        <![CDATA[
+            .st1 { fill: red; }
+        ]]>
+    </style>
+</svg>"#
+        ),
+    )?);
 
     Ok(())
 }
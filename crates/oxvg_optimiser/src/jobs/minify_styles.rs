@@ -97,7 +97,7 @@ impl MinifyStyles {
             log::debug!("removing element: all styles removed");
             element.remove();
         } else {
-            element.clone().set_text_content(css.code.into());
+            inline_styles::set_style_content(element, css.code.into());
         }
     }
 
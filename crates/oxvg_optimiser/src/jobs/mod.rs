@@ -2,12 +2,13 @@ use std::fmt::Display;
 
 use oxvg_ast::{
     element::Element,
+    serialize,
     visitor::{ContextFlags, Info, PrepareOutcome, Visitor},
 };
 use serde::{Deserialize, Serialize};
 
 macro_rules! jobs {
-    ($($name:ident: $job:ident$(< $($t:ty),* >)? $((is_default: $default:ident))?,)+) => {
+    ($($(#[$meta:meta])* $name:ident: $job:ident$(< $($t:ty),* >)? $((is_default: $default:ident))?,)+) => {
         $(mod $name;)+
 
         $(pub use self::$name::$job;)+
@@ -15,7 +16,7 @@ macro_rules! jobs {
         #[derive(Deserialize, Serialize, Clone, Debug)]
         #[serde(rename_all = "camelCase", bound = "E: Element")]
         pub struct Jobs<E: Element> {
-            $($name: Option<$job $( < $($t),* >)?>),+
+            $($(#[$meta])* $name: Option<$job $( < $($t),* >)?>),+
         }
 
         impl<E: Element> Default for Jobs<E> {
@@ -53,11 +54,16 @@ jobs! {
     // Non default plugins
     add_attributes_to_svg_element: AddAttributesToSVGElement,
     add_classes_to_svg: AddClassesToSVG,
+    cleanup_gradients: CleanupGradients<E>,
     cleanup_list_of_values: CleanupListOfValues,
+    convert_stroke_to_fill: ConvertStrokeToFill,
+    convert_text_to_path: ConvertTextToPath,
+    embed_external_resources: EmbedExternalResources,
     prefix_ids: PrefixIds<E>,
     remove_attributes_by_selector: RemoveAttributesBySelector,
     remove_attrs: RemoveAttrs,
     remove_dimensions: RemoveDimensions,
+    remove_duplicate_defs: RemoveDuplicateDefs<E>,
     remove_elements_by_attr: RemoveElementsByAttr,
     remove_off_canvas_paths: RemoveOffCanvasPaths,
     remove_raster_images: RemoveRasterImages,
@@ -66,6 +72,7 @@ jobs! {
     remove_title: RemoveTitle,
     remove_view_box: RemoveViewBox,
     reuse_paths: ReusePaths<E>,
+    sanitize: Sanitize,
 
     // Default plugins
     remove_doctype: RemoveDoctype (is_default: true),
@@ -102,6 +109,10 @@ jobs! {
     sort_attrs: SortAttrs (is_default: true),
     sort_defs_children: SortDefsChildren (is_default: true),
     remove_desc: RemoveDesc (is_default: true),
+
+    // NOTE: `remove_xmlns` should be last, so that no other jobs add namespaces back
+    #[serde(rename = "removeXMLNS")]
+    remove_xmlns: RemoveXMLNS,
 }
 
 #[derive(Debug)]
@@ -122,12 +133,14 @@ impl std::error::Error for Error {}
 impl<E: Element> Jobs<E> {
     /// # Errors
     /// When any job fails for the first time
-    pub fn run(self, root: &E::ParentChild, info: &Info) -> Result<(), Error> {
+    pub fn run(&self, root: &E::ParentChild, info: &Info) -> Result<(), Error> {
         let Some(mut root_element) = <E as Element>::from_parent(root.clone()) else {
             log::warn!("No elements found in the document, skipping");
             return Ok(());
         };
 
+        // NOTE: Jobs collect state about the document they're run on while preparing and
+        // visiting it, so each run starts from a fresh copy of the configured jobs
         let mut jobs = self.clone();
         let count = jobs
             .run_jobs(&mut root_element, info)
@@ -135,6 +148,16 @@ impl<E: Element> Jobs<E> {
         log::debug!("completed {count} jobs");
         Ok(())
     }
+
+    /// Returns the options to serialize the optimised document with, such as writing it for HTML
+    /// when `removeXMLNS` has removed it's namespace declarations.
+    pub fn serialize_options(&self, options: serialize::Options) -> serialize::Options {
+        if self.remove_xmlns.as_ref().is_some_and(|job| job.0) {
+            options.inline(true)
+        } else {
+            options
+        }
+    }
 }
 
 #[cfg(test)]
@@ -154,21 +177,40 @@ pub(crate) fn test_config_default_svg_comment(
 }
 
 #[cfg(test)]
+/// Runs the jobs against the svg with each implementation, returning the serialized result
+/// when both implementations agree.
 pub(crate) fn test_config(config_json: &str, svg: Option<&str>) -> anyhow::Result<String> {
-    use oxvg_ast::{
-        implementations::markup5ever::{Element5Ever, Node5Ever},
-        parse::Node,
-        serialize,
+    use oxvg_ast::implementations::{
+        arena::{ElementArena, NodeArena},
+        markup5ever::{Element5Ever, Node5Ever},
     };
 
-    let jobs: Jobs<Element5Ever> = serde_json::from_str(config_json)?;
-    let dom: Node5Ever = Node::parse(svg.unwrap_or(
+    let svg = svg.unwrap_or(
         r#"<svg xmlns="http://www.w3.org/2000/svg">
     test
 </svg>"#,
-    ))?;
+    );
+    let result = test_config_with::<Node5Ever, Element5Ever>(config_json, svg)?;
+    let arena_result = test_config_with::<NodeArena, ElementArena>(config_json, svg)?;
+    anyhow::ensure!(
+        result == arena_result,
+        "the arena implementation differed from markup5ever:\n{arena_result}\n\nexpected:\n{result}"
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+fn test_config_with<N, E>(config_json: &str, svg: &str) -> anyhow::Result<String>
+where
+    N: oxvg_ast::parse::Node + oxvg_ast::serialize::Node,
+    E: Element<ParentChild = N>,
+    Jobs<E>: for<'de> Deserialize<'de>,
+{
+    let jobs: Jobs<E> = serde_json::from_str(config_json)?;
+    let dom = N::parse(svg)?;
+    let options = jobs.serialize_options(serialize::Options::new().pretty());
     jobs.run(&dom, &Info::default())?;
-    serialize::Node::serialize_with_options(&dom, serialize::Options::new().pretty())
+    serialize::Node::serialize_with_options(&dom, options)
 }
 
 #[test]
@@ -181,3 +223,44 @@ fn test_jobs() -> anyhow::Result<()> {
     )
     .map(|_| ())
 }
+
+#[test]
+fn test_jobs_parallel_subtrees() -> anyhow::Result<()> {
+    use oxvg_ast::{
+        implementations::arena::{ElementArena, NodeArena},
+        node::Node,
+        parse, serialize,
+    };
+
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <symbol id="a"><path d="M 10 10 L 20 20 L 30 30"/></symbol>
+    <symbol id="b"><path d="M 10 10 L 10 20 L 10 30"/></symbol>
+    <symbol id="c"><g><path d="M 0 0 L 20 0 Z"/></g></symbol>
+</svg>"#;
+    let jobs: Jobs<ElementArena> = serde_json::from_str(r#"{ "convertPathData": {} }"#)?;
+
+    let expected: NodeArena = parse::Node::parse(svg)?;
+    jobs.run(&expected, &Info::default())?;
+
+    let dom: NodeArena = parse::Node::parse(svg)?;
+    let root = ElementArena::from_parent(dom.clone()).unwrap();
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = root
+            .children()
+            .into_iter()
+            .map(|symbol| {
+                let jobs = &jobs;
+                scope.spawn(move || jobs.run(&symbol.as_parent_child(), &Info::default()))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().expect("job thread panicked"))
+    })?;
+
+    assert_eq!(
+        serialize::Node::serialize(&dom)?,
+        serialize::Node::serialize(&expected)?
+    );
+    Ok(())
+}
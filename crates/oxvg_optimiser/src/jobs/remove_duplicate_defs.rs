@@ -0,0 +1,277 @@
+use std::collections::{BTreeMap, HashMap};
+
+use derive_where::derive_where;
+use oxvg_ast::{
+    attribute::{Attr, Attributes},
+    element::Element,
+    name::Name,
+    node::{self, Node},
+    visitor::{Context, ContextFlags, PrepareOutcome, Visitor},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::find_references;
+
+#[derive_where(Default, Clone, Debug)]
+/// Removes structurally identical `<linearGradient>`, `<radialGradient>`, `<pattern>`,
+/// `<clipPath>`, `<mask>`, and `<filter>` elements, keeping the first of each and updating
+/// any references to the removed elements.
+///
+/// Elements are considered identical when their attributes, other than `id`, and their children
+/// are the same.
+///
+/// Documents with scripts or stylesheets are skipped, as references within them can't be
+/// reliably updated.
+pub struct RemoveDuplicateDefs<E: Element> {
+    enabled: bool,
+    defs: BTreeMap<String, Vec<E>>,
+    references: Vec<(E, <E::Attr as Attr>::Name)>,
+    depth: usize,
+}
+
+impl<E: Element> Visitor<E> for RemoveDuplicateDefs<E> {
+    type Error = String;
+
+    fn prepare(&mut self, document: &E, context_flags: &mut ContextFlags) -> PrepareOutcome {
+        if !self.enabled {
+            return PrepareOutcome::skip;
+        }
+        context_flags.query_has_script(document);
+        context_flags.query_has_stylesheet(document);
+        if context_flags.contains(ContextFlags::has_script_ref)
+            || context_flags.contains(ContextFlags::has_stylesheet)
+        {
+            log::debug!("RemoveDuplicateDefs::prepare: skipping document with script or style");
+            return PrepareOutcome::skip;
+        }
+        PrepareOutcome::none
+    }
+
+    fn element(&mut self, element: &mut E, _context: &mut Context<E>) -> Result<(), String> {
+        for attr in element.attributes().into_iter() {
+            if find_references(attr.local_name().as_ref(), attr.value().as_ref()).is_some() {
+                self.references.push((element.clone(), attr.name().clone()));
+            }
+        }
+
+        if !is_dedupable(element) {
+            return Ok(());
+        }
+        self.depth += 1;
+        if self.depth > 1 || !element.has_attribute_local(&"id".into()) {
+            return Ok(());
+        }
+
+        let mut key = String::new();
+        write_key(element, &mut key, true);
+        self.defs.entry(key).or_default().push(element.clone());
+        Ok(())
+    }
+
+    fn exit_element(&mut self, element: &mut E, _context: &mut Context<E>) -> Result<(), String> {
+        if is_dedupable(element) {
+            self.depth -= 1;
+        }
+        Ok(())
+    }
+
+    fn exit_document(&mut self, _document: &mut E, _context: &Context<E>) -> Result<(), String> {
+        let id_name = "id".into();
+        let mut renames = HashMap::new();
+        for list in self.defs.values() {
+            let Some((kept, duplicates)) = list.split_first() else {
+                continue;
+            };
+            let Some(kept_id) = kept.get_attribute_local(&id_name) else {
+                continue;
+            };
+            for duplicate in duplicates {
+                let Some(id) = duplicate.get_attribute_local(&id_name) else {
+                    continue;
+                };
+                log::debug!(
+                    "RemoveDuplicateDefs: replacing #{} with #{}",
+                    &*id,
+                    &*kept_id
+                );
+                renames.insert(id.to_string(), kept_id.to_string());
+                drop(id);
+                duplicate.remove();
+            }
+        }
+        if renames.is_empty() {
+            return Ok(());
+        }
+
+        for (element, name) in &self.references {
+            let Some(mut attr) = element.get_attribute_node_mut(name) else {
+                continue;
+            };
+            let value = attr.value().to_string();
+            let Some(matches) = find_references(name.local_name().as_ref(), &value) else {
+                continue;
+            };
+            let mut new_value = String::with_capacity(value.len());
+            let mut last_end = 0;
+            for id in matches.filter_map(|item| item.get(1)) {
+                let Some(rename) = renames.get(id.as_str()) else {
+                    continue;
+                };
+                new_value.push_str(&value[last_end..id.start()]);
+                new_value.push_str(rename);
+                last_end = id.end();
+            }
+            if last_end == 0 {
+                continue;
+            }
+            new_value.push_str(&value[last_end..]);
+            attr.set_value(new_value.into());
+        }
+        Ok(())
+    }
+}
+
+fn is_dedupable<E: Element>(element: &E) -> bool {
+    element.prefix().is_none()
+        && matches!(
+            element.local_name().as_ref(),
+            "linearGradient" | "radialGradient" | "pattern" | "clipPath" | "mask" | "filter"
+        )
+}
+
+/// Writes a representation of the element and it's descendants that can be compared with other
+/// elements
+fn write_key<E: Element>(element: &E, key: &mut String, is_root: bool) {
+    key.push('<');
+    key.push_str(&element.qual_name().formatter().to_string());
+    let mut attrs: Vec<_> = element
+        .attributes()
+        .into_iter()
+        .filter(|attr| !is_root || attr.prefix().is_some() || attr.local_name().as_ref() != "id")
+        .map(|attr| attr.formatter().to_string())
+        .collect();
+    attrs.sort();
+    for attr in attrs {
+        key.push(' ');
+        key.push_str(&attr);
+    }
+    key.push('>');
+    element.for_each_child(|child| match child.node_type() {
+        node::Type::Element => {
+            if let Some(child) = E::new(child) {
+                write_key(&child, key, false);
+            }
+        }
+        node::Type::Text | node::Type::CDataSection => {
+            if let Some(text) = child.text_content() {
+                key.push_str(text.trim());
+            }
+        }
+        _ => {}
+    });
+    key.push_str("</>");
+}
+
+impl<'de, E: Element> Deserialize<'de> for RemoveDuplicateDefs<E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let enabled = bool::deserialize(deserializer)?;
+        Ok(Self {
+            enabled,
+            ..Self::default()
+        })
+    }
+}
+
+impl<E: Element> Serialize for RemoveDuplicateDefs<E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.enabled.serialize(serializer)
+    }
+}
+
+#[test]
+#[allow(clippy::too_many_lines)]
+fn remove_duplicate_defs() -> anyhow::Result<()> {
+    use crate::test_config;
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeDuplicateDefs": true }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 100 100">
+    <!-- Remove identical gradients and update their references -->
+    <defs>
+        <linearGradient id="a" x1="0" y1="0" x2="1" y2="1">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        <linearGradient y2="1" x2="1" y1="0" x1="0" id="b">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        <linearGradient id="c" xlink:href="#b" gradientTransform="rotate(45)"/>
+    </defs>
+    <rect width="10" height="10" fill="url(#a)"/>
+    <rect width="10" height="10" fill="url(#b)" stroke="url('#b')"/>
+    <rect width="10" height="10" fill="url(#c)"/>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeDuplicateDefs": true }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- Only remove definitions with the same name, attributes, and children -->
+    <defs>
+        <radialGradient id="a">
+            <stop offset="0" stop-color="red"/>
+        </radialGradient>
+        <radialGradient id="b">
+            <stop offset="0" stop-color="blue"/>
+        </radialGradient>
+        <clipPath id="c">
+            <rect width="5" height="5"/>
+        </clipPath>
+        <clipPath id="d">
+            <rect width="5" height="5"/>
+        </clipPath>
+        <mask id="e">
+            <rect width="5" height="5"/>
+        </mask>
+    </defs>
+    <g clip-path="url(#d)" mask="url(#e)">
+        <rect width="10" height="10" fill="url(#a)"/>
+        <rect width="10" height="10" fill="url(#b)"/>
+    </g>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeDuplicateDefs": true }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- Skip documents with styles -->
+    <style>
+        .a { fill: url(#b) }
+    </style>
+    <defs>
+        <pattern id="a" width="2" height="2">
+            <rect width="1" height="1"/>
+        </pattern>
+        <pattern id="b" width="2" height="2">
+            <rect width="1" height="1"/>
+        </pattern>
+    </defs>
+    <rect class="a" width="10" height="10" fill="url(#a)"/>
+</svg>"#
+        ),
+    )?);
+
+    Ok(())
+}
@@ -385,15 +385,11 @@ impl<E: Element> RemoveHiddenElems<E> {
             let Some(d) = element.get_attribute_local(&"d".into()) else {
                 return true;
             };
-            return match Path::parse(d.as_ref()) {
-                Ok(d) => {
-                    d.0.is_empty()
-                        || (d.0.len() == 1
-                            && get_computed_styles!(MarkerStart).is_none()
-                            && get_computed_styles!(MarkerEnd).is_none())
-                }
-                Err(_) => true,
-            };
+            let (d, _) = Path::parse_partial(d.as_ref());
+            return d.0.is_empty()
+                || (d.0.len() == 1
+                    && get_computed_styles!(MarkerStart).is_none()
+                    && get_computed_styles!(MarkerEnd).is_none());
         }
         false
     }
@@ -736,5 +732,16 @@ fn remove_hidden_elems() -> anyhow::Result<()> {
         ),
     )?);
 
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeHiddenElems": {} }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <!-- preserve paths drawing commands before an error -->
+    <path d="M 10,50 L 20,30 L 30"/>
+    <path d="M 10,50 L 30"/>
+</svg>"#
+        ),
+    )?);
+
     Ok(())
 }
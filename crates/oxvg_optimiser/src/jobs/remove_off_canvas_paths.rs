@@ -2,7 +2,7 @@ use oxvg_ast::{
     element::Element,
     visitor::{Context, ContextFlags, PrepareOutcome, Visitor},
 };
-use oxvg_path::{command::Data, Path};
+use oxvg_path::{bounds::BoundingBox, command::Data, geometry::Point, Path};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Debug)]
@@ -69,16 +69,29 @@ impl<E: Element> Visitor<E> for RemoveOffCanvasPaths {
             return Ok(());
         }
 
-        if path.0.len() == 2 {
-            path.0.push(Data::ClosePath);
-        }
         let ViewBoxData {
             top,
+            right,
+            bottom,
             left,
             width,
             height,
-            ..
         } = view_box_data.clone();
+        let view_box = BoundingBox {
+            min: Point([left, top]),
+            max: Point([right, bottom]),
+        };
+        if path
+            .bounding_box()
+            .is_none_or(|bounds| !bounds.overlaps(&view_box))
+        {
+            element.remove();
+            return Ok(());
+        }
+
+        if path.0.len() == 2 {
+            path.0.push(Data::ClosePath);
+        }
         let view_box_path_data = Path(vec![
             Data::MoveTo([left, top]),
             Data::HorizontalLineBy([width]),
@@ -272,5 +285,18 @@ fn remove_off_canvas_paths() -> anyhow::Result<()> {
         ),
     )?);
 
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeOffCanvasPaths": true }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- control points within the viewbox, but curve outside -->
+    <path d="M-10 30C2 40 2 60-10 70"/>
+    <path d="M-10 30C20 40 20 60-10 70"/>
+    <path d="M-10-10A100 100 0 0 0-10 110"/>
+    <path d="M-10-10A100 100 0 0 1-10 110"/>
+</svg>"#
+        ),
+    )?);
+
     Ok(())
 }
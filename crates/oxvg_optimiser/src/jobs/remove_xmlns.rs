@@ -0,0 +1,93 @@
+use oxvg_ast::{
+    attribute::{Attr, Attributes},
+    element::Element,
+    visitor::{Context, ContextFlags, PrepareOutcome, Visitor},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+/// Removes the `xmlns` and `xmlns:xlink` namespace declarations from the root `<svg>` element.
+///
+/// This is useful for SVGs that are inlined into HTML, where the namespace is implied. The
+/// document should be serialized with [`crate::Jobs::serialize_options`], so the serializer
+/// doesn't declare the namespace again.
+///
+/// <div class="warning">The resulting document will not be rendered as SVG by a standalone
+/// XML parser</div>
+pub struct RemoveXMLNS(pub(crate) bool);
+
+impl<E: Element> Visitor<E> for RemoveXMLNS {
+    type Error = String;
+
+    fn prepare(&mut self, _document: &E, _context_flags: &mut ContextFlags) -> PrepareOutcome {
+        if self.0 {
+            PrepareOutcome::none
+        } else {
+            PrepareOutcome::skip
+        }
+    }
+
+    fn element(&mut self, element: &mut E, _context: &mut Context<E>) -> Result<(), Self::Error> {
+        if !element.is_root() || element.local_name().as_ref() != "svg" {
+            return Ok(());
+        }
+
+        element.attributes().retain(|attr| match attr.prefix() {
+            Some(prefix) => prefix.as_ref() != "xmlns" || attr.local_name().as_ref() != "xlink",
+            None => attr.local_name().as_ref() != "xmlns",
+        });
+        Ok(())
+    }
+}
+
+#[test]
+fn remove_xmlns() -> anyhow::Result<()> {
+    use crate::test_config;
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeXMLNS": true }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <g>
+        <circle cx="50" cy="50" r="50"/>
+    </g>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeXMLNS": true }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 100 100">
+    <defs>
+        <path id="a" d="M0 0h10v10z"/>
+    </defs>
+    <use xlink:href="#a"/>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeXMLNS": true }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <foreignObject>
+        <div xmlns="http://www.w3.org/1999/xhtml">hello, world</div>
+    </foreignObject>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "removeXMLNS": true }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+    <!-- Only removes declarations from the root element -->
+    <svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"/>
+</svg>"#
+        ),
+    )?);
+
+    Ok(())
+}
@@ -0,0 +1,417 @@
+use lightningcss::{
+    printer::PrinterOptions,
+    rules::CssRule,
+    stylesheet::{ParserOptions, StyleAttribute, StyleSheet},
+    visit_types,
+    visitor::Visit,
+};
+use oxvg_ast::{
+    attribute::{Attr, Attributes},
+    element::Element,
+    name::Name,
+    node::Node,
+    visitor::{Context, Visitor},
+};
+use oxvg_collections::collections::{ANIMATION, EVENT_ATTRS, REFERENCES_PROPS};
+use serde::{Deserialize, Serialize};
+
+use super::inline_styles;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+/// Removes content that can run scripts or load external resources, for documents from
+/// untrusted sources.
+///
+/// This goes beyond `removeScripts` by removing
+/// - `<script>`, `<foreignObject>`, and embedded documents such as `<iframe>`, in any namespace
+/// - event attributes, such as `onclick`
+/// - `javascript:` and `data:text/html` references, and animations that set references
+/// - references, `@import` rules, and `url()`s in styles or presentation attributes to hosts not
+///   in `allowedHosts`, along with any styles that can't be parsed
+/// - the document type, along with any entities it declares
+/// - `xml-stylesheet` processing instructions
+pub struct Sanitize {
+    /// The hosts external references may point to, where `*.example.com` will match any
+    /// subdomain of `example.com`
+    #[serde(default = "Vec::new")]
+    allowed_hosts: Vec<String>,
+}
+
+const XLINK: &str = "http://www.w3.org/1999/xlink";
+
+struct CssVisitor<'a> {
+    job: &'a Sanitize,
+    changed: bool,
+}
+
+impl<E: Element> Visitor<E> for Sanitize {
+    type Error = String;
+
+    fn doctype(&mut self, doctype: &mut <E as Node>::Child) -> Result<(), Self::Error> {
+        doctype.remove();
+        Ok(())
+    }
+
+    fn processing_instruction(
+        &mut self,
+        processing_instruction: &mut <E as Node>::Child,
+        _context: &Context<E>,
+    ) -> Result<(), Self::Error> {
+        if processing_instruction
+            .processing_instruction()
+            .is_some_and(|(target, _)| target.as_ref() == "xml-stylesheet")
+        {
+            processing_instruction.remove();
+        }
+        Ok(())
+    }
+
+    fn element(&mut self, element: &mut E, _context: &mut Context<E>) -> Result<(), String> {
+        // NOTE: Names are matched in every namespace, since an html `<script>` within an svg runs
+        // just the same, and an element in the svg namespace may use any prefix
+        let name = element.local_name();
+        let name = name.as_ref();
+        if matches!(
+            name,
+            "script" | "foreignObject" | "iframe" | "frame" | "object" | "embed"
+        ) || (ANIMATION.contains(name) && Self::is_reference_animation(element))
+        {
+            element.remove();
+            return Ok(());
+        }
+        if name == "style" {
+            self.style_content(element);
+        }
+
+        element.attributes().retain(|attr| {
+            let name = attr.name();
+            let ns = name.ns().as_ref();
+            if ns.is_empty() && EVENT_ATTRS.contains(name.local_name().as_ref()) {
+                return false;
+            }
+            let local_name = name.local_name().as_ref();
+            if local_name == "href" && (ns.is_empty() || ns == XLINK) {
+                return self.is_allowed_url(attr.value());
+            }
+            if ns.is_empty() && local_name != "style" && REFERENCES_PROPS.contains(local_name) {
+                return self.is_allowed_presentation_attr(local_name, attr.value());
+            }
+            true
+        });
+        self.style_attr(element);
+        Ok(())
+    }
+}
+
+impl Sanitize {
+    /// Whether the animation targets a reference, such as `<set attributeName="href" />`
+    fn is_reference_animation<E: Element>(element: &E) -> bool {
+        element
+            .get_attribute_local(&"attributeName".into())
+            .is_some_and(|name| {
+                let name: &str = name.as_ref();
+                name.trim()
+                    .rsplit(':')
+                    .next()
+                    .is_some_and(|name| name == "href")
+            })
+    }
+
+    /// Whether the url is a fragment, a relative path, a `data:` url that isn't html, or points
+    /// to one of the allowed hosts.
+    fn is_allowed_url(&self, url: &str) -> bool {
+        // NOTE: Browsers ignore whitespace and control characters within a url's scheme
+        let url: String = url
+            .chars()
+            .filter(|c| !c.is_whitespace() && !c.is_control())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if url.starts_with('#') {
+            return true;
+        }
+        if let Some(data) = url.strip_prefix("data:") {
+            return !data.starts_with("text/html");
+        }
+
+        let rest = if let Some(rest) = url.strip_prefix("//") {
+            rest
+        } else {
+            match url.split_once(':') {
+                Some((scheme, rest))
+                    if scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && scheme
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) =>
+                {
+                    if scheme != "http" && scheme != "https" {
+                        return false;
+                    }
+                    let Some(rest) = rest.strip_prefix("//") else {
+                        return false;
+                    };
+                    rest
+                }
+                _ => return true,
+            }
+        };
+
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let host = match host.rsplit_once(':') {
+            Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+            _ => host,
+        };
+        self.allowed_hosts.iter().any(|allowed| {
+            let allowed = allowed.to_ascii_lowercase();
+            match allowed.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|subdomain| subdomain.ends_with('.')),
+                None => host == allowed,
+            }
+        })
+    }
+
+    /// Whether each `url()` in the value of a presentation attribute, such as `fill`, is allowed.
+    /// A value that can't be parsed isn't allowed.
+    fn is_allowed_presentation_attr(&self, name: &str, value: &str) -> bool {
+        let declaration = format!("{name}:{value}");
+        let Ok(mut css) = StyleAttribute::parse(&declaration, ParserOptions::default()) else {
+            log::debug!("removing {name}: failed to parse value");
+            return false;
+        };
+        let mut visitor = CssVisitor {
+            job: self,
+            changed: false,
+        };
+        let _ = css.visit(&mut visitor);
+        !visitor.changed
+    }
+
+    fn style_content<E: Element>(&self, element: &E) {
+        let Some(css) = element.text_content() else {
+            return;
+        };
+        let Ok(mut css) = StyleSheet::parse(&css, ParserOptions::default()) else {
+            log::debug!("removing style: failed to parse stylesheet");
+            element.remove();
+            return;
+        };
+
+        let len = css.rules.0.len();
+        css.rules.0.retain(|rule| match rule {
+            CssRule::Import(import) => self.is_allowed_url(&import.url),
+            _ => true,
+        });
+        let mut visitor = CssVisitor {
+            job: self,
+            changed: len != css.rules.0.len(),
+        };
+        let _ = css.visit(&mut visitor);
+        if !visitor.changed {
+            return;
+        }
+
+        let Ok(css) = css.to_css(PrinterOptions {
+            minify: true,
+            ..PrinterOptions::default()
+        }) else {
+            element.remove();
+            return;
+        };
+        inline_styles::set_style_content(element, css.code.into());
+    }
+
+    fn style_attr<E: Element>(&self, element: &E) {
+        let style_name = "style".into();
+        let Some(mut style) = element.get_attribute_node_local_mut(&style_name) else {
+            return;
+        };
+        let Ok(mut css_source) =
+            StyleAttribute::parse(style.value().as_ref(), ParserOptions::default())
+        else {
+            log::debug!("removing style: failed to parse style attribute");
+            drop(style);
+            element.remove_attribute_local(&style_name);
+            return;
+        };
+        let mut visitor = CssVisitor {
+            job: self,
+            changed: false,
+        };
+        let _ = css_source.visit(&mut visitor);
+        if !visitor.changed {
+            return;
+        }
+
+        let Ok(css) = css_source.to_css(PrinterOptions {
+            minify: true,
+            ..PrinterOptions::default()
+        }) else {
+            drop(css_source);
+            drop(style);
+            element.remove_attribute_local(&style_name);
+            return;
+        };
+        let css_atom = css.code.into();
+        drop(css_source);
+        style.set_value(css_atom);
+    }
+}
+
+impl<'i> lightningcss::visitor::Visitor<'i> for CssVisitor<'_> {
+    type Error = ();
+
+    fn visit_types(&self) -> lightningcss::visitor::VisitTypes {
+        visit_types!(URLS)
+    }
+
+    fn visit_url(
+        &mut self,
+        url: &mut lightningcss::values::url::Url<'i>,
+    ) -> Result<(), Self::Error> {
+        if !self.job.is_allowed_url(&url.url) {
+            url.url = "#".into();
+            self.changed = true;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+#[allow(clippy::too_many_lines)]
+fn sanitize() -> anyhow::Result<()> {
+    use crate::test_config;
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "sanitize": {} }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" onload="alert(1)">
+    <script>alert(1)</script>
+    <foreignObject width="100" height="100">
+        <iframe xmlns="http://www.w3.org/1999/xhtml" src="https://example.com"/>
+    </foreignObject>
+    <g onclick="alert(1)" onmouseover="alert(1)" fill="red">
+        <rect width="10" height="10"/>
+    </g>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "sanitize": {} }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+    <!-- unsafe references are removed -->
+    <a href="javascript:alert(1)"><text>1</text></a>
+    <a xlink:href=" JaVa&#x09;Script:alert(1)"><text>2</text></a>
+    <a href="data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg=="><text>3</text></a>
+    <image href="https://evil.com/tracker.png"/>
+    <image href="//evil.com/tracker.png"/>
+    <image href="ftp://example.com/image.png"/>
+    <!-- safe references are kept -->
+    <use href="#a"/>
+    <image href="image.png"/>
+    <image href="data:image/png;base64,..."/>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "sanitize": { "allowedHosts": ["example.com", "*.cdn.com"] } }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <!-- allowed hosts -->
+    <image href="https://example.com/image.png"/>
+    <image href="https://user@example.com:8080/image.png"/>
+    <image href="https://images.cdn.com/image.png"/>
+    <!-- other hosts -->
+    <image href="https://example.com.evil.com/image.png"/>
+    <image href="https://cdn.com/image.png"/>
+    <image href="https://evilcdn.com/image.png"/>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "sanitize": { "allowedHosts": ["example.com"] } }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <style>
+        @import url("https://evil.com/style.css");
+        @import "https://example.com/style.css";
+        .a { fill: url(#gradient); background: url(https://evil.com/image.png); }
+    </style>
+    <style>.b { fill: url(#gradient) }</style>
+    <rect style="fill: url(#gradient); background: url(https://evil.com/image.png)"/>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "sanitize": {} }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+    <a href="#a">
+        <set attributeName="href" to="javascript:alert(1)"/>
+        <animate attributeName="xlink:href" values="javascript:alert(1)"/>
+        <animate attributeName="opacity" values="0;1"/>
+        <text>1</text>
+    </a>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "sanitize": {} }"#,
+        Some(
+            r#"<?xml version="1.0"?>
+<?xml-stylesheet href="https://evil.com/style.css"?>
+<!DOCTYPE svg [
+    <!ENTITY a "alert(1)">
+]>
+<svg xmlns="http://www.w3.org/2000/svg">
+    <text>&a;</text>
+</svg>"#
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "sanitize": {} }"#,
+        Some(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:s="http://www.w3.org/2000/svg" xmlns:h="http://www.w3.org/1999/xhtml" xmlns:x="http://www.w3.org/1999/xlink">
+    <!-- prefixed elements and attributes are matched by their namespace -->
+    <s:script>alert(1)</s:script>
+    <h:script>alert(2)</h:script>
+    <h:iframe src="javascript:alert(3)"/>
+    <h:object data="https://evil.com/object.swf"/>
+    <h:embed src="https://evil.com/embed.swf"/>
+    <a x:href="javascript:alert(4)"><text>1</text></a>
+    <a href="#a">
+        <s:set attributeName="href" to="javascript:alert(5)"/>
+        <text>2</text>
+    </a>
+</svg>"##
+        ),
+    )?);
+
+    insta::assert_snapshot!(test_config(
+        r#"{ "sanitize": { "allowedHosts": ["example.com"] } }"#,
+        Some(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <!-- external urls in presentation attributes are checked -->
+    <rect fill="url(https://evil.com/x.svg#a)" stroke="url(https://evil.com/x.svg#a)"/>
+    <rect filter="url(https://evil.com/x.svg#a)" mask="url(https://evil.com/x.svg#a)"/>
+    <rect clip-path="url(https://evil.com/x.svg#a)"/>
+    <path marker-start="url(https://evil.com/x.svg#a)" marker-mid="url(https://evil.com/x.svg#a)" marker-end="url(https://evil.com/x.svg#a)"/>
+    <rect fill="url('https://example.com/x.svg#a') red" stroke="url(#a)" filter="none"/>
+    <!-- styles that can't be parsed are removed -->
+    <rect style="fill: url(https://evil.com/x.svg#a) }" fill="red"/>
+</svg>"#
+        ),
+    )?);
+
+    Ok(())
+}
@@ -76,7 +76,6 @@ fn sort_attrs() -> anyhow::Result<()> {
         ),
     )?);
 
-    // FIXME: rcdom is breaking this
     insta::assert_snapshot!(test_config(
         r#"{ "sortAttrs": {} }"#,
         Some(
@@ -87,7 +86,6 @@ fn sort_attrs() -> anyhow::Result<()> {
         ),
     )?);
 
-    // FIXME: rcdom is breaking this
     insta::assert_snapshot!(test_config(
         r#"{ "sortAttrs": { "xmlnsOrder": "alphabetical" } }"#,
         Some(
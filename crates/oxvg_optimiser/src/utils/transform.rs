@@ -230,6 +230,28 @@ impl Transform {
 }
 
 impl Matrix {
+    /// Maps the point through the matrix
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let m = &self.0;
+        (m.a * x + m.c * y + m.e, m.b * x + m.d * y + m.f)
+    }
+
+    /// Returns the scale factor of the matrix when it's only made up of translations,
+    /// rotations, reflections, and uniform scaling; i.e. when circles remain circles.
+    pub fn similarity_scale(&self) -> Option<f32> {
+        let m = &self.0;
+        let is_rotation = (m.a - m.d).abs() < f32::EPSILON && (m.b + m.c).abs() < f32::EPSILON;
+        let is_reflection = (m.a + m.d).abs() < f32::EPSILON && (m.b - m.c).abs() < f32::EPSILON;
+        if !is_rotation && !is_reflection {
+            return None;
+        }
+        let scale = m.a.hypot(m.b);
+        if scale == 0.0 {
+            return None;
+        }
+        Some(scale)
+    }
+
     pub fn to_transform(&self, precision: &Precision) -> Vec<Transform> {
         let decomposed = self.get_compositions();
 
@@ -0,0 +1,353 @@
+//! Boolean operations between the filled areas of paths.
+//!
+//! Use [`Path::boolean`] or one of it's shorthands, such as [`Path::union`], to get a path
+//! covering the area of the operation.
+//!
+//! The process of combining paths is as follows:
+//! 1. Convert both paths to closed subpaths of absolute lines and cubic beziers
+//! 2. Split each segment wherever it meets another segment
+//! 3. Keep the segments with the result of the operation on only one side of them, using the
+//!    winding of points either side of the segment
+//! 4. Orient the kept segments so that the result is to their left, and join them into subpaths
+use std::f64::consts::FRAC_PI_2;
+
+use crate::{
+    command::Data,
+    geometry::Point,
+    segment::{closed_segments, normal, Segment, EPSILON},
+    Path,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// The rule for which points are inside a path, as with the `fill-rule` property
+pub enum FillRule {
+    #[default]
+    /// A point is inside when the subpaths around it don't cancel each other out
+    NonZero,
+    /// A point is inside when it's surrounded by an odd number of subpaths
+    EvenOdd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The operation to combine the area of two paths with
+pub enum Operation {
+    /// The area covered by either path
+    Union,
+    /// The area covered by both paths
+    Intersection,
+    /// The area covered by the first path and not the second
+    Difference,
+    /// The area covered by only one of the paths
+    Xor,
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            Self::NonZero => winding != 0,
+            Self::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+impl Operation {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Self::Union => a || b,
+            Self::Intersection => a && b,
+            Self::Difference => a && !b,
+            Self::Xor => a != b,
+        }
+    }
+}
+
+impl Path {
+    /// Returns a path covering the area of the operation between the filled areas of both
+    /// paths, where each subpath is implicitly closed.
+    ///
+    /// The resulting subpaths don't overlap and holes are drawn in the opposite direction to
+    /// their surroundings, so the result can be filled with either fill-rule. Arcs and
+    /// quadratic beziers are converted to cubic beziers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::{boolean::{FillRule, Operation}, Path};
+    ///
+    /// let a = Path::parse("M 0 0 H 10 V 10 H 0 Z").unwrap();
+    /// let b = Path::parse("M 5 5 H 15 V 15 H 5 Z").unwrap();
+    ///
+    /// let path = a.boolean(&b, Operation::Union, FillRule::NonZero);
+    /// assert_eq!(&path.to_string(), "M0 0L10 0L10 5L15 5L15 15L5 15L5 10L0 10Z");
+    /// ```
+    #[must_use]
+    pub fn boolean(&self, other: &Self, operation: Operation, fill_rule: FillRule) -> Self {
+        let a = closed_segments(self, FRAC_PI_2);
+        let b = closed_segments(other, FRAC_PI_2);
+        let all: Vec<Segment> = a.iter().chain(b.iter()).copied().collect();
+        let Some(scale) = all
+            .iter()
+            .map(|segment| {
+                let (min, max) = segment.bounds();
+                [min.0, max.0]
+                    .into_iter()
+                    .flatten()
+                    .fold(0.0_f64, |acc, n| acc.max(n.abs()))
+            })
+            .reduce(f64::max)
+        else {
+            return Self(vec![]);
+        };
+        let scale = scale.max(1.0);
+        let tolerance = scale * 1e-9;
+        let offset = scale * 1e-6;
+
+        let pieces = split(&all, tolerance);
+        let is_inside = |point: Point| {
+            let winding = |segments: &[Segment]| -> i32 {
+                segments.iter().map(|segment| segment.winding(point)).sum()
+            };
+            operation.apply(
+                fill_rule.is_inside(winding(&a)),
+                fill_rule.is_inside(winding(&b)),
+            )
+        };
+
+        let mut boundary: Vec<Segment> = vec![];
+        for piece in pieces {
+            let Some(tangent) = piece.tangent(0.5) else {
+                continue;
+            };
+            let mid = piece.point(0.5);
+            let side = normal(tangent).scale(offset);
+            let (left, right) = (is_inside(mid.add(side)), is_inside(mid.sub(side)));
+            if left == right {
+                continue;
+            }
+            let piece = if left { piece } else { piece.reverse() };
+            let is_duplicate = boundary.iter().any(|other| {
+                other.start().sub(piece.start()).length() <= offset
+                    && other.end().sub(piece.end()).length() <= offset
+                    && other.point(0.5).sub(piece.point(0.5)).length() <= offset
+            });
+            if !is_duplicate {
+                boundary.push(piece);
+            }
+        }
+        Self(join(boundary, offset))
+    }
+
+    /// Returns a path covering the area of either path, using the `nonzero` fill-rule.
+    ///
+    /// See [`Path::boolean`]
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        self.boolean(other, Operation::Union, FillRule::NonZero)
+    }
+
+    /// Returns a path covering the area of both paths, using the `nonzero` fill-rule.
+    ///
+    /// See [`Path::boolean`]
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.boolean(other, Operation::Intersection, FillRule::NonZero)
+    }
+
+    /// Returns a path covering the area of this path without the other, using the `nonzero`
+    /// fill-rule.
+    ///
+    /// See [`Path::boolean`]
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.boolean(other, Operation::Difference, FillRule::NonZero)
+    }
+
+    /// Returns a path covering the area of only one of the paths, using the `nonzero`
+    /// fill-rule.
+    ///
+    /// See [`Path::boolean`]
+    #[must_use]
+    pub fn xor(&self, other: &Self) -> Self {
+        self.boolean(other, Operation::Xor, FillRule::NonZero)
+    }
+}
+
+/// Splits the segments wherever they meet another segment
+fn split(segments: &[Segment], tolerance: f64) -> Vec<Segment> {
+    let is_interior = |t: f64| t > 1e-9 && t < 1.0 - 1e-9;
+    let end_point = |segment: &Segment, t: f64| {
+        if t < 0.5 {
+            segment.start()
+        } else {
+            segment.end()
+        }
+    };
+    let mut splits: Vec<Vec<(f64, Point)>> = vec![vec![]; segments.len()];
+    for (i, a) in segments.iter().enumerate() {
+        for (j, b) in segments.iter().enumerate().skip(i + 1) {
+            for (t, u) in a.intersections(b, tolerance) {
+                // NOTE: Snap to existing end-points, so the split segments can be joined
+                let point = match (is_interior(t), is_interior(u)) {
+                    (false, false) => continue,
+                    (false, true) => end_point(a, t),
+                    (true, false) => end_point(b, u),
+                    (true, true) => a.point(t),
+                };
+                if is_interior(t) {
+                    splits[i].push((t, point));
+                }
+                if is_interior(u) {
+                    splits[j].push((u, point));
+                }
+            }
+        }
+    }
+
+    let mut result = vec![];
+    for (segment, mut splits) in segments.iter().zip(splits) {
+        splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut rest = *segment;
+        let mut from = 0.0;
+        let mut start = segment.start();
+        for (t, point) in splits {
+            if t - from <= 1e-9 {
+                continue;
+            }
+            let (piece, next) = rest.split((t - from) / (1.0 - from));
+            result.push(piece.with_end_points(start, point));
+            rest = next;
+            from = t;
+            start = point;
+        }
+        result.push(rest.with_end_points(start, segment.end()));
+    }
+    result
+}
+
+/// Joins the oriented segments into closed subpaths, merging consecutive lines in the same
+/// direction
+fn join(mut segments: Vec<Segment>, tolerance: f64) -> Vec<Data> {
+    let mut data = vec![];
+    while !segments.is_empty() {
+        let first = segments.remove(0);
+        let start = first.start();
+        let mut current = first;
+        let mut line: Option<Segment> = None;
+        data.push(Data::MoveTo(start.0));
+        loop {
+            let end = current.end();
+            let is_closed = end.sub(start).length() <= tolerance;
+            match current {
+                Segment::Line(_) if is_closed => {}
+                Segment::Line([from, to]) => match line {
+                    Some(Segment::Line([line_from, line_to]))
+                        if is_same_direction(line_to.sub(line_from), to.sub(from)) =>
+                    {
+                        data.pop();
+                        data.push(Data::LineTo(end.0));
+                        line = Some(Segment::Line([line_from, to]));
+                    }
+                    _ => {
+                        data.push(Data::LineTo(end.0));
+                        line = Some(current);
+                    }
+                },
+                Segment::Cubic([_, b, c, d]) => {
+                    line = None;
+                    let d = if is_closed { start } else { d };
+                    data.push(Data::CubicBezierTo([
+                        b.0[0], b.0[1], c.0[0], c.0[1], d.0[0], d.0[1],
+                    ]));
+                }
+            }
+            if is_closed {
+                break;
+            }
+            let Some(index) = segments
+                .iter()
+                .position(|segment| segment.start().sub(end).length() <= tolerance)
+            else {
+                break;
+            };
+            current = segments.remove(index);
+        }
+        data.push(Data::ClosePath);
+    }
+    data
+}
+
+fn is_same_direction(a: Point, b: Point) -> bool {
+    let cross = a.0[0] * b.0[1] - a.0[1] * b.0[0];
+    a.dot(&b) > 0.0 && cross.abs() <= EPSILON * a.length() * b.length()
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_boolean() {
+    use crate::math::to_fixed;
+
+    let path = |definition: &str| Path::parse(definition).unwrap();
+    let area = |path: &Path| to_fixed(path.area(), 3);
+    let a = path("M 0 0 H 10 V 10 H 0 Z");
+    let b = path("M 5 5 H 15 V 15 H 5 Z");
+
+    // Should combine lines
+    assert_eq!(
+        &a.union(&b).to_string(),
+        "M0 0L10 0L10 5L15 5L15 15L5 15L5 10L0 10Z"
+    );
+    assert_eq!(&a.intersection(&b).to_string(), "M10 5L10 10L5 10L5 5Z");
+    assert_eq!(
+        &a.difference(&b).to_string(),
+        "M0 0L10 0L10 5L5 5L5 10L0 10Z"
+    );
+    assert_eq!(area(&a.xor(&b)), 150.0);
+
+    // Should combine curves, where arcs are approximated by cubic beziers
+    let area = |path: &Path| to_fixed(path.area(), 1);
+    let circle = path("M 15 10 A 5 5 0 0 1 5 10 A 5 5 0 0 1 15 10 Z");
+    let circle_area = circle.area();
+    assert_eq!(
+        area(&a.union(&circle)),
+        to_fixed(100.0 + circle_area * 0.75, 1)
+    );
+    assert_eq!(
+        area(&a.intersection(&circle)),
+        to_fixed(circle_area / 4.0, 1)
+    );
+    assert_eq!(
+        area(&circle.difference(&a)),
+        to_fixed(circle_area * 0.75, 1)
+    );
+
+    // Should handle holes, which are counter-clockwise in the result
+    let ring = path("M 0 0 H 10 V 10 H 0 Z M 2 2 V 8 H 8 V 2 Z");
+    assert_eq!(area(&ring.union(&path("M 3 3 H 4 V 4 H 3 Z"))), 65.0);
+    assert_eq!(
+        ring.union(&path("M 100 100 H 101 V 101 H 100 Z"))
+            .subpath_areas(),
+        vec![100.0, -36.0, 1.0]
+    );
+
+    // Should follow the fill-rule
+    let overlapping = path("M 0 0 H 10 V 10 H 0 Z M 2 2 H 8 V 8 H 2 Z");
+    let empty = Path(vec![]);
+    assert_eq!(
+        area(&overlapping.boolean(&empty, Operation::Union, FillRule::NonZero)),
+        100.0
+    );
+    assert_eq!(
+        area(&overlapping.boolean(&empty, Operation::Union, FillRule::EvenOdd)),
+        64.0
+    );
+
+    // Should merge shared edges
+    assert_eq!(
+        &a.union(&path("M 10 0 H 20 V 10 H 10 Z")).to_string(),
+        "M0 0L20 0L20 10L0 10Z"
+    );
+    assert!(a
+        .intersection(&path("M 20 0 H 30 V 10 H 20 Z"))
+        .0
+        .is_empty());
+}
@@ -0,0 +1,238 @@
+//! Bounding boxes of paths.
+//!
+//! Unlike [`Points`](crate::points::Points), which covers the control points of each command,
+//! the bounds of curves and arcs are found by solving for their extrema, giving the tightest
+//! box around the path's geometry.
+use std::f64::consts::PI;
+
+use crate::{
+    geometry::{Arc, Point},
+    math::quadratic_roots,
+    segment::{absolute, Absolute},
+    stroke, Path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// An axis-aligned rectangle containing some geometry
+pub struct BoundingBox {
+    /// The top-left corner of the box
+    pub min: Point,
+    /// The bottom-right corner of the box
+    pub max: Point,
+}
+
+impl BoundingBox {
+    /// Creates an empty box around a single point
+    pub fn new(point: Point) -> Self {
+        Self {
+            min: point,
+            max: point,
+        }
+    }
+
+    /// Returns the bounds of the path's geometry, or `None` if nothing is drawn.
+    ///
+    /// A move command only contributes to the bounds when it's followed by another command.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::{bounds::BoundingBox, Path};
+    ///
+    /// let path = Path::parse("M 0 0 C 0 10 10 10 10 0").unwrap();
+    /// let bounds = BoundingBox::from_path(&path).unwrap();
+    /// assert_eq!(bounds.min.0, [0.0, 0.0]);
+    /// assert_eq!(bounds.max.0, [10.0, 7.5]);
+    /// ```
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let mut bounds: Option<Self> = None;
+        let mut include = |point: Point| match bounds.as_mut() {
+            Some(bounds) => bounds.include(point),
+            None => bounds = Some(Self::new(point)),
+        };
+        // NOTE: A move is only included once something is drawn from it
+        let mut moved = None;
+
+        for command in absolute(path) {
+            if let Absolute::Move(to) = command {
+                moved = Some(to);
+                continue;
+            }
+            if let Some(start) = moved.take() {
+                include(start);
+            }
+            match command {
+                Absolute::Line([_, to]) => include(to),
+                Absolute::Cubic(points) => cubic_extrema(points, &mut include),
+                Absolute::Arc(from, args, to) => {
+                    if let Some(arc) = Arc::from_endpoints(from, args, to) {
+                        arc_extrema(&arc, &mut include);
+                    }
+                    include(to);
+                }
+                Absolute::Move(_) | Absolute::Close(_) => {}
+            }
+        }
+        bounds
+    }
+
+    /// Returns the bounds of the area painted by the path when both filled and stroked, or
+    /// `None` if nothing is drawn.
+    ///
+    /// The stroke's bounds follow the outline from [`stroke::outline`], and so are accurate to
+    /// the options' tolerance, and account for the line-joins, line-caps, and dashes used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::{bounds::BoundingBox, stroke::{LineCap, Options}, Path};
+    ///
+    /// let path = Path::parse("M 0 0 H 10").unwrap();
+    /// let options = Options { width: 2.0, line_cap: LineCap::Square, ..Options::default() };
+    /// let bounds = BoundingBox::from_stroked_path(&path, &options).unwrap();
+    /// assert_eq!(bounds.min.0, [-1.0, -1.0]);
+    /// assert_eq!(bounds.max.0, [11.0, 1.0]);
+    /// ```
+    pub fn from_stroked_path(path: &Path, options: &stroke::Options) -> Option<Self> {
+        let fill = Self::from_path(path);
+        let stroke = Self::from_path(&stroke::outline(path, options));
+        match (fill, stroke) {
+            (Some(fill), Some(stroke)) => Some(fill.union(&stroke)),
+            (fill, stroke) => fill.or(stroke),
+        }
+    }
+
+    /// Returns the width of the box
+    pub fn width(&self) -> f64 {
+        self.max.0[0] - self.min.0[0]
+    }
+
+    /// Returns the height of the box
+    pub fn height(&self) -> f64 {
+        self.max.0[1] - self.min.0[1]
+    }
+
+    /// Extends the box to contain the point
+    pub fn include(&mut self, Point([x, y]): Point) {
+        self.min.0[0] = self.min.0[0].min(x);
+        self.min.0[1] = self.min.0[1].min(y);
+        self.max.0[0] = self.max.0[0].max(x);
+        self.max.0[1] = self.max.0[1].max(y);
+    }
+
+    /// Returns the smallest box containing both boxes
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = *self;
+        result.include(other.min);
+        result.include(other.max);
+        result
+    }
+
+    /// Returns whether the boxes overlap, where boxes touching at an edge don't overlap
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.min.0[0] < other.max.0[0]
+            && other.min.0[0] < self.max.0[0]
+            && self.min.0[1] < other.max.0[1]
+            && other.min.0[1] < self.max.0[1]
+    }
+}
+
+/// Includes the end-point and extrema of a cubic bezier
+fn cubic_extrema([p0, p1, p2, p3]: [Point; 4], include: &mut impl FnMut(Point)) {
+    include(p3);
+    for axis in 0..2 {
+        let [a, b, c, d] = [p0.0[axis], p1.0[axis], p2.0[axis], p3.0[axis]];
+        // NOTE: The derivative, divided by 3, is a quadratic of `t`
+        let qa = -a + 3.0 * b - 3.0 * c + d;
+        let qb = 2.0 * (a - 2.0 * b + c);
+        let qc = b - a;
+        for t in quadratic_roots(qa, qb, qc) {
+            let mt = 1.0 - t;
+            include(
+                p0.scale(mt * mt * mt)
+                    .add(p1.scale(3.0 * mt * mt * t))
+                    .add(p2.scale(3.0 * mt * t * t))
+                    .add(p3.scale(t * t * t)),
+            );
+        }
+    }
+}
+
+/// Includes the extrema of an arc, excluding it's end-points
+fn arc_extrema(arc: &Arc, include: &mut impl FnMut(Point)) {
+    let [rx, ry] = arc.radii;
+    let (sin, cos) = arc.rotation.sin_cos();
+    // NOTE: Angles where the derivative of the ellipse is zero along the x or y axis
+    let x_angle = f64::atan2(-ry * sin, rx * cos);
+    let y_angle = f64::atan2(ry * cos, rx * sin);
+    for angle in [x_angle, x_angle + PI, y_angle, y_angle + PI] {
+        if arc.contains_angle(angle) {
+            include(arc.point_at(angle));
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_bounding_box() {
+    let bounds = |definition: &str| {
+        let bounds = BoundingBox::from_path(&Path::parse(definition).unwrap()).unwrap();
+        [bounds.min.0, bounds.max.0].map(|point| point.map(|n| crate::math::to_fixed(n, 6)))
+    };
+
+    // Should cover lines
+    assert_eq!(bounds("M 10 10 H 20 V 5 Z"), [[10.0, 5.0], [20.0, 10.0]]);
+    // Should follow relative and smooth commands
+    assert_eq!(
+        bounds("m 10 10 c 0 10 10 10 10 0 s 10 -10 10 0"),
+        [[10.0, 2.5], [30.0, 17.5]]
+    );
+    // Should solve quadratic extrema
+    assert_eq!(
+        bounds("M 0 0 Q 5 10 10 0 T 20 0"),
+        [[0.0, -5.0], [20.0, 5.0]]
+    );
+    // Should solve arc extrema
+    assert_eq!(bounds("M 0 0 A 5 5 0 0 1 10 0"), [[0.0, -5.0], [10.0, 0.0]]);
+    assert_eq!(bounds("M 0 0 A 5 5 0 0 0 10 0"), [[0.0, 0.0], [10.0, 5.0]]);
+    assert_eq!(bounds("M 0 0 A 5 5 0 1 1 0 10"), [[0.0, 0.0], [5.0, 10.0]]);
+    // Should solve rotated and scaled arcs
+    assert_eq!(
+        bounds("M -10 0 A 10 5 90 0 1 10 0"),
+        [[-10.0, -20.0], [10.0, 0.0]]
+    );
+    // Should ignore trailing moves
+    assert_eq!(bounds("M 0 0 L 1 1 M 10 10"), [[0.0, 0.0], [1.0, 1.0]]);
+    assert!(BoundingBox::from_path(&Path::parse("M 10 10").unwrap()).is_none());
+
+    let stroked = |definition: &str, options: stroke::Options| {
+        let bounds =
+            BoundingBox::from_stroked_path(&Path::parse(definition).unwrap(), &options).unwrap();
+        [bounds.min.0, bounds.max.0].map(|point| point.map(|n| crate::math::to_fixed(n, 6)))
+    };
+
+    // Should include line-caps
+    assert_eq!(
+        stroked(
+            "M 0 0 H 10",
+            stroke::Options {
+                width: 2.0,
+                line_cap: stroke::LineCap::Round,
+                ..stroke::Options::default()
+            }
+        ),
+        [[-1.0, -1.0], [11.0, 1.0]]
+    );
+    // Should include miters
+    assert_eq!(
+        stroked(
+            "M 0 0 H 10 V 10",
+            stroke::Options {
+                width: 2.0,
+                ..stroke::Options::default()
+            }
+        ),
+        [[0.0, -1.0], [11.0, 10.0]]
+    );
+}
@@ -1,3 +1,5 @@
+//! The commands of a path, such as `M` and `C`, along with the position they're drawn from.
+
 use crate::{
     geometry::{Curve, Point},
     math,
@@ -262,14 +264,6 @@ impl Data {
         result
     }
 
-    /// Whether, when formatting itself, a space is needed between itself and the previous
-    /// command
-    pub(crate) fn is_space_needed(&self, prev: &Self) -> bool {
-        self.is_implicit()
-            && (prev.args().last().is_some_and(|n| (n % 1.0) == 0.0)
-                || self.args().first().is_some_and(|n| n >= &1.0 || n == &0.0))
-    }
-
     /// Calculates the saggita of an arc-by if possible
     pub fn calculate_saggita(&self, error: f64) -> Option<f64> {
         let Self::ArcBy(args) = self else {
@@ -318,57 +312,71 @@ impl From<(&ID, [f64; 7])> for Data {
 
 impl std::fmt::Display for Data {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.id().fmt(f)?;
-        let mut previous_option = None;
-        self.args()
-            .iter()
-            .try_for_each(|current| -> std::fmt::Result {
-                let current = *current;
-                let s = short_number(current);
-                #[allow(clippy::float_cmp)] // This is fine for formatting
-                if let Some(previous) = previous_option {
-                    if current >= 1.0
-                        || (current == 0.0)
-                        || (previous == 0.0 && current >= 0.0)
-                        || (previous % 1.0 == 0.0 && s.chars().next().is_some_and(|c| c == '.'))
-                        || (current > 0.0 && current < 1e-4)
-                    {
-                        f.write_char(' ')?;
-                    }
-                }
-                s.fmt(f)?;
-                previous_option = Some(current);
-                Ok(())
-            })?;
-        Ok(())
+        if !self.is_implicit() {
+            self.id().fmt(f)?;
+        }
+        write_args(f, self.args())
     }
 }
 
+/// Writes the arguments of a command in their shortest form, with only the spaces needed to
+/// separate them
+pub(crate) fn write_args<W: std::fmt::Write>(f: &mut W, args: &[f64]) -> std::fmt::Result {
+    let mut buffer = ryu::Buffer::new();
+    let mut previous_option = None;
+    for current in args {
+        let current = *current;
+        let (prefix, digits) = short_number_parts(&mut buffer, current);
+        #[allow(clippy::float_cmp)] // This is fine for formatting
+        if let Some(previous) = previous_option {
+            if current >= 1.0
+                || (current == 0.0)
+                || (previous == 0.0 && current >= 0.0)
+                || (previous % 1.0 == 0.0 && prefix.starts_with('.'))
+                || (current > 0.0 && current < 1e-4)
+            {
+                f.write_char(' ')?;
+            }
+        }
+        f.write_str(prefix)?;
+        f.write_str(digits)?;
+        previous_option = Some(current);
+    }
+    Ok(())
+}
+
 pub fn short_number<F>(n: F) -> String
 where
     F: ryu::Float,
 {
-    let mut s = ryu::Buffer::new().format(n).to_owned();
+    let mut buffer = ryu::Buffer::new();
+    let (prefix, digits) = short_number_parts(&mut buffer, n);
+    format!("{prefix}{digits}")
+}
+
+/// Returns the shortest form of the number without allocating, split into a prefix for the
+/// sign and leading decimal point and the remaining digits
+fn short_number_parts<F>(buffer: &mut ryu::Buffer, n: F) -> (&'static str, &str)
+where
+    F: ryu::Float,
+{
+    let mut s = buffer.format(n);
     // Remove trailing zeros
     if s.contains('.') {
-        s = match s.strip_suffix('0') {
-            Some(s) => s.into(),
-            None => s,
-        };
+        s = s.strip_suffix('0').unwrap_or(s);
     }
     if s == "0." || s == "-0." {
-        return String::from("0");
-    }
-    if s.ends_with('.') {
-        s.pop();
+        return ("0", "");
     }
+    let s = s.strip_suffix('.').unwrap_or(s);
     // Remove leading zero
-    if s.starts_with("0.") {
-        s.remove(0);
-    } else if s.starts_with("-0.") {
-        s.remove(1);
+    if let Some(s) = s.strip_prefix("0.") {
+        (".", s)
+    } else if let Some(s) = s.strip_prefix("-0.") {
+        ("-.", s)
+    } else {
+        ("", s)
     }
-    s
 }
 
 impl ID {
@@ -13,12 +13,16 @@
 mod cleanup;
 pub mod filter;
 mod mixed;
+mod normalise;
 mod relative;
+mod simplify;
 
 pub use crate::convert::cleanup::{cleanup, cleanup_unpositioned};
 pub use crate::convert::filter::filter;
 pub use crate::convert::mixed::{mixed, to_absolute};
+pub use crate::convert::normalise::normalise;
 pub use crate::convert::relative::relative;
+pub use crate::convert::simplify::simplify;
 use crate::geometry::MakeArcs;
 use crate::math::to_fixed;
 use crate::{command, Path};
@@ -97,6 +101,9 @@ pub struct Options {
     pub flags: Flags,
     pub make_arcs: MakeArcs,
     pub precision: Precision,
+    /// The maximum distance the path may deviate from the original when lossily simplifying
+    /// it with [`simplify`], or `None` to keep every vertex
+    pub simplify: Option<f64>,
 }
 
 /// Returns an optimised version of the input path
@@ -127,6 +134,17 @@ pub fn run(path: &Path, options: &Options, style_info: &StyleInfo) -> Path {
     // The general optimisation process: original -> naively relative -> filter redundant ->
     // optimal mixed
     log::debug!("convert::run: converting path: {path}");
+    let simplified;
+    let path = match options.simplify {
+        // NOTE: Markers are drawn at vertices, which may be moved or removed
+        Some(tolerance)
+            if !style_info.intersects(StyleInfo::has_marker | StyleInfo::has_marker_mid) =>
+        {
+            simplified = simplify(path, tolerance);
+            &simplified
+        }
+        _ => path,
+    };
     let mut positioned_path = relative(path);
     let mut state = filter::State::new(&positioned_path, options, style_info);
     positioned_path = filter(&positioned_path, options, &mut state, style_info);
@@ -360,6 +378,7 @@ impl Options {
             flags: Flags::default(),
             make_arcs: MakeArcs::default(),
             precision: Precision::conservative(),
+            simplify: None,
         }
     }
 }
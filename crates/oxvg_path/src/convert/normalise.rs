@@ -0,0 +1,89 @@
+use std::f64::consts::FRAC_PI_2;
+
+use crate::{
+    command::Data,
+    segment::{absolute, arc_to_segments, Absolute, Segment},
+    Path,
+};
+
+/// Returns the path as only absolute move, line, cubic bezier, and close path commands, as with
+/// the normalized path segment list of SVG 2.
+///
+/// Relative and implicit commands are made absolute and explicit, horizontal and vertical
+/// lines are expanded, smooth curves have their reflected control point written out, quadratic
+/// beziers are elevated to cubic beziers, and arcs are converted to cubic beziers of no more
+/// than 90 degrees each.
+///
+/// # Examples
+///
+/// ```
+/// use oxvg_path::{convert::normalise, Path};
+///
+/// let path = Path::parse("m 10 10 h 10 v 10 q 0 15 -15 15 z").unwrap();
+/// assert_eq!(
+///     &normalise(&path).to_string(),
+///     "M10 10L20 10L20 20C20 30 15 35 5 35Z"
+/// );
+/// ```
+pub fn normalise(path: &Path) -> Path {
+    let mut result = Vec::with_capacity(path.0.len());
+    for command in absolute(path) {
+        match command {
+            Absolute::Move(to) => result.push(Data::MoveTo(to.0)),
+            Absolute::Close(_) => result.push(Data::ClosePath),
+            Absolute::Line([_, to]) => result.push(Data::LineTo(to.0)),
+            Absolute::Cubic(points) => result.push(Segment::Cubic(points).to_command()),
+            // NOTE: Arcs without radii are lines, and arcs ending where they start are omitted
+            Absolute::Arc(from, args, to) => result.extend(
+                arc_to_segments(from, args, to, FRAC_PI_2)
+                    .into_iter()
+                    .map(Segment::to_command),
+            ),
+        }
+    }
+    Path(result)
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_normalise() {
+    let normalise = |definition: &str| {
+        let mut path = normalise(&Path::parse(definition).unwrap());
+        path.0.iter_mut().for_each(|command| {
+            command
+                .args_mut()
+                .iter_mut()
+                .for_each(|arg| *arg = crate::math::to_fixed(*arg, 3));
+        });
+        path.to_string()
+    };
+
+    // Should make commands absolute and explicit
+    assert_eq!(
+        normalise("m 10 10 20 0 0 20 M 0 0 L 5 5 10 0 m 5 5 z l 5 5"),
+        "M10 10L30 10L30 30M0 0L5 5L10 0M15 5ZL20 10"
+    );
+    // Should expand shorthands
+    assert_eq!(
+        normalise("M 0 0 H 10 V 10 h -10 v -10"),
+        "M0 0L10 0L10 10L0 10L0 0"
+    );
+    assert_eq!(
+        normalise("M 0 0 C 0 10 10 10 10 0 S 20 -10 20 0 s 10 10 10 0"),
+        "M0 0C0 10 10 10 10 0C10-10 20-10 20 0C20 10 30 10 30 0"
+    );
+    // Should elevate quadratics, including those reflected from previous curves
+    assert_eq!(
+        normalise("M 0 0 Q 15 15 30 0 T 60 0 L 90 0 T 120 0"),
+        "M0 0C10 10 20 10 30 0C40-10 50-10 60 0L90 0C90 0 100 0 120 0"
+    );
+    // Should convert arcs to curves
+    assert_eq!(
+        normalise("M 0 0 A 10 10 0 0 1 20 0"),
+        "M0 0C0-5.523 4.477-10 10-10C15.523-10 20-5.523 20 0"
+    );
+    assert_eq!(
+        normalise("M 0 0 A 0 10 0 0 1 20 0 A 10 10 0 0 1 20 0"),
+        "M0 0L20 0"
+    );
+}
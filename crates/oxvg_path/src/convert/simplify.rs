@@ -0,0 +1,539 @@
+use crate::{
+    command::Data,
+    geometry::Point,
+    segment::{absolute, normalise, Absolute, Segment, EPSILON},
+    Path,
+};
+
+/// The angle, in radians, between the joined ends of curves beyond which the join is treated as
+/// a corner and kept
+const CORNER_ANGLE: f64 = std::f64::consts::PI / 18.0;
+/// The number of points sampled from each curve when fitting a new curve to them
+const SAMPLES_PER_CURVE: usize = 8;
+/// The number of attempts at improving a fitted curve before splitting it
+const MAX_REPARAMETERISATIONS: usize = 8;
+
+/// Returns a path with fewer commands, which deviates from the original by about no more than
+/// the given tolerance.
+///
+/// Runs of lines are simplified with the Ramer–Douglas–Peucker algorithm, and smooth runs of
+/// curves are replaced by cubic beziers fitted to them with least-squares. Moves, arcs, and close
+/// paths are kept as they are, and the resulting commands are absolute.
+///
+/// Every removed vertex of a run of lines is within the tolerance of the simplified lines.
+/// Fitted curves are only checked against a number of points sampled along each original curve,
+/// so the deviation between those points may slightly exceed the tolerance.
+///
+/// This is lossy, as the vertices in between may move or be removed, which can affect markers
+/// and how strokes are joined.
+///
+/// # Examples
+///
+/// ```
+/// use oxvg_path::{convert::simplify, Path};
+///
+/// let path = Path::parse("M 0 0 L 5 0.1 L 10 0 L 15 -0.1 L 20 0").unwrap();
+/// assert_eq!(&simplify(&path, 0.5).to_string(), "M0 0L20 0");
+/// ```
+pub fn simplify(path: &Path, tolerance: f64) -> Path {
+    let mut simplifier = Simplifier {
+        result: vec![],
+        points: vec![],
+        curves: vec![],
+        tolerance,
+    };
+    for command in absolute(path) {
+        match command {
+            Absolute::Move(to) => {
+                simplifier.flush();
+                simplifier.result.push(Data::MoveTo(to.0));
+            }
+            Absolute::Close(_) => {
+                simplifier.flush();
+                simplifier.result.push(Data::ClosePath);
+            }
+            Absolute::Line([from, to]) => simplifier.line(from, to),
+            Absolute::Cubic(points) => simplifier.curve(points),
+            Absolute::Arc(_, [rx, ry, angle, large_arc, sweep], Point([x, y])) => {
+                simplifier.flush();
+                simplifier
+                    .result
+                    .push(Data::ArcTo([rx, ry, angle, large_arc, sweep, x, y]));
+            }
+        }
+    }
+    simplifier.flush();
+    Path(simplifier.result)
+}
+
+/// Collects runs of lines or curves, which are simplified once the run ends
+struct Simplifier {
+    result: Vec<Data>,
+    /// The vertices of the current run of lines, including it's start
+    points: Vec<Point>,
+    /// The current run of curves
+    curves: Vec<[Point; 4]>,
+    tolerance: f64,
+}
+
+impl Simplifier {
+    fn line(&mut self, from: Point, to: Point) {
+        if !self.curves.is_empty() {
+            self.flush();
+        }
+        if self.points.is_empty() {
+            self.points.push(from);
+        }
+        self.points.push(to);
+    }
+
+    fn curve(&mut self, points: [Point; 4]) {
+        if !self.points.is_empty() {
+            self.flush();
+        }
+        self.curves.push(points);
+    }
+
+    /// Writes the simplified commands of the current run
+    fn flush(&mut self) {
+        if let Some(first) = self.points.first() {
+            let mut kept = vec![*first];
+            ramer_douglas_peucker(&self.points, self.tolerance, &mut kept);
+            self.result
+                .extend(kept.into_iter().skip(1).map(|point| Data::LineTo(point.0)));
+            self.points.clear();
+        }
+        if !self.curves.is_empty() {
+            let curves = std::mem::take(&mut self.curves);
+            for run in split_at_corners(&curves) {
+                let fitted = fit_run(run, self.tolerance);
+                let curves = if fitted.len() < run.len() {
+                    &fitted
+                } else {
+                    run
+                };
+                self.result.extend(
+                    curves
+                        .iter()
+                        .map(|curve| Segment::Cubic(*curve).to_command()),
+                );
+            }
+        }
+    }
+}
+
+/// Pushes the vertices after the first which are needed to keep the polyline within the
+/// tolerance
+fn ramer_douglas_peucker(points: &[Point], tolerance: f64, kept: &mut Vec<Point>) {
+    if points.len() < 2 {
+        return;
+    }
+    // NOTE: Ranges are split with a stack rather than recursion, so that long runs of lines can't
+    // overflow the stack
+    let mut is_kept = vec![false; points.len()];
+    is_kept[points.len() - 1] = true;
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((from, to)) = ranges.pop() {
+        let furthest = (from + 1..to)
+            .map(|index| {
+                let distance = distance_to_line(points[index], points[from], points[to]);
+                (index, distance)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        if let Some((index, distance)) = furthest {
+            if distance > tolerance {
+                is_kept[index] = true;
+                ranges.push((index, to));
+                ranges.push((from, index));
+            }
+        }
+    }
+    kept.extend(
+        points
+            .iter()
+            .zip(is_kept)
+            .skip(1)
+            .filter_map(|(point, is_kept)| is_kept.then_some(*point)),
+    );
+}
+
+/// Returns the distance from the point to the line segment between `a` and `b`
+fn distance_to_line(point: Point, a: Point, b: Point) -> f64 {
+    let ab = b.sub(a);
+    let length = ab.dot(&ab);
+    if length <= EPSILON * EPSILON {
+        return point.sub(a).length();
+    }
+    let t = (point.sub(a).dot(&ab) / length).clamp(0.0, 1.0);
+    point.sub(a.add(ab.scale(t))).length()
+}
+
+/// Splits the curves into runs that join smoothly
+fn split_at_corners(curves: &[[Point; 4]]) -> Vec<&[[Point; 4]]> {
+    let mut runs = vec![];
+    let mut from = 0;
+    for index in 1..curves.len() {
+        let end = Segment::Cubic(curves[index - 1]).end_tangent();
+        let start = Segment::Cubic(curves[index]).start_tangent();
+        let is_smooth = end
+            .zip(start)
+            .is_some_and(|(end, start)| end.dot(&start) >= CORNER_ANGLE.cos());
+        if !is_smooth {
+            runs.push(&curves[from..index]);
+            from = index;
+        }
+    }
+    runs.push(&curves[from..]);
+    runs
+}
+
+/// Fits cubic beziers to the points sampled along a smooth run of curves
+fn fit_run(run: &[[Point; 4]], tolerance: f64) -> Vec<[Point; 4]> {
+    if run.len() < 2 {
+        return run.to_vec();
+    }
+    let mut points = vec![run[0][0]];
+    for curve in run {
+        let segment = Segment::Cubic(*curve);
+        points.extend((1..=SAMPLES_PER_CURVE).map(|index| {
+            #[allow(clippy::cast_precision_loss)]
+            segment.point(index as f64 / SAMPLES_PER_CURVE as f64)
+        }));
+    }
+    let (Some(start_tangent), Some(end_tangent)) = (
+        Segment::Cubic(run[0]).start_tangent(),
+        Segment::Cubic(run[run.len() - 1]).end_tangent(),
+    ) else {
+        return run.to_vec();
+    };
+
+    let mut result = vec![];
+    fit_cubic(
+        &points,
+        start_tangent,
+        end_tangent.minus(),
+        tolerance,
+        &mut result,
+    );
+    result
+}
+
+/// Fits cubic beziers to the points with the given directions leaving each end, splitting the
+/// points where the fit is worst until each curve is within the tolerance.
+///
+/// Based on "An Algorithm for Automatically Fitting Digitized Curves", from Graphics Gems.
+fn fit_cubic(
+    points: &[Point],
+    start_tangent: Point,
+    end_tangent: Point,
+    tolerance: f64,
+    result: &mut Vec<[Point; 4]>,
+) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    if points.len() == 2 {
+        let distance = last.sub(first).length() / 3.0;
+        result.push([
+            first,
+            first.add(start_tangent.scale(distance)),
+            last.add(end_tangent.scale(distance)),
+            last,
+        ]);
+        return;
+    }
+
+    let mut parameters = chord_length_parameters(points);
+    let mut curve = generate_bezier(points, &parameters, start_tangent, end_tangent);
+    let (mut error, mut split) = max_error(points, &curve, &parameters);
+    for _ in 0..MAX_REPARAMETERISATIONS {
+        if error <= tolerance {
+            break;
+        }
+        parameters = reparameterise(points, &curve, &parameters);
+        curve = generate_bezier(points, &parameters, start_tangent, end_tangent);
+        (error, split) = max_error(points, &curve, &parameters);
+    }
+    if error <= tolerance {
+        result.push(curve);
+        return;
+    }
+
+    // NOTE: The direction either side of the split is estimated from it's neighbours, falling
+    // back to the chord when they're the same point
+    let center = [points[split - 1].sub(points[split + 1]), first.sub(last)]
+        .into_iter()
+        .find(|vector| vector.length() > EPSILON)
+        .map_or(Point([-1.0, 0.0]), normalise);
+    fit_cubic(&points[..=split], start_tangent, center, tolerance, result);
+    fit_cubic(
+        &points[split..],
+        center.minus(),
+        end_tangent,
+        tolerance,
+        result,
+    );
+}
+
+/// Returns the parameter of each point, by it's distance along the polyline through them
+fn chord_length_parameters(points: &[Point]) -> Vec<f64> {
+    let mut parameters = vec![0.0];
+    let mut total = 0.0;
+    for pair in points.windows(2) {
+        total += pair[1].sub(pair[0]).length();
+        parameters.push(total);
+    }
+    if total > EPSILON {
+        for t in &mut parameters {
+            *t /= total;
+        }
+    }
+    parameters
+}
+
+/// Returns the curve with the given end-points and tangents which best fits the points at their
+/// parameters, using least-squares to find the length of each handle
+#[allow(clippy::similar_names)]
+fn generate_bezier(
+    points: &[Point],
+    parameters: &[f64],
+    start_tangent: Point,
+    end_tangent: Point,
+) -> [Point; 4] {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let mut c = [[0.0; 2]; 2];
+    let mut x = [0.0; 2];
+    for (point, t) in points.iter().zip(parameters) {
+        let mt = 1.0 - t;
+        let a = [
+            start_tangent.scale(3.0 * mt * mt * t),
+            end_tangent.scale(3.0 * mt * t * t),
+        ];
+        c[0][0] += a[0].dot(&a[0]);
+        c[0][1] += a[0].dot(&a[1]);
+        c[1][1] += a[1].dot(&a[1]);
+        let remainder = point.sub(
+            first
+                .scale(mt * mt * mt + 3.0 * mt * mt * t)
+                .add(last.scale(3.0 * mt * t * t + t * t * t)),
+        );
+        x[0] += a[0].dot(&remainder);
+        x[1] += a[1].dot(&remainder);
+    }
+    c[1][0] = c[0][1];
+
+    let determinant = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+    let (start_length, end_length) = if determinant.abs() > EPSILON {
+        (
+            (x[0] * c[1][1] - x[1] * c[0][1]) / determinant,
+            (c[0][0] * x[1] - c[1][0] * x[0]) / determinant,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    let chord = last.sub(first).length();
+    let (start_length, end_length) = if start_length < chord * 1e-6 || end_length < chord * 1e-6 {
+        (chord / 3.0, chord / 3.0)
+    } else {
+        (start_length, end_length)
+    };
+    [
+        first,
+        first.add(start_tangent.scale(start_length)),
+        last.add(end_tangent.scale(end_length)),
+        last,
+    ]
+}
+
+/// Returns the furthest distance of a point from the curve at it's parameter, and the index of
+/// that point
+fn max_error(points: &[Point], curve: &[Point; 4], parameters: &[f64]) -> (f64, usize) {
+    let segment = Segment::Cubic(*curve);
+    let mut result = (0.0, points.len() / 2);
+    for (index, (point, t)) in points.iter().zip(parameters).enumerate() {
+        if index == 0 || index == points.len() - 1 {
+            continue;
+        }
+        let error = segment.point(*t).sub(*point).length();
+        if error > result.0 {
+            result = (error, index);
+        }
+    }
+    result
+}
+
+/// Improves the parameters of each point with a step of Newton-Raphson, moving them closer to
+/// the nearest point on the curve
+#[allow(clippy::many_single_char_names)]
+fn reparameterise(points: &[Point], curve: &[Point; 4], parameters: &[f64]) -> Vec<f64> {
+    let [a, b, c, d] = *curve;
+    let segment = Segment::Cubic(*curve);
+    let first = [
+        b.sub(a).scale(3.0),
+        c.sub(b).scale(3.0),
+        d.sub(c).scale(3.0),
+    ];
+    let second = [
+        first[1].sub(first[0]).scale(2.0),
+        first[2].sub(first[1]).scale(2.0),
+    ];
+    points
+        .iter()
+        .zip(parameters)
+        .map(|(point, t)| {
+            let t = *t;
+            let mt = 1.0 - t;
+            let difference = segment.point(t).sub(*point);
+            let derivative = first[0]
+                .scale(mt * mt)
+                .add(first[1].scale(2.0 * mt * t))
+                .add(first[2].scale(t * t));
+            let second_derivative = second[0].scale(mt).add(second[1].scale(t));
+            let denominator = derivative.dot(&derivative) + difference.dot(&second_derivative);
+            if denominator.abs() <= EPSILON {
+                t
+            } else {
+                (t - difference.dot(&derivative) / denominator).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_simplify() {
+    let path = |definition: &str| Path::parse(definition).unwrap();
+
+    // Should remove vertices within the tolerance
+    let polyline = path("M 0 0 L 5 0.1 L 10 0 L 15 -0.1 L 20 0 V 10 H 0 Z");
+    assert_eq!(
+        &simplify(&polyline, 0.5).to_string(),
+        "M0 0L20 0L20 10L0 10Z"
+    );
+    assert_eq!(
+        &simplify(&polyline, 0.05).to_string(),
+        "M0 0L5 .1L15-.1L20 0L20 10L0 10Z"
+    );
+    // Should keep vertices beyond the tolerance
+    assert_eq!(
+        &simplify(&path("M 0 0 L 10 5 L 20 0"), 1.0).to_string(),
+        "M0 0L10 5L20 0"
+    );
+
+    // Should refit a dense run of curves
+    let mut original = Segment::Cubic([
+        Point([0.0, 0.0]),
+        Point([0.0, 20.0]),
+        Point([30.0, 20.0]),
+        Point([30.0, 0.0]),
+    ]);
+    let mut commands = vec![Data::MoveTo([0.0, 0.0])];
+    for t in [0.2, 0.25, 1.0 / 3.0, 0.5] {
+        let (left, right) = original.split(t);
+        let Segment::Cubic([_, b, c, d]) = left else {
+            unreachable!();
+        };
+        commands.push(Data::CubicBezierTo([
+            b.0[0], b.0[1], c.0[0], c.0[1], d.0[0], d.0[1],
+        ]));
+        original = right;
+    }
+    let Segment::Cubic([_, b, c, d]) = original else {
+        unreachable!();
+    };
+    commands.push(Data::CubicBezierTo([
+        b.0[0], b.0[1], c.0[0], c.0[1], d.0[0], d.0[1],
+    ]));
+    let simplified = simplify(&Path(commands), 0.01);
+    assert_eq!(simplified.0.len(), 2);
+    let Data::CubicBezierTo(args) = simplified.0[1] else {
+        unreachable!();
+    };
+    assert_eq!(
+        args.map(|arg| crate::math::to_fixed(arg, 1)),
+        [0.0, 20.0, 30.0, 20.0, 30.0, 0.0]
+    );
+
+    // Should keep corners between curves
+    assert_eq!(
+        simplify(&path("M 0 0 Q 5 5 10 0 Q 15 5 20 0"), 1.0).0.len(),
+        3
+    );
+    // Should keep arcs and moves
+    assert_eq!(
+        &simplify(
+            &path("m 10 10 a 5 5 0 0 1 10 0 l 5 0 l 5 0 z m 5 5 h 1"),
+            0.1
+        )
+        .to_string(),
+        "M10 10A5 5 0 0 1 20 10L30 10ZM15 15L16 15"
+    );
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_simplify_deviation() {
+    use crate::segment::{subpaths, MAX_ARC_ANGLE};
+
+    /// Returns points densely sampled along the path
+    fn samples(path: &Path) -> Vec<Vec<Point>> {
+        subpaths(path, MAX_ARC_ANGLE)
+            .iter()
+            .map(|contour| {
+                let mut points = vec![contour.start];
+                for segment in &contour.segments {
+                    points.extend((1..=16).map(|index| segment.point(f64::from(index) / 16.0)));
+                }
+                points
+            })
+            .collect()
+    }
+    /// Returns the furthest distance of a point of either path from the other path
+    fn deviation(a: &Path, b: &Path) -> f64 {
+        let (a, b) = (samples(a), samples(b));
+        let furthest = |from: &[Vec<Point>], to: &[Vec<Point>]| {
+            from.iter()
+                .zip(to)
+                .flat_map(|(from, to)| {
+                    from.iter().map(|point| {
+                        to.windows(2)
+                            .map(|line| distance_to_line(*point, line[0], line[1]))
+                            .fold(f64::INFINITY, f64::min)
+                    })
+                })
+                .fold(0.0, f64::max)
+        };
+        assert_eq!(a.len(), b.len());
+        furthest(&a, &b).max(furthest(&b, &a))
+    }
+
+    let noisy_line = (0..100).fold(String::from("M 0 0"), |path, index| {
+        let x = f64::from(index);
+        format!(
+            "{path} L {x} {}",
+            (x * 0.7).sin() * 2.0 + (x * 0.05).cos() * 20.0
+        )
+    });
+    let curves = (0..64).fold(String::from("M 100 0"), |path, index| {
+        let angle = |index: i32| f64::from(index) * std::f64::consts::TAU / 64.0;
+        let point = |angle: f64, radius: f64| (angle.cos() * radius, angle.sin() * radius);
+        let (x, y) = point(angle(index + 1), 100.0 + f64::from(index % 4));
+        let (x1, y1) = point(angle(index) + 0.03, 100.0);
+        let (x2, y2) = point(angle(index + 1) - 0.03, 100.0);
+        format!("{path} C {x1} {y1} {x2} {y2} {x} {y}")
+    });
+    for definition in [
+        noisy_line.as_str(),
+        curves.as_str(),
+        "M 0 0 Q 10 20 20 0 T 40 0 T 60 0 L 70 10 L 80 0 Z M 100 0 C 100 50 150 50 150 0",
+    ] {
+        let path = Path::parse(definition).unwrap();
+        for tolerance in [0.01, 0.1, 1.0, 5.0] {
+            let simplified = simplify(&path, tolerance);
+            let deviation = deviation(&path, &simplified);
+            assert!(
+                deviation <= tolerance,
+                "deviated by {deviation} with a tolerance of {tolerance}: {simplified}"
+            );
+        }
+    }
+}
@@ -1,12 +1,20 @@
+//! Points, curves, and transforms that describe the geometry of a path's commands.
+
 use crate::{
     command::{self, Position},
     math,
 };
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 /// A point is an `[x, y]` coordinate
 pub struct Point(pub [f64; 2]);
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A 2d affine transformation `[a, b, c, d, e, f]`, as with the `matrix()` transform function.
+///
+/// A point `[x, y]` is transformed to `[a * x + c * y + e, b * x + d * y + f]`
+pub struct Matrix(pub [f64; 6]);
+
 #[derive(Debug, Clone)]
 /// A bezier curve.
 ///
@@ -22,6 +30,23 @@ pub struct Point(pub [f64; 2]);
 /// start of the curve and the latter controlling the end.
 pub struct Curve(pub [f64; 6]);
 
+#[derive(Debug, Clone, Copy)]
+/// An elliptical arc in center parameterization, as described in
+/// [SVG 1.1, F.6.5](https://www.w3.org/TR/SVG11/implnote.html#ArcConversionEndpointToCenter)
+pub struct Arc {
+    /// The center of the ellipse
+    pub center: Point,
+    /// The radii of the ellipse, scaled up if too small to reach the end-point
+    pub radii: [f64; 2],
+    /// The rotation of the ellipse's x-axis, in radians
+    pub rotation: f64,
+    /// The angle of the start of the arc, in radians
+    pub start_angle: f64,
+    /// The angle swept from the start to the end of the arc, in radians, which is positive for
+    /// a positive-angle direction
+    pub sweep_angle: f64,
+}
+
 #[derive(Debug, Clone)]
 pub struct Circle {
     pub center: Point,
@@ -200,6 +225,21 @@ impl Point {
         Self([self.0[0] - v2[0], self.0[1] - v2[1]])
     }
 
+    /// Returns the sum of two vectors
+    pub fn add(&self, Self(v2): Self) -> Self {
+        Self([self.0[0] + v2[0], self.0[1] + v2[1]])
+    }
+
+    /// Returns the vector multiplied by a scalar
+    pub fn scale(&self, factor: f64) -> Self {
+        Self([self.0[0] * factor, self.0[1] * factor])
+    }
+
+    /// Returns the length of the vector
+    pub fn length(&self) -> f64 {
+        math::hypot(self.0[0], self.0[1])
+    }
+
     pub fn dot(&self, Self(v2): &Self) -> f64 {
         self.0[0] * v2[0] + self.0[1] * v2[1]
     }
@@ -276,6 +316,148 @@ impl Point {
     }
 }
 
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[allow(clippy::many_single_char_names)]
+impl Matrix {
+    /// The matrix which leaves points unchanged
+    pub const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    /// Returns the matrix applying `other` and then `self`
+    #[must_use]
+    pub fn multiply(&self, Self(other): &Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        Self([
+            a * other[0] + c * other[1],
+            b * other[0] + d * other[1],
+            a * other[2] + c * other[3],
+            b * other[2] + d * other[3],
+            a * other[4] + c * other[5] + e,
+            b * other[4] + d * other[5] + f,
+        ])
+    }
+
+    /// Returns the transformed point
+    pub fn apply(&self, Point([x, y]): Point) -> Point {
+        let [a, b, c, d, e, f] = self.0;
+        Point([a * x + c * y + e, b * x + d * y + f])
+    }
+
+    /// Returns the transformed vector, ignoring the translation of the matrix
+    pub fn apply_vector(&self, Point([x, y]): Point) -> Point {
+        let [a, b, c, d, ..] = self.0;
+        Point([a * x + c * y, b * x + d * y])
+    }
+
+    /// Returns the determinant of the matrix, which is negative when the matrix flips the
+    /// orientation of shapes
+    pub fn determinant(&self) -> f64 {
+        let [a, b, c, d, ..] = self.0;
+        a * d - b * c
+    }
+}
+
+impl Arc {
+    /// Converts the endpoint parameterization of an arc, as with the args of
+    /// [`Data::ArcTo`](crate::command::Data::ArcTo), to a center parameterization.
+    ///
+    /// Returns `None` when the arc is drawn as a straight line or not at all, such as when the
+    /// end-points are the same or a radius is zero.
+    #[allow(clippy::many_single_char_names, clippy::similar_names)]
+    pub fn from_endpoints(
+        from: Point,
+        [rx, ry, angle, large_arc, sweep]: [f64; 5],
+        to: Point,
+    ) -> Option<Self> {
+        const EPSILON: f64 = 1e-9;
+        if from.sub(to).length() <= EPSILON {
+            return None;
+        }
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if rx <= EPSILON || ry <= EPSILON {
+            return None;
+        }
+        let rotation = angle.to_radians();
+        let (sin, cos) = rotation.sin_cos();
+        let half = from.sub(to).scale(0.5);
+        let x1 = cos * half.0[0] + sin * half.0[1];
+        let y1 = -sin * half.0[0] + cos * half.0[1];
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+        if (large_arc != 0.0) == (sweep != 0.0) {
+            coefficient = -coefficient;
+        }
+        let cx1 = coefficient * rx * y1 / ry;
+        let cy1 = -coefficient * ry * x1 / rx;
+        let mid = from.add(to).scale(0.5);
+        let center = Point([
+            cos * cx1 - sin * cy1 + mid.0[0],
+            sin * cx1 + cos * cy1 + mid.0[1],
+        ]);
+
+        let angle_between =
+            |u: Point, v: Point| f64::atan2(u.0[0] * v.0[1] - u.0[1] * v.0[0], u.dot(&v));
+        let start_vector = Point([(x1 - cx1) / rx, (y1 - cy1) / ry]);
+        let end_vector = Point([(-x1 - cx1) / rx, (-y1 - cy1) / ry]);
+        let start_angle = angle_between(Point([1.0, 0.0]), start_vector);
+        let mut sweep_angle = angle_between(start_vector, end_vector);
+        if sweep == 0.0 && sweep_angle > 0.0 {
+            sweep_angle -= 2.0 * std::f64::consts::PI;
+        } else if sweep != 0.0 && sweep_angle < 0.0 {
+            sweep_angle += 2.0 * std::f64::consts::PI;
+        }
+        Some(Self {
+            center,
+            radii: [rx, ry],
+            rotation,
+            start_angle,
+            sweep_angle,
+        })
+    }
+
+    /// Returns the point on the ellipse at the given angle
+    pub fn point_at(&self, angle: f64) -> Point {
+        let [rx, ry] = self.radii;
+        let (sin, cos) = self.rotation.sin_cos();
+        let (sin_a, cos_a) = angle.sin_cos();
+        Point([
+            self.center.0[0] + rx * cos_a * cos - ry * sin_a * sin,
+            self.center.0[1] + rx * cos_a * sin + ry * sin_a * cos,
+        ])
+    }
+
+    /// Returns the derivative of the ellipse at the given angle
+    pub fn derivative_at(&self, angle: f64) -> Point {
+        let [rx, ry] = self.radii;
+        let (sin, cos) = self.rotation.sin_cos();
+        let (sin_a, cos_a) = angle.sin_cos();
+        Point([
+            -rx * sin_a * cos - ry * cos_a * sin,
+            -rx * sin_a * sin + ry * cos_a * cos,
+        ])
+    }
+
+    /// Returns whether the angle of the ellipse is between the start and end of the arc
+    pub fn contains_angle(&self, angle: f64) -> bool {
+        let tau = 2.0 * std::f64::consts::PI;
+        if self.sweep_angle >= 0.0 {
+            (angle - self.start_angle).rem_euclid(tau) <= self.sweep_angle
+        } else {
+            (self.start_angle - angle).rem_euclid(tau) <= -self.sweep_angle
+        }
+    }
+}
+
 impl Circle {
     /// From a curve, which is potentially an arc, find the correspoding circle
     pub fn find(curve: &Curve, make_arcs: &MakeArcs, error: f64) -> Option<Self> {
@@ -0,0 +1,141 @@
+//! Finding where the outlines and areas of paths meet.
+//!
+//! Unlike [`Path::intersects`], which compares the convex hulls of each path's points, these
+//! follow the geometry of lines and curves, so concave paths and curves don't produce false
+//! positives. Arcs are approximated as cubic beziers.
+use crate::{
+    geometry::Point,
+    segment::{closed_segments, subpaths, Segment, MAX_ARC_ANGLE},
+    Path,
+};
+
+/// The default distance within which outlines are considered to meet
+pub const TOLERANCE: f64 = 1e-6;
+
+impl Path {
+    /// Returns the points at which the drawn outlines of the paths meet, where points within
+    /// the tolerance of each other are treated as one.
+    ///
+    /// Subpaths are only closed when they end with a close path command, and the points where
+    /// a path meets itself aren't included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let a = Path::parse("M 0 0 L 10 10").unwrap();
+    /// let b = Path::parse("M 0 10 L 10 0").unwrap();
+    /// let points = a.intersections(&b, 1e-6);
+    /// assert_eq!(points.len(), 1);
+    /// assert_eq!(points[0].0, [5.0, 5.0]);
+    /// ```
+    pub fn intersections(&self, other: &Self, tolerance: f64) -> Vec<Point> {
+        let drawn_segments = |path: &Self| -> Vec<Segment> {
+            subpaths(path, MAX_ARC_ANGLE)
+                .into_iter()
+                .flat_map(|contour| contour.segments)
+                .collect()
+        };
+        let a = drawn_segments(self);
+        let b = drawn_segments(other);
+
+        let mut result: Vec<Point> = vec![];
+        for segment in &a {
+            for other_segment in &b {
+                for (t, _) in segment.intersections(other_segment, tolerance) {
+                    let point = segment.point(t);
+                    if result
+                        .iter()
+                        .all(|other_point| other_point.sub(point).length() > tolerance * 4.0)
+                    {
+                        result.push(point);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns whether the filled areas of the paths overlap or their outlines meet within the
+    /// tolerance, where each subpath is implicitly closed.
+    ///
+    /// Areas are filled with the `nonzero` fill-rule, which covers at least the area of the
+    /// `evenodd` fill-rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let a = Path::parse("M 0 0 H 30 V 30 H 20 V 10 H 10 V 30 H 0 Z").unwrap();
+    /// let b = Path::parse("M 12 12 H 18 V 28 H 12 Z").unwrap();
+    /// assert!(!a.overlaps(&b, 1e-6));
+    ///
+    /// let c = Path::parse("M 5 5 H 8 V 8 H 5 Z").unwrap();
+    /// assert!(a.overlaps(&c, 1e-6));
+    /// ```
+    pub fn overlaps(&self, other: &Self, tolerance: f64) -> bool {
+        let a = closed_segments(self, MAX_ARC_ANGLE);
+        let b = closed_segments(other, MAX_ARC_ANGLE);
+        if a.iter().any(|segment| {
+            b.iter()
+                .any(|other| !segment.intersections(other, tolerance).is_empty())
+        }) {
+            return true;
+        }
+
+        // NOTE: Without meeting outlines, each subpath is either entirely inside or outside the
+        // other path, so checking a single point of each is enough
+        let contains = |segments: &[Segment], point: Point| {
+            segments
+                .iter()
+                .map(|segment| segment.winding(point))
+                .sum::<i32>()
+                != 0
+        };
+        b.iter().any(|segment| contains(&a, segment.start()))
+            || a.iter().any(|segment| contains(&b, segment.start()))
+    }
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_intersect() {
+    let path = |definition: &str| Path::parse(definition).unwrap();
+    let round = |point: Point| point.0.map(|n| crate::math::to_fixed(n, 4));
+
+    // Should find where lines and curves cross
+    let points = path("M 0 5 H 10").intersections(&path("M 0 0 C 0 10 10 10 10 0"), TOLERANCE);
+    assert_eq!(
+        points.into_iter().map(round).collect::<Vec<_>>(),
+        vec![[1.151, 5.0], [8.849, 5.0]]
+    );
+    let points = path("M 0 5 H 20").intersections(&path("M 5 0 A 5 5 0 0 1 5 10"), TOLERANCE);
+    assert_eq!(
+        points.into_iter().map(round).collect::<Vec<_>>(),
+        vec![[10.0, 5.0]]
+    );
+    // Should only include closing lines that are drawn
+    assert!(path("M 0 0 H 10 V 10")
+        .intersections(&path("M 5 -5 L 10 0 L 0 10"), TOLERANCE)
+        .iter()
+        .all(|point| point.0 != [5.0, 5.0]));
+    assert!(path("M 0 0 H 10 V 10")
+        .intersections(&path("M 4 8 L 8 4"), TOLERANCE)
+        .is_empty());
+
+    // Should not overlap paths within a concave region
+    let concave = path("M 0 0 H 30 V 30 H 20 V 10 H 10 V 30 H 0 Z");
+    assert!(!concave.overlaps(&path("M 12 12 H 18 V 28 H 12 Z"), TOLERANCE));
+    assert!(concave.overlaps(&path("M 5 12 H 18 V 28 H 12 Z"), TOLERANCE));
+    // Should not overlap paths outside of a curve
+    let curve = path("M 0 0 C 0 20 20 20 20 0 Z");
+    assert!(!curve.overlaps(&path("M 0 14 H 4 V 18 H 0 Z"), TOLERANCE));
+    assert!(curve.overlaps(&path("M 8 12 H 12 V 18 H 8 Z"), TOLERANCE));
+    // Should overlap paths contained by another
+    assert!(path("M 0 0 H 10 V 10 H 0 Z").overlaps(&path("M 2 2 H 8 V 8 H 2 Z"), TOLERANCE));
+    assert!(path("M 2 2 H 8 V 8 H 2 Z").overlaps(&path("M 0 0 H 10 V 10 H 0 Z"), TOLERANCE));
+    // Should treat touching outlines as meeting
+    assert!(path("M 0 0 H 10 V 10 H 0 Z").overlaps(&path("M 10 0 H 20 V 10 H 10 Z"), TOLERANCE));
+}
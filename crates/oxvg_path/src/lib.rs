@@ -11,7 +11,7 @@
 //! # Differences to SVGO
 //!
 //! - Unlike SVGO, all close paths are serialized as `Z` instead of either `z` or `Z`. This is fine because the two commands function exactly the same.
-//! - An equivalent of the `applyTransforms` option isn't available, but may be in the future.
+//! - An equivalent of the `applyTransforms` option isn't available as part of [run](convert::run), instead use [`Path::transform`] before running.
 //!
 //! # Licensing
 //!
@@ -21,6 +21,10 @@
 #[macro_use]
 extern crate bitflags;
 
+#[cfg(feature = "optimise")]
+pub mod boolean;
+#[cfg(feature = "optimise")]
+pub mod bounds;
 #[cfg(feature = "optimise")]
 pub mod command;
 #[cfg(feature = "optimise")]
@@ -28,19 +32,31 @@ pub mod convert;
 #[cfg(feature = "optimise")]
 pub mod geometry;
 #[cfg(feature = "optimise")]
+pub mod intersect;
+#[cfg(feature = "optimise")]
 pub(crate) mod math;
+#[cfg(feature = "optimise")]
+pub mod measure;
 #[cfg(feature = "parse")]
 pub mod parser;
 #[cfg(feature = "optimise")]
 pub mod points;
 #[cfg(feature = "optimise")]
 pub mod positioned;
+#[cfg(feature = "optimise")]
+pub(crate) mod segment;
+#[cfg(feature = "optimise")]
+pub mod stroke;
+#[cfg(feature = "optimise")]
+pub mod subpath;
+#[cfg(feature = "optimise")]
+pub mod transform;
+#[cfg(feature = "format")]
+pub mod writer;
 
+#[cfg(feature = "optimise")]
 use points::{Point, Points};
 
-#[cfg(feature = "parse")]
-use crate::parser::Parser;
-
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
@@ -68,15 +84,63 @@ impl Path {
     /// # Errors
     /// If the definition is invalid
     pub fn parse(definition: &str) -> Result<Self, parser::Error> {
-        Parser::default().parse(definition)
+        parser::Commands::new(definition)
+            .map(|command| command.map(parser::Command::into_data))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    #[cfg(feature = "parse")]
+    /// Parses a path definition from a string up to the first error, returning the commands
+    /// before the one containing the error along with the error, if any.
+    ///
+    /// As with browsers, the returned path can be rendered in place of an invalid definition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let (path, error) = Path::parse_partial("M 10 10 L 20 20 L 30 ,, 30");
+    /// assert_eq!(&path.to_string(), "M10 10 20 20");
+    /// assert_eq!(error.unwrap().offset(), 22);
+    /// ```
+    pub fn parse_partial(definition: &str) -> (Self, Option<parser::Error>) {
+        parser::parse_partial(definition)
     }
 
+    #[cfg(feature = "optimise")]
+    /// Returns the tight bounds of the path's geometry, or `None` if nothing is drawn.
+    ///
+    /// See [`BoundingBox::from_path`](bounds::BoundingBox::from_path)
+    pub fn bounding_box(&self) -> Option<bounds::BoundingBox> {
+        bounds::BoundingBox::from_path(self)
+    }
+
+    #[cfg(feature = "optimise")]
+    /// Returns the bounds of the path when both filled and stroked, or `None` if nothing is
+    /// drawn.
+    ///
+    /// See [`BoundingBox::from_stroked_path`](bounds::BoundingBox::from_stroked_path)
+    pub fn stroke_bounding_box(&self, options: &stroke::Options) -> Option<bounds::BoundingBox> {
+        bounds::BoundingBox::from_stroked_path(self, options)
+    }
+
+    #[cfg(feature = "optimise")]
     /// Checks if two paths have an intersection by checking convex hulls collision using
-    /// Gilbert-Johnson-Keerthi distance algorithm.
+    /// Gilbert-Johnson-Keerthi distance algorithm, and then whether the areas or outlines
+    /// of colliding paths meet with [`Path::overlaps`].
     ///
     /// # Panics
     /// If internal assertions fail
     pub fn intersects(&self, other: &Self) -> bool {
+        self.hulls_intersect(other) && self.overlaps(other, intersect::TOLERANCE)
+    }
+
+    #[cfg(feature = "optimise")]
+    /// Checks if the convex hulls of the paths' points collide, which is a quick but
+    /// conservative estimate of whether the paths intersect
+    fn hulls_intersect(&self, other: &Self) -> bool {
         let points_1 = Points::from_positioned(&convert::relative(self));
         let points_2 = Points::from_positioned(&convert::relative(other));
 
@@ -136,28 +200,8 @@ impl Path {
 #[cfg(feature = "format")]
 impl std::fmt::Display for Path {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use std::fmt::Write;
-
-        if self.0.len() == 1 {
-            self.0.first().unwrap().fmt(f)?;
-            return Ok(());
-        }
-        self.0
-            .windows(2)
-            .enumerate()
-            .try_for_each(|(i, window)| -> std::fmt::Result {
-                let prev = &window[0];
-                let current = &window[1];
-                if i == 0 {
-                    prev.fmt(f)?;
-                }
-                let str = current.to_string();
-                if current.is_space_needed(prev) && !str.starts_with('-') {
-                    f.write_char(' ')?;
-                }
-                f.write_str(&str)?;
-                Ok(())
-            })
+        let mut writer = writer::Writer::new(f);
+        self.0.iter().try_for_each(|command| writer.write(command))
     }
 }
 
@@ -202,4 +246,43 @@ fn test_path_parse() {
 
     // Should error when args are missing
     assert!(Path::parse("m1").is_err());
+
+    // Should keep the commands before an error, with the error's position
+    let partial = |definition: &str| {
+        let (path, error) = Path::parse_partial(definition);
+        (path.to_string(), error.map(|error| error.offset()))
+    };
+    assert_eq!(
+        partial("M 10,50 L 20,30"),
+        ("M10 50 20 30".to_string(), None)
+    );
+    assert_eq!(partial("M 10,50 L 20"), ("M10 50".to_string(), Some(12)));
+    assert_eq!(
+        partial("M 10,50 L 20,30 40"),
+        ("M10 50 20 30".to_string(), Some(18))
+    );
+    assert_eq!(
+        partial("M 10,50 L 20,,30"),
+        ("M10 50".to_string(), Some(13))
+    );
+    assert_eq!(
+        partial("M 10,50 A 10 10 0 2 1 20 20"),
+        ("M10 50".to_string(), Some(18))
+    );
+    assert_eq!(partial("M 10,50 H 1e"), ("M10 50".to_string(), Some(10)));
+    assert_eq!(partial("L 10,50"), (String::new(), Some(0)));
+    assert_eq!(partial("  10,50"), (String::new(), Some(2)));
+    assert_eq!(partial("M 0 0 Z 10 10"), ("M0 0Z".to_string(), Some(8)));
+
+    // Should not repeat close path or nest implicit commands
+    assert_eq!(
+        Path::parse("M 0 0 h 1 2 3 z").unwrap().0,
+        vec![
+            command::Data::MoveTo([0.0, 0.0]),
+            command::Data::HorizontalLineBy([1.0]),
+            command::Data::Implicit(Box::new(command::Data::HorizontalLineBy([2.0]))),
+            command::Data::Implicit(Box::new(command::Data::HorizontalLineBy([3.0]))),
+            command::Data::ClosePath,
+        ]
+    );
 }
@@ -26,3 +26,26 @@ pub fn saggita(args: &[f64; 7], error: f64) -> Option<f64> {
     }
     Some(rx - f64::sqrt((rx * rx) - 0.25 * (chord * chord)))
 }
+
+/// Returns the roots of `a * t^2 + b * t + c` where `0 < t < 1`
+pub(crate) fn quadratic_roots(a: f64, b: f64, c: f64) -> impl Iterator<Item = f64> {
+    const EPSILON: f64 = 1e-12;
+    let roots = if a.abs() < EPSILON {
+        if b.abs() < EPSILON {
+            [None, None]
+        } else {
+            [Some(-c / b), None]
+        }
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            [None, None]
+        } else {
+            // NOTE: The roots are found without subtracting similar values, as a tiny `a`, such
+            // as that of a quadratic bezier elevated to a cubic, would otherwise lose precision
+            let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+            [Some(q / a), (q != 0.0).then(|| c / q)]
+        }
+    };
+    roots.into_iter().flatten().filter(|t| *t > 0.0 && *t < 1.0)
+}
@@ -0,0 +1,213 @@
+//! Measurements of a path's geometry, similar to those of `SVGGeometryElement`.
+//!
+//! Arcs are measured by their approximation as cubic beziers, and curves are measured to within
+//! [`TOLERANCE`], so results may differ slightly from those of a browser.
+use crate::{
+    geometry::Point,
+    segment::{subpaths, Contour, Segment, MAX_ARC_ANGLE},
+    Path,
+};
+
+/// The maximum error of a curve's length when measuring it
+pub const TOLERANCE: f64 = 1e-6;
+
+impl Path {
+    /// Returns the total length of the path, as with `SVGGeometryElement.getTotalLength()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let path = Path::parse("M 0 0 H 10 M 0 10 V 20").unwrap();
+    /// assert_eq!(path.length(), 20.0);
+    /// ```
+    pub fn length(&self) -> f64 {
+        self.subpath_lengths().into_iter().sum()
+    }
+
+    /// Returns the length of each subpath that's drawn, in order.
+    pub fn subpath_lengths(&self) -> Vec<f64> {
+        subpaths(self, MAX_ARC_ANGLE)
+            .iter()
+            .map(Contour::length)
+            .collect()
+    }
+
+    /// Returns the signed area of the path, where each subpath is implicitly closed, as it would
+    /// be when filled.
+    ///
+    /// The area of subpaths drawn clockwise, in the y-down coordinates of SVG, are positive and
+    /// those drawn counter-clockwise are negative; so overlapping subpaths of opposite direction
+    /// will cancel out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let path = Path::parse("M 0 0 H 10 V 10 H 0 Z").unwrap();
+    /// assert_eq!(path.area(), 100.0);
+    /// let path = Path::parse("M 0 0 V 10 H 10 V 0 Z").unwrap();
+    /// assert_eq!(path.area(), -100.0);
+    /// ```
+    pub fn area(&self) -> f64 {
+        self.subpath_areas().into_iter().sum()
+    }
+
+    /// Returns the signed area of each subpath that's drawn, in order.
+    ///
+    /// See [`Path::area`] for the meaning of the sign, which can be used to find the winding
+    /// direction of each subpath.
+    pub fn subpath_areas(&self) -> Vec<f64> {
+        subpaths(self, MAX_ARC_ANGLE)
+            .iter()
+            .map(Contour::area)
+            .collect()
+    }
+
+    /// Returns the point at the given distance along the path, as with
+    /// `SVGGeometryElement.getPointAtLength()`, or `None` if the path draws nothing.
+    ///
+    /// The distance is clamped to the length of the path, and moving between subpaths doesn't
+    /// add to the distance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let path = Path::parse("M 0 0 H 10 V 10").unwrap();
+    /// assert_eq!(path.point_at_length(15.0).unwrap().0, [10.0, 5.0]);
+    /// assert_eq!(path.point_at_length(100.0).unwrap().0, [10.0, 10.0]);
+    /// ```
+    pub fn point_at_length(&self, length: f64) -> Option<Point> {
+        self.at_length(length).map(|(point, _)| point)
+    }
+
+    /// Returns the unit vector of the path's direction at the given distance along the path, or
+    /// `None` if the path draws nothing or has no direction.
+    ///
+    /// The distance is clamped to the length of the path, as with [`Path::point_at_length`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let path = Path::parse("M 0 0 H 10 V 10").unwrap();
+    /// assert_eq!(path.tangent_at_length(15.0).unwrap().0, [0.0, 1.0]);
+    /// ```
+    pub fn tangent_at_length(&self, length: f64) -> Option<Point> {
+        self.at_length(length).and_then(|(_, tangent)| tangent)
+    }
+
+    /// Returns the point and direction at the given distance along the path
+    fn at_length(&self, length: f64) -> Option<(Point, Option<Point>)> {
+        let contours = subpaths(self, MAX_ARC_ANGLE);
+        let mut last = None;
+        let mut remaining = length.max(0.0);
+        for contour in &contours {
+            if contour.segments.is_empty() {
+                last.get_or_insert((contour.start, None));
+                continue;
+            }
+            for segment in &contour.segments {
+                let total = segment.length(TOLERANCE);
+                if remaining <= total {
+                    let (point, tangent) = segment.point_at_length(remaining, total, TOLERANCE);
+                    return Some((point, Some(tangent)));
+                }
+                remaining -= total;
+                last = Some((segment.end(), segment.end_tangent()));
+            }
+        }
+        last
+    }
+}
+
+impl Contour {
+    fn length(&self) -> f64 {
+        self.segments
+            .iter()
+            .map(|segment| segment.length(TOLERANCE))
+            .sum()
+    }
+
+    fn area(&self) -> f64 {
+        let cross = |Point([x0, y0]): Point, Point([x1, y1]): Point| x0 * y1 - x1 * y0;
+        let closing = self
+            .segments
+            .last()
+            .map_or(0.0, |last| cross(last.end(), self.start) / 2.0);
+        self.segments
+            .iter()
+            .map(|segment| match *segment {
+                Segment::Line([a, b]) => cross(a, b) / 2.0,
+                Segment::Cubic([a, b, c, d]) => {
+                    (6.0 * cross(a, b)
+                        + 3.0 * cross(a, c)
+                        + cross(a, d)
+                        + 3.0 * cross(b, c)
+                        + 3.0 * cross(b, d)
+                        + 6.0 * cross(c, d))
+                        / 20.0
+                }
+            })
+            .sum::<f64>()
+            + closing
+    }
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_measure() {
+    use std::f64::consts::PI;
+
+    let path = |definition: &str| Path::parse(definition).unwrap();
+    let round = |n: f64| crate::math::to_fixed(n, 4);
+
+    // Should measure lines, ignoring moves
+    assert_eq!(path("M 0 0 l 3 4 m 10 0 h 5").length(), 10.0);
+    assert_eq!(
+        path("M 0 0 l 3 4 m 10 0 h 5 Z").subpath_lengths(),
+        vec![5.0, 10.0]
+    );
+    // Should measure curves and arcs
+    assert_eq!(round(path("M 0 0 Q 5 0 10 0").length()), 10.0);
+    assert_eq!(
+        round(path("M 0 0 A 5 5 0 0 1 10 0").length()),
+        round(5.0 * PI)
+    );
+    assert_eq!(round(path("M 0 0 C 0 10 10 10 10 0").length()), 20.0);
+
+    // Should find the signed area of curves and open subpaths
+    assert_eq!(path("M 0 0 H 10 V 10").area(), 50.0);
+    assert_eq!(
+        round(path("M 0 0 A 5 5 0 0 0 10 0 Z").area()),
+        round(-12.5 * PI)
+    );
+    assert_eq!(
+        path("M 0 0 H 10 V 10 H 0 Z M 2 2 V 8 H 8 V 2 Z").subpath_areas(),
+        vec![100.0, -36.0]
+    );
+    assert_eq!(path("M 0 0 C 0 10 10 10 10 0 Z").area(), -60.0);
+
+    // Should find points across subpaths
+    let p = path("M 0 0 H 10 M 20 0 V 10 Z");
+    assert_eq!(p.point_at_length(-5.0).unwrap().0, [0.0, 0.0]);
+    assert_eq!(p.point_at_length(10.0).unwrap().0, [10.0, 0.0]);
+    assert_eq!(p.point_at_length(15.0).unwrap().0, [20.0, 5.0]);
+    assert_eq!(p.point_at_length(25.0).unwrap().0, [20.0, 5.0]);
+    assert_eq!(p.tangent_at_length(25.0).unwrap().0, [0.0, -1.0]);
+    assert_eq!(p.point_at_length(50.0).unwrap().0, [20.0, 0.0]);
+    let point = path("M 0 0 A 5 5 0 0 1 10 0")
+        .point_at_length(2.5 * PI)
+        .unwrap();
+    assert_eq!(point.0.map(round), [5.0, -5.0]);
+
+    // Should handle paths that draw nothing
+    assert!(path("M 0 0").point_at_length(0.0).is_none());
+    assert_eq!(path("M 5 5 Z").point_at_length(1.0).unwrap().0, [5.0, 5.0]);
+    assert!(path("M 5 5 Z").tangent_at_length(1.0).is_none());
+}
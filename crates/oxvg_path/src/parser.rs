@@ -1,203 +1,246 @@
+//! Reading path definitions one command at a time.
 use crate::{command, Path};
 
-#[derive(Default)]
-pub(crate) struct Parser {
-    path_data: Vec<command::Data>,
-    can_have_comma: bool,
-    had_comma: bool,
-    current_command: command::ID,
-    args: [f64; 7],
-    args_len: usize,
-    args_capacity: usize,
-    current_number: String,
-    had_decminal: bool,
+/// An iterator over the commands of a path definition, which reads each command only as it's
+/// needed and without allocating.
+///
+/// Iteration stops after the first error, so the commands read before it can be used in place
+/// of an invalid definition, as with [`Path::parse_partial`].
+///
+/// # Examples
+///
+/// ```
+/// use oxvg_path::{command::Data, parser::Commands};
+///
+/// let mut commands = Commands::new("M 10 10 L 20 20 30 30");
+/// let first = commands.next().unwrap().unwrap();
+/// assert_eq!(first.data, Data::MoveTo([10.0, 10.0]));
+/// assert!(!first.implicit);
+/// assert_eq!(commands.count(), 2);
+/// ```
+pub struct Commands<'a> {
+    definition: &'a str,
+    /// The byte offset of the next character to read
     cursor: usize,
+    /// The command read before the current one, which arguments without a command repeat
+    previous: Option<command::ID>,
+    done: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A command read by [`Commands`]
+pub struct Command {
+    /// The command, which is never [`command::Data::Implicit`]
+    pub data: command::Data,
+    /// Whether the command can be written without its letter, since it's what follows the
+    /// previous command
+    pub implicit: bool,
 }
 
 #[derive(Debug)]
+/// An error found while parsing a path, with the byte offset in the definition at which it
+/// was found
 pub enum Error {
+    /// A command was given fewer arguments than it needs
     CommandEndedTooEarly(usize),
-    NoCommand,
-    DuplicateComma,
-    InvalidFirstCommand,
-    InvalidArcSign,
-    InvalidArc,
-    InvalidNumber(std::num::ParseFloatError),
+    /// An argument was given before any command
+    NoCommand(usize),
+    /// Arguments were separated by more than one comma
+    DuplicateComma(usize),
+    /// The first command wasn't a move
+    InvalidFirstCommand(usize),
+    /// An arc's radius was given a sign
+    InvalidArcSign(usize),
+    /// An arc's flag wasn't `0` or `1`, or an arc was given an invalid character
+    InvalidArc(usize),
+    /// An argument couldn't be parsed as a number
+    InvalidNumber(usize, std::num::ParseFloatError),
 }
 
-impl Parser {
-    /// Returns whether the numbers of args parsed matches what's expected for the command
-    fn is_flush_ready(&self) -> bool {
-        self.current_command.is_implicit()
-            && (self.args_len == 0 || self.args_len == self.args_capacity)
-            || !self.current_command.is_implicit() && self.args_len == self.args_capacity
-    }
-
-    /// Resets `args`, `args_len`, `args_capacity`, and `can_has_comma` for the new command
-    /// The old command is collated and pushed to `path_data`
-    fn flush_args(&mut self, command: &command::ID) -> Result<(), Error> {
-        if !self.is_flush_ready() {
-            Err(Error::CommandEndedTooEarly(self.cursor))?;
+impl<'a> Commands<'a> {
+    /// Creates an iterator over the commands of the path definition
+    pub fn new(definition: &'a str) -> Self {
+        Self {
+            definition,
+            cursor: 0,
+            previous: None,
+            done: false,
         }
-        self.args_capacity = command.args();
-        if self.current_command.is_implicit() && self.args_len == 0 {
-            self.current_command = command.clone();
-            return Ok(());
-        }
-        let is_implicit = match self.path_data.last() {
-            Some(command::Data::Implicit(c)) => c.id().next_implicit() == self.current_command,
-            Some(c) => c.id().next_implicit() == self.current_command,
-            _ => false,
-        };
-        let flushed_args: [f64; 7] = std::mem::replace(&mut self.args, [0.0; 7]);
-        self.args_len = 0;
-        self.can_have_comma = false;
+    }
 
-        let from_command = if is_implicit {
-            &command::ID::Implicit(Box::new(self.current_command.clone()))
-        } else {
-            &self.current_command
-        };
-        self.path_data
-            .push(command::Data::from((from_command, flushed_args)));
-        if !command.is_none() && self.args_capacity == 0 {
-            self.path_data
-                .push(command::Data::from((command, flushed_args)));
-        }
-        self.current_command = command.clone();
-        Ok(())
+    fn peek(&self) -> Option<u8> {
+        self.definition.as_bytes().get(self.cursor).copied()
     }
 
-    fn done(&mut self) -> Path {
-        Path(std::mem::take(&mut self.path_data))
+    fn is_command(byte: u8) -> bool {
+        command::ID::try_from(char::from(byte)).is_ok()
     }
 
-    fn next_command(&mut self, command: &command::ID) -> Result<(), Error> {
-        if self.had_comma {
-            Err(Error::DuplicateComma)?;
-        }
-        if self.current_command.is_none() {
-            // MoveTo should be leading command
-            if !matches!(command, command::ID::MoveBy | command::ID::MoveTo) {
-                Err(Error::InvalidFirstCommand)?;
+    /// Skips whitespace along with up to one comma
+    fn skip_separator(&mut self) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.peek() == Some(b',') {
+            self.cursor += 1;
+            self.skip_whitespace();
+            if self.peek() == Some(b',') {
+                return Err(Error::DuplicateComma(self.cursor));
             }
-            self.current_command = command.clone();
-            self.args_capacity = self.current_command.args();
-            return Ok(());
-        } else if !self.is_flush_ready() {
-            // stop if previous arguments are not flushed
-            Err(Error::CommandEndedTooEarly(self.cursor))?;
         }
-        self.flush_args(command)?;
         Ok(())
     }
 
-    fn process_number(&mut self) -> Result<(), Error> {
-        let number = std::mem::take(&mut self.current_number)
-            .parse::<f64>()
-            .map_err(Error::InvalidNumber)?;
-        self.args[self.args_len] = number;
-        self.args_len += 1;
-        self.can_have_comma = true;
-        self.had_comma = false;
-        self.had_decminal = false;
-        Ok(())
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.cursor += 1;
+        }
     }
 
-    pub fn parse(&mut self, definition: &str) -> Result<Path, Error> {
-        self.cursor = 0;
-        for char in definition.chars() {
-            if char.is_whitespace() && self.current_number.is_empty() {
-                continue;
-            }
-
-            // Allow comma only between arguments
-            if char == ',' && self.current_number.is_empty() {
-                if self.had_comma {
-                    Err(Error::DuplicateComma)?;
-                }
-                self.had_comma = true;
-                continue;
+    /// Reads a number, where a sign, decimal point, or exponent ends the previous number
+    fn number(&mut self) -> Result<f64, Error> {
+        let bytes = self.definition.as_bytes();
+        let start = self.cursor;
+        let mut end = start;
+        let digits = |end: &mut usize| {
+            while bytes.get(*end).is_some_and(u8::is_ascii_digit) {
+                *end += 1;
             }
-            self.had_comma = false;
-            if let Ok(command_id) = command::ID::try_from(char) {
-                if !self.current_number.is_empty() {
-                    self.process_number()?;
-                }
-                self.next_command(&command_id)?;
-                continue;
+        };
+        if matches!(bytes.get(end), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        digits(&mut end);
+        if bytes.get(end) == Some(&b'.') {
+            end += 1;
+            digits(&mut end);
+        }
+        if matches!(bytes.get(end), Some(b'e' | b'E')) {
+            end += 1;
+            if matches!(bytes.get(end), Some(b'+' | b'-')) {
+                end += 1;
             }
+            digits(&mut end);
+        }
+        self.cursor = end;
+        self.definition[start..end]
+            .parse()
+            .map_err(|error| Error::InvalidNumber(start, error))
+    }
 
-            // avoid parsing arguments if no command is detected
-            if self.current_command.is_none() {
-                Err(Error::NoCommand)?;
-            }
-            if (!char.is_numeric() && !matches!(char, '+' | '-' | '.' | 'e' | 'E'))
-                // '.' is start of new number
-                || (self.had_decminal && char == '.' && !self.current_number.ends_with('e') && !self.current_number.ends_with('-'))
-                // '-' is start of new number
-                || (!self.current_number.is_empty()
-                    && !self.current_number.ends_with('e')
-                    && char == '-')
-            {
-                self.process_number()?;
-                self.had_comma = char == ',';
-                if char == '.' {
-                    self.current_number.push(char);
-                    self.had_decminal = true;
-                } else if char == '-' {
-                    self.current_number.push(char);
+    /// Reads the arguments of the command
+    fn args(&mut self, id: &command::ID) -> Result<command::Data, Error> {
+        let is_arc = matches!(id, command::ID::ArcTo | command::ID::ArcBy);
+        let mut args = [0.0; 7];
+        for (index, arg) in args.iter_mut().enumerate().take(id.args()) {
+            self.skip_separator()?;
+            *arg = match self.peek() {
+                None => return Err(Error::CommandEndedTooEarly(self.cursor)),
+                Some(byte) if Self::is_command(byte) => {
+                    return Err(Error::CommandEndedTooEarly(self.cursor));
                 }
-                if self.args_len != self.args_capacity {
-                    continue;
+                // don't allow sign on first two args
+                Some(b'+' | b'-') if is_arc && index <= 1 => {
+                    return Err(Error::InvalidArcSign(self.cursor));
                 }
-                // flush arguments when capacity is reached
-                self.flush_args(&command::ID::Implicit(match self.current_command {
-                    command::ID::MoveTo => Box::new(command::ID::LineTo),
-                    command::ID::MoveBy => Box::new(command::ID::LineBy),
-                    _ => Box::new(self.current_command.clone()),
-                }))?;
-                continue;
-            }
-            // read next argument
-            if matches!(
-                self.current_command,
-                command::ID::ArcTo | command::ID::ArcBy
-            ) {
-                let number = match char {
-                    // don't allow sign on first two args
-                    '+' | '-' if self.args_len <= 1 => {
-                        return Err(Error::InvalidArcSign)?;
-                    }
-                    '0' if (3..=4).contains(&self.args_len) => 0.0,
-                    '1' if (3..=4).contains(&self.args_len) => 1.0,
-                    '+' | '-' | '.' => {
-                        self.current_number.push(char);
-                        continue;
-                    }
-                    char if char.is_numeric() => {
-                        self.current_number.push(char);
-                        continue;
-                    }
-                    _ => {
-                        return Err(Error::InvalidArc)?;
-                    }
-                };
-                self.args[self.args_len] = number;
-                self.args_len += 1;
-            } else {
-                self.had_decminal = self.had_decminal || char == '.';
-                self.current_number.push(char);
+                // flags must be either `0` or `1`
+                Some(byte) if is_arc && (3..=4).contains(&index) => {
+                    let flag = match byte {
+                        b'0' => 0.0,
+                        b'1' => 1.0,
+                        _ => return Err(Error::InvalidArc(self.cursor)),
+                    };
+                    self.cursor += 1;
+                    flag
+                }
+                Some(_) => self.number()?,
+            };
+        }
+        Ok(command::Data::from((id, args)))
+    }
+
+    fn next_command(&mut self) -> Result<Option<Command>, Error> {
+        self.skip_separator()?;
+        let Some(byte) = self.peek() else {
+            return Ok(None);
+        };
+        let id = if let Ok(id) = command::ID::try_from(char::from(byte)) {
+            // MoveTo should be leading command
+            if self.previous.is_none() && !matches!(id, command::ID::MoveBy | command::ID::MoveTo) {
+                return Err(Error::InvalidFirstCommand(self.cursor));
             }
             self.cursor += 1;
+            id
+        } else {
+            // avoid parsing arguments if no command is detected
+            match &self.previous {
+                None | Some(command::ID::ClosePath) => return Err(Error::NoCommand(self.cursor)),
+                Some(previous) => previous.next_implicit(),
+            }
+        };
+        let data = self.args(&id)?;
+        let implicit = id.args() > 0
+            && self
+                .previous
+                .as_ref()
+                .is_some_and(|previous| previous.next_implicit() == id);
+        self.previous = Some(id);
+        Ok(Some(Command { data, implicit }))
+    }
+}
+
+impl Iterator for Commands<'_> {
+    type Item = Result<Command, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
         }
-        if !self.current_number.is_empty() {
-            self.process_number()?;
+        let result = self.next_command();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
         }
-        if !self.current_command.is_none() {
-            self.flush_args(&command::ID::None)?;
+        result.transpose()
+    }
+}
+
+impl std::iter::FusedIterator for Commands<'_> {}
+
+impl Command {
+    /// Returns the command as it's stored in a [`Path`], where implicit commands are wrapped
+    /// with [`command::Data::Implicit`]
+    pub fn into_data(self) -> command::Data {
+        if self.implicit {
+            command::Data::Implicit(Box::new(self.data))
+        } else {
+            self.data
+        }
+    }
+}
+
+/// Parses the definition up to the first error, returning the commands before the one
+/// containing the error, along with the error
+pub(crate) fn parse_partial(definition: &str) -> (Path, Option<Error>) {
+    let mut path = vec![];
+    for command in Commands::new(definition) {
+        match command {
+            Ok(command) => path.push(command.into_data()),
+            Err(error) => return (Path(path), Some(error)),
+        }
+    }
+    (Path(path), None)
+}
+
+impl Error {
+    /// Returns the byte offset in the definition at which the error was found
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::CommandEndedTooEarly(offset)
+            | Self::NoCommand(offset)
+            | Self::DuplicateComma(offset)
+            | Self::InvalidFirstCommand(offset)
+            | Self::InvalidArcSign(offset)
+            | Self::InvalidArc(offset)
+            | Self::InvalidNumber(offset, _) => *offset,
         }
-        Ok(self.done())
     }
 }
 
@@ -205,16 +248,73 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let fmt = match self {
             Self::CommandEndedTooEarly(_) => "A path command ended too early",
-            Self::NoCommand => "Expected a path command",
-            Self::DuplicateComma => "Found unexpected comma in path command",
-            Self::InvalidFirstCommand => "Expected path to start with `m` or `M`",
-            Self::InvalidArcSign => "Unexpected sign given on one of first two `a` or `A` commands",
-            Self::InvalidArc => "Badly formatted `a` or `A` command",
-            Self::InvalidNumber(e) => &format!("Failed to parse number in path: {e}"),
+            Self::NoCommand(_) => "Expected a path command",
+            Self::DuplicateComma(_) => "Found unexpected comma in path command",
+            Self::InvalidFirstCommand(_) => "Expected path to start with `m` or `M`",
+            Self::InvalidArcSign(_) => {
+                "Unexpected sign given on one of first two `a` or `A` commands"
+            }
+            Self::InvalidArc(_) => "Badly formatted `a` or `A` command",
+            Self::InvalidNumber(_, e) => &format!("Failed to parse number in path: {e}"),
         };
         f.write_str(fmt)?;
-        Ok(())
+        write!(f, " at position {}", self.offset())
     }
 }
 
 impl std::error::Error for Error {}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_commands() {
+    use command::Data;
+
+    let commands = |definition: &str| {
+        Commands::new(definition)
+            .map(|command| command.map(|command| (command.data, command.implicit)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+    let error = |definition: &str| {
+        let mut commands = Commands::new(definition);
+        let error = commands.by_ref().find_map(Result::err).unwrap();
+        // Should stop after the first error
+        assert!(commands.next().is_none());
+        error
+    };
+
+    // Should read each command, with whether it follows on from the previous command
+    assert_eq!(
+        commands("M 10 10 20 20 l-5-5 L 1 1 z m .5.5"),
+        vec![
+            (Data::MoveTo([10.0, 10.0]), false),
+            (Data::LineTo([20.0, 20.0]), true),
+            (Data::LineBy([-5.0, -5.0]), false),
+            (Data::LineTo([1.0, 1.0]), false),
+            (Data::ClosePath, false),
+            (Data::MoveBy([0.5, 0.5]), false),
+        ]
+    );
+    assert!(commands("").is_empty());
+    assert!(commands(" \n ").is_empty());
+
+    // Should give the byte offset of each error
+    assert!(matches!(error("0,0"), Error::NoCommand(0)));
+    assert!(matches!(error("L 0 0"), Error::InvalidFirstCommand(0)));
+    assert!(matches!(error("M 0"), Error::CommandEndedTooEarly(3)));
+    assert!(matches!(error("M 0 0 L 1,,1"), Error::DuplicateComma(10)));
+    assert!(matches!(
+        error("M 0 0 A -1 1 0 0 1 5 5"),
+        Error::InvalidArcSign(8)
+    ));
+    assert!(matches!(
+        error("M 0 0 A 1 1 0 2 1 5 5"),
+        Error::InvalidArc(14)
+    ));
+    assert!(matches!(error("M 0 0 L 1e 1"), Error::InvalidNumber(8, _)));
+    assert!(matches!(
+        error("M 0 0 L 1 1 $"),
+        Error::InvalidNumber(12, _)
+    ));
+    assert!(matches!(error("M 0 0 é 1"), Error::InvalidNumber(6, _)));
+}
@@ -1,3 +1,5 @@
+//! The control points of a path's subpaths, used to compare their convex hulls.
+
 use core::f64;
 
 use crate::{
@@ -1,3 +1,5 @@
+//! Paths with the start and end position of each command.
+
 use crate::command::Position;
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,673 @@
+//! Absolute line and cubic bezier segments, used for measuring, outlining, and combining paths.
+use crate::{
+    command::Data,
+    geometry::{Arc, Point},
+    math::quadratic_roots,
+    Path,
+};
+
+#[derive(Debug, Clone, Copy)]
+/// An absolute line or cubic bezier segment
+pub(crate) enum Segment {
+    Line([Point; 2]),
+    Cubic([Point; 4]),
+}
+
+#[derive(Debug, Clone)]
+/// A sequence of connected segments
+pub(crate) struct Contour {
+    pub start: Point,
+    pub segments: Vec<Segment>,
+    pub closed: bool,
+    /// Whether a zero-length contour should be drawn with caps
+    pub drawn: bool,
+    /// The direction of caps for zero-length contours
+    pub direction: Point,
+}
+
+pub(crate) const EPSILON: f64 = 1e-9;
+
+/// The maximum angle of each curve approximating an arc when precision matters, which is small
+/// enough that the approximation is within `1e-6` for most radii
+pub(crate) const MAX_ARC_ANGLE: f64 = std::f64::consts::PI / 16.0;
+
+#[derive(Debug, Clone, Copy)]
+/// An absolute command of a path, as walked by [`absolute`]
+pub(crate) enum Absolute {
+    Move(Point),
+    Line([Point; 2]),
+    Cubic([Point; 4]),
+    /// An arc from the first point to the last, which is left for the caller to either measure
+    /// or approximate with [`arc_to_segments`]
+    Arc(Point, [f64; 5], Point),
+    /// A line closing the subpath, from the current point back to the start of the subpath
+    Close([Point; 2]),
+}
+
+/// Walks the path as absolute commands, where relative and implicit commands are made absolute
+/// and explicit, horizontal and vertical lines are expanded, smooth curves have their reflected
+/// control point written out, and quadratic beziers are elevated to cubic beziers.
+pub(crate) fn absolute(path: &Path) -> impl Iterator<Item = Absolute> + '_ {
+    let mut cursor = Point::default();
+    let mut start = Point::default();
+    let mut prev_cubic_control: Option<Point> = None;
+    let mut prev_quadratic_control: Option<Point> = None;
+
+    path.0.iter().map(move |command| {
+        let command = command.as_explicit();
+        let base = if command.is_by() {
+            cursor
+        } else {
+            Point::default()
+        };
+        let point = |x: f64, y: f64| Point([x, y]).add(base);
+        let from = cursor;
+        let mut cubic_control = None;
+        let mut quadratic_control = None;
+
+        let result = match *command {
+            Data::MoveTo([x, y]) | Data::MoveBy([x, y]) => {
+                cursor = point(x, y);
+                start = cursor;
+                Absolute::Move(cursor)
+            }
+            Data::ClosePath => {
+                cursor = start;
+                Absolute::Close([from, start])
+            }
+            Data::LineTo([x, y]) | Data::LineBy([x, y]) => {
+                cursor = point(x, y);
+                Absolute::Line([from, cursor])
+            }
+            Data::HorizontalLineTo([x]) | Data::HorizontalLineBy([x]) => {
+                cursor = Point([x + base.0[0], cursor.0[1]]);
+                Absolute::Line([from, cursor])
+            }
+            Data::VerticalLineTo([y]) | Data::VerticalLineBy([y]) => {
+                cursor = Point([cursor.0[0], y + base.0[1]]);
+                Absolute::Line([from, cursor])
+            }
+            Data::CubicBezierTo([x1, y1, x2, y2, x, y])
+            | Data::CubicBezierBy([x1, y1, x2, y2, x, y]) => {
+                let control_2 = point(x2, y2);
+                cursor = point(x, y);
+                cubic_control = Some(control_2);
+                Absolute::Cubic([from, point(x1, y1), control_2, cursor])
+            }
+            Data::SmoothBezierTo([x2, y2, x, y]) | Data::SmoothBezierBy([x2, y2, x, y]) => {
+                let control_1 = prev_cubic_control.map_or(from, |c| c.reflect(from));
+                let control_2 = point(x2, y2);
+                cursor = point(x, y);
+                cubic_control = Some(control_2);
+                Absolute::Cubic([from, control_1, control_2, cursor])
+            }
+            Data::QuadraticBezierTo([x1, y1, x, y]) | Data::QuadraticBezierBy([x1, y1, x, y]) => {
+                let control = point(x1, y1);
+                cursor = point(x, y);
+                quadratic_control = Some(control);
+                Absolute::Cubic(elevate(from, control, cursor))
+            }
+            Data::SmoothQuadraticBezierTo([x, y]) | Data::SmoothQuadraticBezierBy([x, y]) => {
+                let control = prev_quadratic_control.map_or(from, |c| c.reflect(from));
+                cursor = point(x, y);
+                quadratic_control = Some(control);
+                Absolute::Cubic(elevate(from, control, cursor))
+            }
+            Data::ArcTo([rx, ry, angle, large_arc, sweep, x, y])
+            | Data::ArcBy([rx, ry, angle, large_arc, sweep, x, y]) => {
+                cursor = point(x, y);
+                Absolute::Arc(from, [rx, ry, angle, large_arc, sweep], cursor)
+            }
+            Data::Implicit(_) => unreachable!("explicit command expected"),
+        };
+        prev_cubic_control = cubic_control;
+        prev_quadratic_control = quadratic_control;
+        result
+    })
+}
+
+/// Returns the control points of the cubic bezier drawing the same curve as the quadratic
+fn elevate(from: Point, control: Point, to: Point) -> [Point; 4] {
+    [
+        from,
+        from.add(control.sub(from).scale(2.0 / 3.0)),
+        to.add(control.sub(to).scale(2.0 / 3.0)),
+        to,
+    ]
+}
+
+/// Converts a path into subpaths of absolute lines and cubic beziers, where arcs are split into
+/// curves of no more than `max_arc_angle` radians
+pub(crate) fn subpaths(path: &Path, max_arc_angle: f64) -> Vec<Contour> {
+    let mut result: Vec<Contour> = vec![];
+    let mut contour = Contour::new(Point::default());
+
+    for command in absolute(path) {
+        match command {
+            Absolute::Move(to) => {
+                result.push(std::mem::replace(&mut contour, Contour::new(to)));
+            }
+            Absolute::Close([from, start]) => {
+                if from.sub(start).length() > EPSILON {
+                    contour.segments.push(Segment::Line([from, start]));
+                }
+                contour.closed = true;
+                contour.drawn = true;
+                result.push(std::mem::replace(&mut contour, Contour::new(start)));
+            }
+            Absolute::Line([from, to]) => contour.line(from, to),
+            Absolute::Cubic(points) => contour.cubic(points),
+            Absolute::Arc(from, args, to) => {
+                contour.drawn = true;
+                contour
+                    .segments
+                    .extend(arc_to_segments(from, args, to, max_arc_angle));
+            }
+        }
+    }
+    result.push(contour);
+    result.retain(|contour| contour.drawn);
+    result
+}
+
+/// Returns the segments of the path, with each subpath closed as it would be when filled
+pub(crate) fn closed_segments(path: &Path, max_arc_angle: f64) -> Vec<Segment> {
+    subpaths(path, max_arc_angle)
+        .into_iter()
+        .flat_map(|mut contour| {
+            let end = contour.segments.last().map(Segment::end);
+            if let Some(end) = end {
+                if end.sub(contour.start).length() > EPSILON {
+                    contour.segments.push(Segment::Line([end, contour.start]));
+                }
+            }
+            contour.segments
+        })
+        .collect()
+}
+
+/// Converts the arc from `from` to `to` into cubic beziers.
+///
+/// Unlike [`crate::convert::filter::arc::Convert::a2c`], this keeps the end-point exact and
+/// applies the arc's rotation to both end-points.
+pub(crate) fn arc_to_segments(
+    from: Point,
+    args: [f64; 5],
+    to: Point,
+    max_angle: f64,
+) -> Vec<Segment> {
+    if from.sub(to).length() <= EPSILON {
+        return vec![];
+    }
+    let Some(arc) = Arc::from_endpoints(from, args, to) else {
+        return vec![Segment::Line([from, to])];
+    };
+    let Arc {
+        start_angle,
+        sweep_angle,
+        ..
+    } = arc;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let count = (sweep_angle.abs() / max_angle - EPSILON).ceil().max(1.0) as usize;
+    #[allow(clippy::cast_precision_loss)]
+    let step = sweep_angle / count as f64;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+    let mut start = from;
+    (0..count)
+        .map(|i| {
+            #[allow(clippy::cast_precision_loss)]
+            let a1 = start_angle + step * i as f64;
+            let a2 = a1 + step;
+            let end = if i + 1 == count { to } else { arc.point_at(a2) };
+            let segment = Segment::Cubic([
+                start,
+                start.add(arc.derivative_at(a1).scale(handle)),
+                end.sub(arc.derivative_at(a2).scale(handle)),
+                end,
+            ]);
+            start = end;
+            segment
+        })
+        .collect()
+}
+
+/// Returns the unit normal of the vector, pointing to it's left
+pub(crate) fn normal(Point([x, y]): Point) -> Point {
+    Point([-y, x])
+}
+
+pub(crate) fn normalise(vector: Point) -> Point {
+    vector.scale(1.0 / vector.length())
+}
+
+impl Contour {
+    pub fn new(start: Point) -> Self {
+        Self {
+            start,
+            segments: vec![],
+            closed: false,
+            drawn: false,
+            direction: Point([1.0, 0.0]),
+        }
+    }
+
+    pub fn push(&mut self, segment: Option<Segment>) {
+        if let Some(segment) = segment {
+            self.segments.push(segment);
+        }
+    }
+
+    pub fn line(&mut self, from: Point, to: Point) {
+        self.drawn = true;
+        if from.sub(to).length() > EPSILON {
+            self.segments.push(Segment::Line([from, to]));
+        }
+    }
+
+    pub fn cubic(&mut self, points: [Point; 4]) {
+        self.drawn = true;
+        let segment = Segment::Cubic(points);
+        if segment.start_tangent().is_some() {
+            self.segments.push(segment);
+        }
+    }
+
+    pub fn reversed(&self) -> Vec<Segment> {
+        self.segments.iter().rev().map(Segment::reverse).collect()
+    }
+}
+
+#[allow(clippy::many_single_char_names)]
+impl Segment {
+    /// Returns the segment as an absolute line or cubic bezier command from it's start
+    pub fn to_command(self) -> Data {
+        match self {
+            Self::Line([_, end]) => Data::LineTo(end.0),
+            Self::Cubic([_, b, c, d]) => {
+                Data::CubicBezierTo([b.0[0], b.0[1], c.0[0], c.0[1], d.0[0], d.0[1]])
+            }
+        }
+    }
+
+    pub fn start(&self) -> Point {
+        match self {
+            Self::Line([start, _]) | Self::Cubic([start, ..]) => *start,
+        }
+    }
+
+    pub fn end(&self) -> Point {
+        match self {
+            Self::Line([_, end]) | Self::Cubic([.., end]) => *end,
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        match *self {
+            Self::Line([a, b]) => Self::Line([b, a]),
+            Self::Cubic([a, b, c, d]) => Self::Cubic([d, c, b, a]),
+        }
+    }
+
+    /// Returns the direction of the segment at it's start
+    pub fn start_tangent(&self) -> Option<Point> {
+        let start = self.start();
+        let points = match self {
+            Self::Line([_, end]) => vec![*end],
+            Self::Cubic([_, rest @ ..]) => rest.to_vec(),
+        };
+        points
+            .into_iter()
+            .map(|point| point.sub(start))
+            .find(|vector| vector.length() > EPSILON)
+            .map(normalise)
+    }
+
+    /// Returns the direction of the segment at it's end
+    pub fn end_tangent(&self) -> Option<Point> {
+        self.reverse()
+            .start_tangent()
+            .map(|tangent| tangent.minus())
+    }
+
+    pub fn point(&self, t: f64) -> Point {
+        match *self {
+            Self::Line([a, b]) => a.add(b.sub(a).scale(t)),
+            Self::Cubic([a, b, c, d]) => {
+                let mt = 1.0 - t;
+                a.scale(mt * mt * mt)
+                    .add(b.scale(3.0 * mt * mt * t))
+                    .add(c.scale(3.0 * mt * t * t))
+                    .add(d.scale(t * t * t))
+            }
+        }
+    }
+
+    /// Returns the direction of the segment at `t`
+    pub fn tangent(&self, t: f64) -> Option<Point> {
+        if t <= EPSILON {
+            return self.start_tangent();
+        } else if t >= 1.0 - EPSILON {
+            return self.end_tangent();
+        }
+        let derivative = match *self {
+            Self::Line([a, b]) => b.sub(a),
+            Self::Cubic([a, b, c, d]) => {
+                let mt = 1.0 - t;
+                b.sub(a)
+                    .scale(mt * mt)
+                    .add(c.sub(b).scale(2.0 * mt * t))
+                    .add(d.sub(c).scale(t * t))
+            }
+        };
+        if derivative.length() > EPSILON {
+            Some(normalise(derivative))
+        } else {
+            None
+        }
+    }
+
+    /// Splits the segment in two at `t`, using de Casteljau's algorithm
+    pub fn split(&self, t: f64) -> (Self, Self) {
+        match *self {
+            Self::Line([a, b]) => {
+                let mid = self.point(t);
+                (Self::Line([a, mid]), Self::Line([mid, b]))
+            }
+            Self::Cubic([a, b, c, d]) => {
+                let lerp = |p: Point, q: Point| p.add(q.sub(p).scale(t));
+                let (ab, bc, cd) = (lerp(a, b), lerp(b, c), lerp(c, d));
+                let (abc, bcd) = (lerp(ab, bc), lerp(bc, cd));
+                let mid = lerp(abc, bcd);
+                (
+                    Self::Cubic([a, ab, abc, mid]),
+                    Self::Cubic([mid, bcd, cd, d]),
+                )
+            }
+        }
+    }
+
+    pub fn length(&self, tolerance: f64) -> f64 {
+        self.length_recursive(tolerance, 16)
+    }
+
+    fn length_recursive(&self, tolerance: f64, depth: usize) -> f64 {
+        match *self {
+            Self::Line([a, b]) => b.sub(a).length(),
+            Self::Cubic([a, b, c, d]) => {
+                let chord = d.sub(a).length();
+                let polygon = b.sub(a).length() + c.sub(b).length() + d.sub(c).length();
+                if depth == 0 || polygon - chord <= tolerance {
+                    return f64::midpoint(chord, polygon);
+                }
+                let (left, right) = self.split(0.5);
+                left.length_recursive(tolerance / 2.0, depth - 1)
+                    + right.length_recursive(tolerance / 2.0, depth - 1)
+            }
+        }
+    }
+
+    /// Returns the `t` at which the segment reaches the given length
+    pub fn t_at_length(&self, length: f64, total: f64, tolerance: f64) -> f64 {
+        if length <= 0.0 {
+            return 0.0;
+        } else if length >= total {
+            return 1.0;
+        }
+        match self {
+            Self::Line(_) => length / total,
+            Self::Cubic(_) => {
+                let (mut low, mut high) = (0.0, 1.0);
+                for _ in 0..32 {
+                    let mid = f64::midpoint(low, high);
+                    if self.split(mid).0.length(tolerance) < length {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                    if high - low <= EPSILON {
+                        break;
+                    }
+                }
+                f64::midpoint(low, high)
+            }
+        }
+    }
+
+    /// Returns the point and direction at the given length along the segment
+    pub fn point_at_length(&self, length: f64, total: f64, tolerance: f64) -> (Point, Point) {
+        let t = self.t_at_length(length, total, tolerance);
+        let direction = self
+            .tangent(t)
+            .or_else(|| self.start_tangent())
+            .unwrap_or(Point([1.0, 0.0]));
+        (self.point(t), direction)
+    }
+
+    /// Returns the part of the segment between the two lengths
+    pub fn slice(&self, from: f64, to: f64, total: f64, tolerance: f64) -> Option<Self> {
+        if to - from <= EPSILON {
+            return None;
+        }
+        let t_from = self.t_at_length(from, total, tolerance);
+        let t_to = self.t_at_length(to, total, tolerance);
+        let segment = if t_to < 1.0 {
+            self.split(t_to).0
+        } else {
+            *self
+        };
+        let segment = if t_from > 0.0 {
+            segment.split(t_from / t_to).1
+        } else {
+            segment
+        };
+        Some(segment)
+    }
+
+    /// Returns the top-left and bottom-right corners of a box containing the segment
+    pub fn bounds(&self) -> (Point, Point) {
+        let points: &[Point] = match self {
+            Self::Line(points) => points,
+            Self::Cubic(points) => points,
+        };
+        points
+            .iter()
+            .skip(1)
+            .fold((points[0], points[0]), |(min, max), p| {
+                (
+                    Point([min.0[0].min(p.0[0]), min.0[1].min(p.0[1])]),
+                    Point([max.0[0].max(p.0[0]), max.0[1].max(p.0[1])]),
+                )
+            })
+    }
+
+    /// Returns the segment with it's end-points moved, moving the adjacent control points along
+    /// with them
+    pub fn with_end_points(&self, start: Point, end: Point) -> Self {
+        match *self {
+            Self::Line(_) => Self::Line([start, end]),
+            Self::Cubic([a, b, c, d]) => {
+                Self::Cubic([start, b.add(start.sub(a)), c.add(end.sub(d)), end])
+            }
+        }
+    }
+
+    /// Returns the pairs of `t` for each segment at which they meet, where intersections
+    /// within the tolerance of each other are treated as one.
+    ///
+    /// Overlapping collinear lines meet at the end-points of the overlap.
+    pub fn intersections(&self, other: &Self, tolerance: f64) -> Vec<(f64, f64)> {
+        let mut result = vec![];
+        if let (Self::Line([a, b]), Self::Line([c, d])) = (self, other) {
+            line_intersections([*a, *b], [*c, *d], tolerance, &mut result);
+        } else {
+            intersections_recursive(
+                (*self, 0.0, 1.0),
+                (*other, 0.0, 1.0),
+                tolerance,
+                MAX_INTERSECTION_DEPTH,
+                &mut result,
+            );
+        }
+
+        let mut deduped: Vec<(f64, f64)> = Vec::with_capacity(result.len());
+        for (t, u) in result {
+            let point = self.point(t);
+            if deduped
+                .iter()
+                .all(|(other_t, _)| self.point(*other_t).sub(point).length() > tolerance * 4.0)
+            {
+                deduped.push((t, u));
+            }
+        }
+        deduped
+    }
+
+    /// Returns the sum of the directions in which the segment crosses the ray extending to the
+    /// right of the point, where downward crossings are positive.
+    pub fn winding(&self, Point([x, y]): Point) -> i32 {
+        let crossing = |t0: f64, t1: f64| -> i32 {
+            let (y0, y1) = (self.point(t0).0[1], self.point(t1).0[1]);
+            let direction = if y0 <= y && y < y1 {
+                1
+            } else if y1 <= y && y < y0 {
+                -1
+            } else {
+                return 0;
+            };
+            // NOTE: Within the interval, `y` is monotonic, so the crossing can be bisected
+            let (mut low, mut high) = (t0, t1);
+            for _ in 0..64 {
+                let mid = f64::midpoint(low, high);
+                if (self.point(mid).0[1] < y) == (y0 < y1) {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            if self.point(f64::midpoint(low, high)).0[0] > x {
+                direction
+            } else {
+                0
+            }
+        };
+        match *self {
+            Self::Line(_) => crossing(0.0, 1.0),
+            Self::Cubic([a, b, c, d]) => {
+                let [a, b, c, d] = [a.0[1], b.0[1], c.0[1], d.0[1]];
+                let mut ts: Vec<f64> =
+                    quadratic_roots(-a + 3.0 * b - 3.0 * c + d, 2.0 * (a - 2.0 * b + c), b - a)
+                        .collect();
+                ts.sort_by(f64::total_cmp);
+                ts.insert(0, 0.0);
+                ts.push(1.0);
+                ts.windows(2).map(|w| crossing(w[0], w[1])).sum()
+            }
+        }
+    }
+}
+
+/// The maximum depth of subdivisions when searching for intersections of curves
+const MAX_INTERSECTION_DEPTH: usize = 96;
+
+#[allow(clippy::many_single_char_names)]
+fn line_intersections(
+    [a, b]: [Point; 2],
+    [c, d]: [Point; 2],
+    tolerance: f64,
+    result: &mut Vec<(f64, f64)>,
+) {
+    let cross = |Point([x0, y0]): Point, Point([x1, y1]): Point| x0 * y1 - x1 * y0;
+    let (ab, cd, ac) = (b.sub(a), d.sub(c), c.sub(a));
+    let denominator = cross(ab, cd);
+    let (ab_length, cd_length) = (ab.length(), cd.length());
+    if ab_length <= EPSILON || cd_length <= EPSILON {
+        return;
+    }
+    if denominator.abs() > EPSILON * ab_length * cd_length {
+        let t = cross(ac, cd) / denominator;
+        let u = cross(ac, ab) / denominator;
+        let (t_margin, u_margin) = (tolerance / ab_length, tolerance / cd_length);
+        if (-t_margin..=1.0 + t_margin).contains(&t) && (-u_margin..=1.0 + u_margin).contains(&u) {
+            result.push((t.clamp(0.0, 1.0), u.clamp(0.0, 1.0)));
+        }
+        return;
+    }
+    if (cross(ab, ac) / ab_length).abs() > tolerance {
+        return;
+    }
+    // NOTE: Collinear, so the overlap is bound by end-points within the other line
+    let project = |point: Point, from: Point, vector: Point, length: f64| {
+        let t = point.sub(from).dot(&vector) / (length * length);
+        let margin = tolerance / length;
+        (-margin..=1.0 + margin)
+            .contains(&t)
+            .then_some(t.clamp(0.0, 1.0))
+    };
+    for (u, point) in [(0.0, c), (1.0, d)] {
+        if let Some(t) = project(point, a, ab, ab_length) {
+            result.push((t, u));
+        }
+    }
+    for (t, point) in [(0.0, a), (1.0, b)] {
+        if let Some(u) = project(point, c, cd, cd_length) {
+            result.push((t, u));
+        }
+    }
+}
+
+/// Finds intersections by subdividing both segments, discarding parts whose bounds don't
+/// overlap, until they're within the tolerance
+#[allow(clippy::similar_names)]
+fn intersections_recursive(
+    (a, a_from, a_to): (Segment, f64, f64),
+    (b, b_from, b_to): (Segment, f64, f64),
+    tolerance: f64,
+    depth: usize,
+    result: &mut Vec<(f64, f64)>,
+) {
+    let (a_min, a_max) = a.bounds();
+    let (b_min, b_max) = b.bounds();
+    if a_min.0[0] > b_max.0[0] + tolerance
+        || b_min.0[0] > a_max.0[0] + tolerance
+        || a_min.0[1] > b_max.0[1] + tolerance
+        || b_min.0[1] > a_max.0[1] + tolerance
+    {
+        return;
+    }
+    let size = |min: Point, max: Point| (max.0[0] - min.0[0]).max(max.0[1] - min.0[1]);
+    let (a_size, b_size) = (size(a_min, a_max), size(b_min, b_max));
+    if depth == 0 || (a_size <= tolerance && b_size <= tolerance) {
+        result.push((f64::midpoint(a_from, a_to), f64::midpoint(b_from, b_to)));
+        return;
+    }
+    // NOTE: Overlapping curves would otherwise produce an intersection for every subdivision
+    if result.len() > 64 {
+        return;
+    }
+    let a_mid = f64::midpoint(a_from, a_to);
+    let b_mid = f64::midpoint(b_from, b_to);
+    let (a_left, a_right) = a.split(0.5);
+    let (b_left, b_right) = b.split(0.5);
+    if a_size >= b_size {
+        for (a, from, to) in [(a_left, a_from, a_mid), (a_right, a_mid, a_to)] {
+            intersections_recursive(
+                (a, from, to),
+                (b, b_from, b_to),
+                tolerance,
+                depth - 1,
+                result,
+            );
+        }
+    } else {
+        for (b, from, to) in [(b_left, b_from, b_mid), (b_right, b_mid, b_to)] {
+            intersections_recursive(
+                (a, a_from, a_to),
+                (b, from, to),
+                tolerance,
+                depth - 1,
+                result,
+            );
+        }
+    }
+}
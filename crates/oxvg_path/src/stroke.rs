@@ -0,0 +1,500 @@
+//! Outlining of a path's stroke into a path that can be filled.
+//!
+//! Use the [outline] function to get a path which, when filled with the `nonzero` fill-rule,
+//! covers the same area as the stroke of the given path.
+//!
+//! The process of outlining a path is as follows:
+//! 1. Convert all commands to absolute lines and cubic beziers, split into subpaths
+//! 2. Split the subpaths into dashes, if a dash-array is given
+//! 3. Offset each segment to either side of the subpath, connecting them with joins
+//! 4. Connect each side of an open subpath with caps
+use std::f64::consts::FRAC_PI_2;
+
+use crate::{
+    command::Data,
+    geometry::Point,
+    segment::{normal, normalise, subpaths, Contour, Segment, EPSILON},
+    Path,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// The shape at the end of open subpaths, as with the `stroke-linecap` property
+pub enum LineCap {
+    #[default]
+    /// The stroke ends flat at the end of the subpath
+    Butt,
+    /// The stroke ends with a half-circle
+    Round,
+    /// The stroke ends with a half-square, extending past the end of the subpath
+    Square,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// The shape at the corners of a subpath, as with the `stroke-linejoin` property
+pub enum LineJoin {
+    #[default]
+    /// The outer edges of the stroke are extended to meet at a point, falling back to
+    /// [`LineJoin::Bevel`] when the miter-limit is exceeded
+    Miter,
+    /// The corner is rounded off with a circular arc
+    Round,
+    /// The corner is cut off with a straight line
+    Bevel,
+}
+
+#[derive(Debug, Clone)]
+/// The stroke properties used when outlining a path
+pub struct Options {
+    /// The width of the stroke, as with `stroke-width`
+    pub width: f64,
+    /// The shape at the end of open subpaths, as with `stroke-linecap`
+    pub line_cap: LineCap,
+    /// The shape at the corners of subpaths, as with `stroke-linejoin`
+    pub line_join: LineJoin,
+    /// The limit of the ratio of a miter's length to the stroke width, as with
+    /// `stroke-miterlimit`
+    pub miter_limit: f64,
+    /// The lengths of alternating dashes and gaps, as with `stroke-dasharray`
+    pub dash_array: Vec<f64>,
+    /// The distance into the dash pattern to start at, as with `stroke-dashoffset`
+    pub dash_offset: f64,
+    /// The maximum distance the outline of a curve may stray from it's true offset
+    pub tolerance: f64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
+            miter_limit: 4.0,
+            dash_array: vec![],
+            dash_offset: 0.0,
+            tolerance: 0.01,
+        }
+    }
+}
+
+/// Returns a path covering the area of the given path's stroke.
+///
+/// The path returned only uses absolute commands and is intended to be filled using the
+/// `nonzero` fill-rule, as the outlines of overlapping segments and inner joins may intersect.
+///
+/// # Examples
+///
+/// ```
+/// use oxvg_path::Path;
+/// use oxvg_path::stroke::{outline, Options};
+///
+/// let path = Path::parse("M 0 0 H 10").unwrap();
+/// let options = Options { width: 2.0, ..Options::default() };
+///
+/// let path = outline(&path, &options);
+/// assert_eq!(&path.to_string(), "M0 1L10 1L10-1L0-1Z");
+/// ```
+pub fn outline(path: &Path, options: &Options) -> Path {
+    let mut outline = Outline::default();
+    if options.width <= 0.0 || !options.width.is_finite() {
+        return Path(vec![]);
+    }
+    let half_width = options.width / 2.0;
+    let dash_array = dash_array(&options.dash_array);
+
+    for subpath in subpaths(path, FRAC_PI_2) {
+        let contours = match &dash_array {
+            Some(dash_array) if !subpath.segments.is_empty() => {
+                dash(subpath, dash_array, options.dash_offset, options.tolerance)
+            }
+            _ => vec![subpath],
+        };
+        for contour in contours {
+            outline.contour(&contour, half_width, options);
+        }
+    }
+    Path(outline.data)
+}
+
+#[derive(Default)]
+/// A builder for the commands of the outline
+struct Outline {
+    data: Vec<Data>,
+    start: Point,
+    current: Point,
+}
+
+/// Returns the normalised form of the dash array, or `None` if it shouldn't be dashed
+fn dash_array(dash_array: &[f64]) -> Option<Vec<f64>> {
+    if dash_array.is_empty() || dash_array.iter().any(|d| *d < 0.0 || !d.is_finite()) {
+        return None;
+    }
+    if dash_array.iter().sum::<f64>() <= EPSILON {
+        return None;
+    }
+    let mut dash_array = dash_array.to_vec();
+    if dash_array.len() % 2 == 1 {
+        dash_array.extend_from_within(..);
+    }
+    Some(dash_array)
+}
+
+/// Splits a contour into dashes, following the dash array and offset.
+fn dash(contour: Contour, dash_array: &[f64], offset: f64, tolerance: f64) -> Vec<Contour> {
+    let total: f64 = dash_array.iter().sum();
+    let mut index = 0;
+    let mut phase = offset.rem_euclid(total);
+    while phase > 0.0 && phase >= dash_array[index] {
+        phase -= dash_array[index];
+        index = (index + 1) % dash_array.len();
+    }
+    let mut remaining = dash_array[index] - phase;
+    let starts_on = index % 2 == 0;
+
+    let mut result = vec![];
+    let mut current: Option<Contour> = None;
+    for segment in &contour.segments {
+        let length = segment.length(tolerance);
+        let mut position = 0.0;
+        loop {
+            if index % 2 == 0 && current.is_none() {
+                let (point, direction) = segment.point_at_length(position, length, tolerance);
+                current = Some(Contour {
+                    drawn: true,
+                    direction,
+                    ..Contour::new(point)
+                });
+            }
+            let end = position + remaining;
+            if end > length {
+                if let Some(current) = current.as_mut() {
+                    current.push(segment.slice(position, length, length, tolerance));
+                }
+                remaining = end - length;
+                break;
+            }
+            if let Some(mut dash) = current.take() {
+                dash.push(segment.slice(position, end, length, tolerance));
+                result.push(dash);
+            }
+            position = end;
+            index = (index + 1) % dash_array.len();
+            remaining = dash_array[index];
+        }
+    }
+
+    if let Some(mut last) = current {
+        if !contour.closed || !starts_on {
+            result.push(last);
+        } else if result.is_empty() {
+            // NOTE: The dash covers the entire contour, so it should still be joined at the start
+            return vec![contour];
+        } else {
+            let first = result.remove(0);
+            last.segments.extend(first.segments);
+            result.push(last);
+        }
+    }
+    result
+}
+
+#[allow(clippy::many_single_char_names)]
+impl Segment {
+    /// Pushes segments approximating the offset of the segment by `distance` to it's left.
+    fn offset(&self, distance: f64, tolerance: f64, depth: usize, output: &mut Vec<Self>) {
+        let (Some(start_tangent), Some(end_tangent)) = (self.start_tangent(), self.end_tangent())
+        else {
+            return;
+        };
+        let start = self.start().add(normal(start_tangent).scale(distance));
+        let end = self.end().add(normal(end_tangent).scale(distance));
+        let Self::Cubic([_, b, c, _]) = *self else {
+            output.push(Self::Line([start, end]));
+            return;
+        };
+
+        // Tiller-Hanson: Offset each edge of the control polygon, using where the edges meet as
+        // the new control points.
+        let middle = c.sub(b);
+        let (control_1, control_2) = if middle.length() > EPSILON {
+            let middle_offset = b.add(normal(normalise(middle)).scale(distance));
+            let limit = self.start().sub(self.end()).length() + 4.0 * distance.abs();
+            let intersect = |point: Point, tangent: Point, fallback: Point| {
+                line_intersection(point, tangent, middle_offset, middle)
+                    .filter(|p| p.sub(fallback).length() <= limit)
+                    .unwrap_or(fallback)
+            };
+            (
+                intersect(
+                    start,
+                    start_tangent,
+                    b.add(normal(start_tangent).scale(distance)),
+                ),
+                intersect(end, end_tangent, c.add(normal(end_tangent).scale(distance))),
+            )
+        } else {
+            (
+                b.add(normal(start_tangent).scale(distance)),
+                c.add(normal(end_tangent).scale(distance)),
+            )
+        };
+        let approximation = Self::Cubic([start, control_1, control_2, end]);
+
+        let is_within_tolerance = [0.25, 0.5, 0.75].into_iter().all(|t| {
+            let Some(tangent) = self.tangent(t) else {
+                return true;
+            };
+            let expected = self.point(t).add(normal(tangent).scale(distance));
+            approximation.point(t).sub(expected).length() <= tolerance
+        });
+        if depth == 0 || is_within_tolerance {
+            output.push(approximation);
+            return;
+        }
+        let (left, right) = self.split(0.5);
+        left.offset(distance, tolerance, depth - 1, output);
+        right.offset(distance, tolerance, depth - 1, output);
+    }
+}
+
+/// Returns where the line through `a` in the direction of `u` crosses the line through `b` in the
+/// direction of `v`
+#[allow(clippy::many_single_char_names)]
+fn line_intersection(a: Point, u: Point, b: Point, v: Point) -> Option<Point> {
+    let denominator = u.0[0] * v.0[1] - u.0[1] * v.0[0];
+    if denominator.abs() <= EPSILON {
+        return None;
+    }
+    let difference = b.sub(a);
+    let t = (difference.0[0] * v.0[1] - difference.0[1] * v.0[0]) / denominator;
+    Some(a.add(u.scale(t)))
+}
+
+impl Outline {
+    fn move_to(&mut self, point: Point) {
+        self.data.push(Data::MoveTo(point.0));
+        self.start = point;
+        self.current = point;
+    }
+
+    fn line_to(&mut self, point: Point) {
+        if point.sub(self.current).length() <= EPSILON {
+            return;
+        }
+        self.data.push(Data::LineTo(point.0));
+        self.current = point;
+    }
+
+    fn arc_to(&mut self, radius: f64, point: Point) {
+        self.data.push(Data::ArcTo([
+            radius, radius, 0.0, 0.0, 0.0, point.0[0], point.0[1],
+        ]));
+        self.current = point;
+    }
+
+    fn segment(&mut self, segment: &Segment) {
+        if segment.start().sub(self.current).length() > EPSILON {
+            self.line_to(segment.start());
+        }
+        match segment {
+            Segment::Line([_, end]) => self.line_to(*end),
+            Segment::Cubic([_, b, c, d]) => {
+                self.data.push(Data::CubicBezierTo([
+                    b.0[0], b.0[1], c.0[0], c.0[1], d.0[0], d.0[1],
+                ]));
+                self.current = *d;
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if matches!(self.data.last(), Some(Data::LineTo(point)) if Point(*point).sub(self.start).length() <= EPSILON)
+        {
+            self.data.pop();
+        }
+        self.data.push(Data::ClosePath);
+        self.current = self.start;
+    }
+
+    fn contour(&mut self, contour: &Contour, distance: f64, options: &Options) {
+        let Some(first) = contour.segments.first() else {
+            if contour.drawn && options.line_cap != LineCap::Butt {
+                let direction = contour.direction;
+                self.move_to(contour.start.add(normal(direction).scale(distance)));
+                self.cap(contour.start, direction, distance, options);
+                self.cap(contour.start, direction.minus(), distance, options);
+                self.close();
+            }
+            return;
+        };
+        let reversed = contour.reversed();
+        let start_tangent = first.start_tangent().unwrap_or(contour.direction);
+        self.move_to(first.start().add(normal(start_tangent).scale(distance)));
+        self.side(&contour.segments, contour.closed, distance, options);
+        if contour.closed {
+            self.close();
+            let start_tangent = reversed[0].start_tangent().unwrap_or(contour.direction);
+            self.move_to(
+                reversed[0]
+                    .start()
+                    .add(normal(start_tangent).scale(distance)),
+            );
+        } else {
+            let last = contour.segments.last().unwrap_or(first);
+            let end_tangent = last.end_tangent().unwrap_or(contour.direction);
+            self.cap(last.end(), end_tangent, distance, options);
+        }
+        self.side(&reversed, contour.closed, distance, options);
+        if !contour.closed {
+            let last = reversed.last().unwrap_or(first);
+            let end_tangent = last.end_tangent().unwrap_or(contour.direction.minus());
+            self.cap(last.end(), end_tangent, distance, options);
+        }
+        self.close();
+    }
+
+    /// Outlines the left side of the segments, from the offset start of the first segment
+    fn side(&mut self, segments: &[Segment], closed: bool, distance: f64, options: &Options) {
+        let mut offset = vec![];
+        for (i, segment) in segments.iter().enumerate() {
+            offset.clear();
+            segment.offset(distance, options.tolerance, 8, &mut offset);
+            for segment in &offset {
+                self.segment(segment);
+            }
+
+            let next = match segments.get(i + 1) {
+                Some(next) => next,
+                None if closed => &segments[0],
+                None => break,
+            };
+            if let (Some(incoming), Some(outgoing)) = (segment.end_tangent(), next.start_tangent())
+            {
+                self.join(segment.end(), incoming, outgoing, distance, options);
+            }
+        }
+    }
+
+    fn join(
+        &mut self,
+        vertex: Point,
+        incoming: Point,
+        outgoing: Point,
+        distance: f64,
+        options: &Options,
+    ) {
+        let to = vertex.add(normal(outgoing).scale(distance));
+        if to.sub(self.current).length() <= EPSILON {
+            return;
+        }
+        let cross = incoming.0[0] * outgoing.0[1] - incoming.0[1] * outgoing.0[0];
+        let dot = incoming.dot(&outgoing);
+        if cross > 0.0 && dot > EPSILON - 1.0 {
+            // NOTE: The inner side of the corner pivots through the vertex, which is covered by
+            // the outline when using the `nonzero` fill-rule.
+            self.line_to(vertex);
+            self.line_to(to);
+            return;
+        }
+        match options.line_join {
+            LineJoin::Bevel => {}
+            LineJoin::Round => {
+                self.arc_to(distance, to);
+                return;
+            }
+            LineJoin::Miter => {
+                let cos_half_angle = f64::midpoint(1.0, dot).sqrt();
+                if cos_half_angle > EPSILON && 1.0 / cos_half_angle <= options.miter_limit {
+                    let tip = normal(incoming)
+                        .add(normal(outgoing))
+                        .scale(distance / (1.0 + dot));
+                    self.line_to(vertex.add(tip));
+                }
+            }
+        }
+        self.line_to(to);
+    }
+
+    fn cap(&mut self, point: Point, tangent: Point, distance: f64, options: &Options) {
+        let side = normal(tangent).scale(distance);
+        let to = point.sub(side);
+        match options.line_cap {
+            LineCap::Butt => self.line_to(to),
+            LineCap::Round => self.arc_to(distance, to),
+            LineCap::Square => {
+                let extension = tangent.scale(distance);
+                self.line_to(point.add(side).add(extension));
+                self.line_to(to.add(extension));
+                self.line_to(to);
+            }
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_outline() {
+    let stroke = |definition: &str, options: Options| {
+        self::outline(&Path::parse(definition).unwrap(), &options).to_string()
+    };
+
+    // Should outline open subpaths with caps
+    insta::assert_snapshot!(stroke(
+        "M 0 0 H 10 V 10",
+        Options {
+            width: 2.0,
+            line_cap: LineCap::Square,
+            ..Options::default()
+        }
+    ));
+
+    // Should outline closed subpaths without caps
+    insta::assert_snapshot!(stroke(
+        "M 0 0 H 10 V 10 H 0 Z",
+        Options {
+            width: 2.0,
+            line_join: LineJoin::Round,
+            ..Options::default()
+        }
+    ));
+
+    // Should bevel when miter-limit is exceeded
+    insta::assert_snapshot!(stroke(
+        "M 0 0 L 10 1 L 0 2",
+        Options {
+            width: 2.0,
+            ..Options::default()
+        }
+    ));
+
+    // Should split into dashes
+    insta::assert_snapshot!(stroke(
+        "M 0 0 H 10",
+        Options {
+            width: 2.0,
+            dash_array: vec![4.0, 2.0],
+            dash_offset: 1.0,
+            ..Options::default()
+        }
+    ));
+
+    // Should draw dots for zero-length subpaths and dashes
+    insta::assert_snapshot!(stroke(
+        "M 0 0 Z M 0 10 H 10",
+        Options {
+            width: 2.0,
+            line_cap: LineCap::Round,
+            dash_array: vec![0.0, 5.0],
+            ..Options::default()
+        }
+    ));
+
+    // Should outline curves within tolerance
+    insta::assert_snapshot!(stroke(
+        "M 0 0 C 0 10 10 10 10 0",
+        Options {
+            width: 2.0,
+            tolerance: 0.1,
+            ..Options::default()
+        }
+    ));
+}
@@ -0,0 +1,276 @@
+//! Splitting paths into subpaths, and querying or reversing the direction they're drawn in.
+use crate::{command::Data, convert, geometry::Point, segment::EPSILON, Path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The direction a subpath is drawn in, within the y-down coordinates of SVG
+pub enum Direction {
+    /// The subpath is drawn clockwise, as seen on screen
+    Clockwise,
+    /// The subpath is drawn counter-clockwise, as seen on screen
+    CounterClockwise,
+}
+
+impl Path {
+    /// Returns each subpath of the path, in order.
+    ///
+    /// Each subpath starts with an absolute move, including those following a close path without
+    /// a move of their own, so that they can be used independently of the rest of the path. The
+    /// rest of the commands are kept as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let path = Path::parse("M 0 0 H 10 m 5 5 h 5 z l 1 1").unwrap();
+    /// let subpaths: Vec<_> = path.subpaths().map(|subpath| subpath.to_string()).collect();
+    /// assert_eq!(subpaths, vec!["M0 0H10", "M15 5h5Z", "M15 5l1 1"]);
+    /// ```
+    pub fn subpaths(&self) -> impl DoubleEndedIterator<Item = Self> {
+        let positions = convert::relative(self).0;
+        let mut result = vec![];
+        let mut current: Vec<Data> = vec![];
+        for (command, position) in self.0.iter().zip(positions) {
+            match command.as_explicit() {
+                Data::MoveTo(_) | Data::MoveBy(_) => {
+                    if !current.is_empty() {
+                        result.push(Self(std::mem::take(&mut current)));
+                    }
+                    current.push(Data::MoveTo(position.end.0));
+                }
+                explicit => {
+                    if current.is_empty() {
+                        current.push(Data::MoveTo(position.start.0));
+                    }
+                    // NOTE: An implicit command following a move would be read as part of the
+                    // move, which may no longer be relative
+                    if current.len() == 1 {
+                        current.push(explicit.clone());
+                    } else {
+                        current.push(command.clone());
+                    }
+                    if matches!(explicit, Data::ClosePath) {
+                        result.push(Self(std::mem::take(&mut current)));
+                    }
+                }
+            }
+        }
+        if !current.is_empty() {
+            result.push(Self(current));
+        }
+        result.into_iter()
+    }
+
+    /// Returns a path drawing each of the subpaths, in order.
+    ///
+    /// Each subpath should start with an absolute move, as those from [`Path::subpaths`] do,
+    /// otherwise it will continue from the end of the previous subpath.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let path = Path::parse("M 0 0 H 10 M 20 0 H 30").unwrap();
+    /// let path = Path::from_subpaths(path.subpaths().rev());
+    /// assert_eq!(&path.to_string(), "M20 0H30M0 0H10");
+    /// ```
+    pub fn from_subpaths<I: IntoIterator<Item = Self>>(subpaths: I) -> Self {
+        Self(subpaths.into_iter().flat_map(|subpath| subpath.0).collect())
+    }
+
+    /// Returns a path drawing the same shape, where each subpath is drawn in the opposite
+    /// direction.
+    ///
+    /// The order of subpaths is kept, and the resulting commands are absolute. Smooth curves
+    /// are written out in full, since the curves they'd be reflected from have changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::Path;
+    ///
+    /// let path = Path::parse("M 0 0 H 10 V 10 H 0 Z").unwrap();
+    /// assert_eq!(&path.reverse().to_string(), "M0 0L0 10H10V0Z");
+    /// ```
+    #[must_use]
+    pub fn reverse(&self) -> Self {
+        Self::from_subpaths(
+            self.subpaths()
+                .map(|subpath| Self(reverse_subpath(&subpath))),
+        )
+    }
+
+    /// Returns the direction the path is drawn in, by the sign of it's area, or `None` if it
+    /// doesn't cover any area.
+    ///
+    /// For paths with multiple subpaths, this is the direction of the subpaths which cover the
+    /// most area. See [`Path::subpath_directions`] for the direction of each subpath.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::{subpath::Direction, Path};
+    ///
+    /// let path = Path::parse("M 0 0 H 10 V 10 H 0 Z").unwrap();
+    /// assert_eq!(path.direction(), Some(Direction::Clockwise));
+    /// assert_eq!(path.reverse().direction(), Some(Direction::CounterClockwise));
+    /// ```
+    pub fn direction(&self) -> Option<Direction> {
+        let area = self.area();
+        if area > EPSILON {
+            Some(Direction::Clockwise)
+        } else if area < -EPSILON {
+            Some(Direction::CounterClockwise)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the direction each subpath is drawn in, in the order of [`Path::subpaths`].
+    pub fn subpath_directions(&self) -> Vec<Option<Direction>> {
+        self.subpaths().map(|subpath| subpath.direction()).collect()
+    }
+}
+
+/// Returns the commands drawing the subpath in reverse, where the subpath starts with an
+/// absolute move and has no other moves
+fn reverse_subpath(subpath: &Path) -> Vec<Data> {
+    let positions = convert::relative(subpath).0;
+    let Some(first) = positions.first().map(|position| position.end) else {
+        return vec![];
+    };
+
+    let mut closed = false;
+    let mut end = first;
+    let mut segments: Vec<(Point, Data)> = vec![];
+    let mut prev_cubic_control: Option<Point> = None;
+    let mut prev_quadratic_control: Option<Point> = None;
+    for position in &positions {
+        let mut cubic_control = None;
+        let mut quadratic_control = None;
+        let command = match convert::to_absolute(position) {
+            Data::MoveTo(_) => continue,
+            Data::ClosePath => {
+                closed = true;
+                continue;
+            }
+            Data::SmoothBezierTo([x2, y2, x, y]) => {
+                let start = position.start;
+                let [x1, y1] = prev_cubic_control.map_or(start, |c| c.reflect(start)).0;
+                Data::CubicBezierTo([x1, y1, x2, y2, x, y])
+            }
+            Data::SmoothQuadraticBezierTo([x, y]) => {
+                let start = position.start;
+                let [x1, y1] = prev_quadratic_control.map_or(start, |c| c.reflect(start)).0;
+                Data::QuadraticBezierTo([x1, y1, x, y])
+            }
+            command => command,
+        };
+        match command {
+            Data::CubicBezierTo([_, _, x2, y2, _, _]) => cubic_control = Some(Point([x2, y2])),
+            Data::QuadraticBezierTo([x1, y1, _, _]) => quadratic_control = Some(Point([x1, y1])),
+            _ => {}
+        }
+        prev_cubic_control = cubic_control;
+        prev_quadratic_control = quadratic_control;
+        end = position.end;
+        segments.push((position.start, command));
+    }
+
+    let mut result = vec![Data::MoveTo(if closed { first.0 } else { end.0 })];
+    // NOTE: When closed, the closing line is drawn first and the line back to the start is
+    // drawn by closing
+    if closed && end.sub(first).length() > EPSILON {
+        result.push(Data::LineTo(end.0));
+    }
+    for (index, (Point([x, y]), command)) in segments.into_iter().enumerate().rev() {
+        result.push(match command {
+            Data::LineTo(_) | Data::HorizontalLineTo(_) | Data::VerticalLineTo(_)
+                if closed && index == 0 =>
+            {
+                continue;
+            }
+            Data::HorizontalLineTo(_) => Data::HorizontalLineTo([x]),
+            Data::VerticalLineTo(_) => Data::VerticalLineTo([y]),
+            Data::CubicBezierTo([x1, y1, x2, y2, _, _]) => {
+                Data::CubicBezierTo([x2, y2, x1, y1, x, y])
+            }
+            Data::QuadraticBezierTo([x1, y1, _, _]) => Data::QuadraticBezierTo([x1, y1, x, y]),
+            Data::ArcTo([rx, ry, angle, large_arc, sweep, _, _]) => {
+                let sweep = if sweep == 0.0 { 1.0 } else { 0.0 };
+                Data::ArcTo([rx, ry, angle, large_arc, sweep, x, y])
+            }
+            _ => Data::LineTo([x, y]),
+        });
+    }
+    if closed {
+        result.push(Data::ClosePath);
+    }
+    result
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_subpath() {
+    let path = |definition: &str| Path::parse(definition).unwrap();
+    let subpaths = |definition: &str| {
+        path(definition)
+            .subpaths()
+            .map(|subpath| subpath.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Should start each subpath with an absolute move
+    assert_eq!(
+        subpaths("m 10 10 5 5 m 10 0 l 5 5 z m 0 10 z"),
+        vec!["M10 10l5 5", "M25 15l5 5Z", "M25 25Z"]
+    );
+    assert_eq!(
+        subpaths("M 0 0 M 10 10 L 20 20"),
+        vec!["M0 0", "M10 10L20 20"]
+    );
+    // Should join subpaths back together
+    let original = path("M 0 0 H 10 m 5 5 h 5 z l 1 1 Q 5 5 10 10 T 20 20");
+    assert_eq!(
+        Path::from_subpaths(original.subpaths()).to_string(),
+        "M0 0H10M15 5h5ZM15 5l1 1Q5 5 10 10T20 20"
+    );
+
+    // Should reverse open and closed subpaths
+    assert_eq!(
+        &path("M 0 0 C 0 10 10 10 10 0 S 20 -10 20 0 A 5 5 0 0 1 30 0")
+            .reverse()
+            .to_string(),
+        "M30 0A5 5 0 0 0 20 0C20-10 10-10 10 0C10 10 0 10 0 0"
+    );
+    assert_eq!(
+        &path("M 0 0 Q 5 10 10 0 L 10 10 L 0 0 Z m 20 0 h 10 l 0 10 z")
+            .reverse()
+            .to_string(),
+        "M0 0L10 10L10 0Q5 10 0 0ZM20 0L30 10L30 0Z"
+    );
+    assert_eq!(
+        &path("M 0 0 Q 10 0 10 10 T 20 20").reverse().to_string(),
+        "M20 20Q10 20 10 10Q10 0 0 0"
+    );
+
+    // Should find the direction of each subpath
+    let rings = path("M 0 0 H 10 V 10 H 0 Z M 2 2 V 8 H 8 V 2 Z M 0 0 H 10");
+    assert_eq!(
+        rings.subpath_directions(),
+        vec![
+            Some(Direction::Clockwise),
+            Some(Direction::CounterClockwise),
+            None
+        ]
+    );
+    assert_eq!(
+        rings.reverse().subpath_directions(),
+        vec![
+            Some(Direction::CounterClockwise),
+            Some(Direction::Clockwise),
+            None
+        ]
+    );
+}
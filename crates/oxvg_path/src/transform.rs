@@ -0,0 +1,246 @@
+//! Applying affine transformations to paths.
+use crate::{
+    command::Data,
+    geometry::{Matrix, Point},
+    Path,
+};
+
+impl Path {
+    /// Applies the transformation to each command of the path, such that the path draws the
+    /// same shape as it would with the equivalent `transform` attribute.
+    ///
+    /// Horizontal and vertical lines are converted to lines, since they may no longer be
+    /// axis-aligned, and the radii, rotation, and sweep of arcs are adjusted to match the
+    /// transformed ellipse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxvg_path::{geometry::Matrix, Path};
+    ///
+    /// let mut path = Path::parse("M 10 10 H 20 A 5 5 0 0 1 30 10").unwrap();
+    /// path.transform(&Matrix([2.0, 0.0, 0.0, 1.0, 5.0, 0.0]));
+    /// assert_eq!(&path.to_string(), "M25 10L45 10A10 5 0 0 1 65 10");
+    /// ```
+    pub fn transform(&mut self, matrix: &Matrix) {
+        log::debug!("applying matrix: {matrix:?}");
+        let mut start = Point::default();
+        let mut cursor = Point::default();
+        if let Some(data) = self.0.get_mut(0) {
+            if let Data::MoveBy(args) = data {
+                *data = Data::MoveTo(*args);
+            }
+        }
+
+        let absolute = |[x, y]: [f64; 2]| matrix.apply(Point([x, y])).0;
+        let relative = |[x, y]: [f64; 2]| matrix.apply_vector(Point([x, y])).0;
+        self.0.iter_mut().for_each(|data| {
+            if let Data::Implicit(_) = data {
+                *data = data.as_explicit().clone();
+            }
+            match data {
+                Data::HorizontalLineTo([x]) => *data = Data::LineTo([*x, cursor.0[1]]),
+                Data::HorizontalLineBy([x]) => *data = Data::LineBy([*x, 0.0]),
+                Data::VerticalLineTo([y]) => *data = Data::LineTo([cursor.0[0], *y]),
+                Data::VerticalLineBy([y]) => *data = Data::LineBy([0.0, *y]),
+                _ => {}
+            }
+            match data {
+                Data::MoveTo(args) => {
+                    cursor = Point(*args);
+                    start = cursor;
+                    *args = absolute(*args);
+                }
+                Data::MoveBy(args) => {
+                    cursor = cursor.add(Point(*args));
+                    start = cursor;
+                    *args = relative(*args);
+                }
+                Data::LineTo(args) | Data::SmoothQuadraticBezierTo(args) => {
+                    cursor = Point(*args);
+                    *args = absolute(*args);
+                }
+                Data::LineBy(args) | Data::SmoothQuadraticBezierBy(args) => {
+                    cursor = cursor.add(Point(*args));
+                    *args = relative(*args);
+                }
+                Data::CubicBezierTo([x1, y1, x2, y2, x, y]) => {
+                    cursor = Point([*x, *y]);
+                    [*x1, *y1] = absolute([*x1, *y1]);
+                    [*x2, *y2] = absolute([*x2, *y2]);
+                    [*x, *y] = absolute([*x, *y]);
+                }
+                Data::CubicBezierBy([x1, y1, x2, y2, x, y]) => {
+                    cursor = cursor.add(Point([*x, *y]));
+                    [*x1, *y1] = relative([*x1, *y1]);
+                    [*x2, *y2] = relative([*x2, *y2]);
+                    [*x, *y] = relative([*x, *y]);
+                }
+                Data::SmoothBezierTo([x1, y1, x, y]) | Data::QuadraticBezierTo([x1, y1, x, y]) => {
+                    cursor = Point([*x, *y]);
+                    [*x1, *y1] = absolute([*x1, *y1]);
+                    [*x, *y] = absolute([*x, *y]);
+                }
+                Data::SmoothBezierBy([x1, y1, x, y]) | Data::QuadraticBezierBy([x1, y1, x, y]) => {
+                    cursor = cursor.add(Point([*x, *y]));
+                    [*x1, *y1] = relative([*x1, *y1]);
+                    [*x, *y] = relative([*x, *y]);
+                }
+                Data::ArcTo(args) => {
+                    transform_arc(cursor, args, matrix);
+                    cursor = Point([args[5], args[6]]);
+                    normalise_arc_rotation(args);
+                    [args[5], args[6]] = absolute([args[5], args[6]]);
+                }
+                Data::ArcBy(args) => {
+                    transform_arc(Point::default(), args, matrix);
+                    cursor = cursor.add(Point([args[5], args[6]]));
+                    normalise_arc_rotation(args);
+                    [args[5], args[6]] = relative([args[5], args[6]]);
+                }
+                Data::ClosePath => cursor = start,
+                Data::HorizontalLineBy(_)
+                | Data::HorizontalLineTo(_)
+                | Data::VerticalLineBy(_)
+                | Data::VerticalLineTo(_)
+                | Data::Implicit(_) => {
+                    unreachable!("Reached destroyed command type")
+                }
+            }
+        });
+    }
+}
+
+/// Updates the radii, rotation, and sweep of the arc to that of the transformed ellipse
+#[allow(clippy::manual_midpoint)]
+fn transform_arc(cursor: Point, args: &mut [f64; 7], matrix: &Matrix) {
+    let x = args[5] - cursor.0[0];
+    let y = args[6] - cursor.0[1];
+    let [a, b, cos, sin] = rotated_ellipse(args, [x, y]);
+
+    let ellipse = Matrix([a * cos, a * sin, -b * sin, b * cos, 0.0, 0.0]);
+    let new_matrix = matrix.multiply(&ellipse).0;
+    let last_col = new_matrix[2] * new_matrix[2] + new_matrix[3] * new_matrix[3];
+    let square_sum = new_matrix[0] * new_matrix[0] + new_matrix[1] * new_matrix[1] + last_col;
+    let root = f64::hypot(new_matrix[0] - new_matrix[3], new_matrix[1] + new_matrix[2])
+        * f64::hypot(new_matrix[0] + new_matrix[3], new_matrix[1] - new_matrix[2]);
+
+    if root == 0.0 {
+        args[0] = f64::sqrt(square_sum / 2.0);
+        args[1] = args[0];
+        args[2] = 0.0;
+    } else {
+        let major_axis_square = (square_sum + root) / 2.0;
+        let minor_axis_square = (square_sum - root) / 2.0;
+        let major = f64::abs(major_axis_square - last_col) > 1e-6;
+        let sub = if major {
+            major_axis_square
+        } else {
+            minor_axis_square
+        } - last_col;
+        let rows_sum = new_matrix[0] * new_matrix[2] + new_matrix[1] * new_matrix[3];
+        let term_1 = new_matrix[0] * sub + new_matrix[2] * rows_sum;
+        let term_2 = new_matrix[1] * sub + new_matrix[3] * rows_sum;
+        let term = if major { term_1 } else { term_2 };
+        args[0] = major_axis_square.sqrt();
+        args[1] = minor_axis_square.sqrt();
+        let term_sign = if (major && term_2 < 0.0) || (!major && term_1 > 0.0) {
+            -1.0
+        } else {
+            1.0
+        };
+        args[2] = (term_sign * f64::acos(term / f64::hypot(term_1, term_2)) * 180.0)
+            / std::f64::consts::PI;
+    }
+
+    // NOTE: A reflection reverses the direction the arc is drawn in
+    if matrix.determinant() < 0.0 {
+        args[4] = 1.0 - args[4];
+    }
+}
+
+/// Returns the radii of the arc, scaled up to reach the end-point, along with the cosine and
+/// sine of it's rotation
+fn rotated_ellipse(args: &[f64; 7], point: [f64; 2]) -> [f64; 4] {
+    let rotation = (args[2] * std::f64::consts::PI) / 180.0;
+    let cos = f64::cos(rotation);
+    let sin = f64::sin(rotation);
+
+    let mut a = args[0];
+    let mut b = args[1];
+    if a > 0.0 && b > 0.0 {
+        let h = (point[0] * cos + point[1] * sin).powi(2) / (4.0 * a * a)
+            + (point[1] * cos - point[0] * sin).powi(2) / (4.0 * b * b);
+        if h > 1.0 {
+            let h = h.sqrt();
+            a *= h;
+            b *= h;
+        }
+    }
+    [a, b, cos, sin]
+}
+
+/// Swaps the radii of arcs rotated by more than 80 degrees, for a shorter rotation
+fn normalise_arc_rotation(args: &mut [f64; 7]) {
+    if f64::abs(args[2]) > 80.0 {
+        args.swap(0, 1);
+        args[2] += if args[2] > 0.0 { -90.0 } else { 90.0 };
+    }
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_transform() {
+    let transform = |definition: &str, matrix: [f64; 6]| {
+        let mut path = Path::parse(definition).unwrap();
+        path.transform(&Matrix(matrix));
+        path.0.iter_mut().for_each(|command| {
+            command
+                .args_mut()
+                .iter_mut()
+                .for_each(|arg| *arg = crate::math::to_fixed(*arg, 3));
+        });
+        path.to_string()
+    };
+
+    // Should translate absolute commands, but not relative commands
+    assert_eq!(
+        transform("M 0 0 L 10 10 l 10 0 Z", [1.0, 0.0, 0.0, 1.0, 5.0, 5.0]),
+        "M5 5L15 15l10 0Z"
+    );
+    // Should convert horizontal and vertical lines under rotation
+    assert_eq!(
+        transform(
+            "M 0 0 H 10 V 10 h -10 v -10",
+            [0.0, 1.0, -1.0, 0.0, 0.0, 0.0]
+        ),
+        "M0 0L0 10L-10 10l0-10l10 0"
+    );
+    // Should scale curves
+    assert_eq!(
+        transform(
+            "M 0 0 C 0 10 10 10 10 0 s 10 -10 10 0",
+            [2.0, 0.0, 0.0, 3.0, 0.0, 0.0]
+        ),
+        "M0 0C0 30 20 30 20 0s20-30 20 0"
+    );
+    // Should transform arcs under non-uniform scale
+    assert_eq!(
+        transform("M 0 0 A 5 5 0 0 1 10 0", [2.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+        "M0 0A10 5 0 0 1 20 0"
+    );
+    // Should transform arcs under skew
+    assert_eq!(
+        transform("M 0 0 A 5 5 0 0 1 10 0", [1.0, 0.0, 1.0, 1.0, 0.0, 0.0]),
+        "M0 0A8.09 3.09 31.717 0 1 10 0"
+    );
+    // Should flip the sweep of arcs under reflection
+    assert_eq!(
+        transform("M 0 0 A 5 5 0 0 1 10 0", [-1.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+        "M0 0A5 5 0 0 0-10 0"
+    );
+    assert_eq!(
+        transform("M 0 0 A 5 5 0 0 1 10 0", [0.0, 1.0, 1.0, 0.0, 0.0, 0.0]),
+        "M0 0A5 5 0 0 0 0 10"
+    );
+}
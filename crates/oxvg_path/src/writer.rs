@@ -0,0 +1,126 @@
+//! Writing path definitions one command at a time.
+use std::fmt::Write;
+
+use crate::command::{self, Data};
+
+/// Writes commands into a [`Write`] as they're given, producing the same minified definition
+/// as displaying a [`Path`](crate::Path) of those commands, without allocating.
+///
+/// Along with [`Commands`](crate::parser::Commands), this allows large definitions to be
+/// rewritten without holding every command in memory.
+///
+/// # Examples
+///
+/// ```
+/// use oxvg_path::{parser::Commands, writer::Writer};
+///
+/// let mut writer = Writer::new(String::new());
+/// for command in Commands::new("M 10 0.01 L 0.5 -1") {
+///     let command = command.unwrap();
+///     writer.write_command(&command.data, command.implicit).unwrap();
+/// }
+/// assert_eq!(&writer.into_inner(), "M10 .01.5-1");
+/// ```
+pub struct Writer<W> {
+    inner: W,
+    /// Whether any command has been written
+    started: bool,
+    /// The last argument of the previous command, if it had any
+    previous_arg: Option<f64>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a writer writing commands into `inner`
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            started: false,
+            previous_arg: None,
+        }
+    }
+
+    /// Writes the command, where [`Data::Implicit`] is written without its letter
+    ///
+    /// # Errors
+    /// If writing to the inner writer fails
+    pub fn write(&mut self, command: &Data) -> std::fmt::Result {
+        self.write_command(command, command.is_implicit())
+    }
+
+    /// Writes the command, omitting its letter when it's implicit
+    ///
+    /// # Errors
+    /// If writing to the inner writer fails
+    pub fn write_command(&mut self, command: &Data, implicit: bool) -> std::fmt::Result {
+        let command = command.as_explicit();
+        let args = command.args();
+        if implicit {
+            #[allow(clippy::float_cmp)] // This is fine for formatting
+            let is_space_needed = self.started
+                && (self.previous_arg.is_some_and(|n| (n % 1.0) == 0.0)
+                    || args.first().is_some_and(|n| n >= &1.0 || n == &0.0));
+            if is_space_needed && !args.first().is_some_and(|n| n < &0.0) {
+                self.inner.write_char(' ')?;
+            }
+        } else {
+            self.inner.write_char(command.id().into())?;
+        }
+        command::write_args(&mut self.inner, args)?;
+        self.started = true;
+        self.previous_arg = args.last().copied();
+        Ok(())
+    }
+
+    /// Returns the inner writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[test]
+#[cfg(feature = "default")]
+fn test_writer() {
+    use crate::{parser::Commands, Path};
+
+    let write = |definition: &str| {
+        let mut writer = Writer::new(String::new());
+        for command in &Path::parse(definition).unwrap().0 {
+            writer.write(command).unwrap();
+        }
+        writer.into_inner()
+    };
+    let stream = |definition: &str| {
+        let mut writer = Writer::new(String::new());
+        for command in Commands::new(definition) {
+            let command = command.unwrap();
+            writer
+                .write_command(&command.data, command.implicit)
+                .unwrap();
+        }
+        writer.into_inner()
+    };
+
+    for (definition, expected) in [
+        ("M 10 0.01 L 0.5 -1", "M10 .01.5-1"),
+        (
+            "M 10,50 C 1,2 3,4 5,6.5 .1 .2 .3 .4 .5 -.05176e-005",
+            "M10 50C1 2 3 4 5 6.5.1.2.3.4.5-5.176e-7",
+        ),
+        ("m-0,1a 25,25 -30 0,1 0,0", "m0 1a25 25-30 0 1 0 0"),
+        (
+            "M 0 0 h 1 2 3 z m 5 5 l -0.5 -0.5 0.25 0 z",
+            "M0 0h1 2 3Zm5 5-.5-.5.25 0Z",
+        ),
+        (
+            "M 1 1 L 2 2 2.5 .5 0 0 -0 -0 1e-7 1e-7 Q 1.5 .5 0.5 0 T 3 3 Z",
+            "M1 1 2 2 2.5.5 0 0 0 0 1e-7 1e-7Q1.5.5.5 0T3 3Z",
+        ),
+        (
+            "M0 0 A 10 10 0 1120 20 S 0.1 0.2 -0.3 0.4",
+            "M0 0A10 10 0 1 1 20 20S.1.2-.3.4",
+        ),
+    ] {
+        assert_eq!(write(definition), expected);
+        assert_eq!(stream(definition), expected);
+    }
+}